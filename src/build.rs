@@ -249,6 +249,7 @@ fn build_file<'ctx>(path: &Path, ctx: &mut CompCtx<'ctx>, opts: &BuildOptions) -
         return Err(101)
     }
     let pm = inkwell::passes::PassManager::create(());
+    opt::add_coroutine_passes(&ctx.module, &pm);
     opt::load_profile(Some(opts.profile), &pm);
     pm.run_on(&ctx.module);
     let target_machine = inkwell::targets::Target::from_triple(opts.triple).unwrap().create_target_machine(
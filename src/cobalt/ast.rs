@@ -18,6 +18,8 @@ impl Display for TreePrefix {
 pub trait AST {
     fn loc(&self) -> Location;
     fn is_const(&self) -> bool {false}
+    fn var_name(&self) -> Option<&DottedName> {None} // the variable this refers to, if it's just a name
+    fn children(&self) -> Vec<&dyn AST> {vec![]} // the expressions directly inside of this one
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type;
     fn codegen<'ctx>(& self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>);
     fn to_code(&self) -> String;
@@ -47,6 +49,11 @@ pub mod scope;
 pub mod misc;
 pub mod funcs;
 pub mod ops;
+pub mod flow;
+pub mod structs;
+pub mod coroutines;
+pub mod enums;
+pub mod traits;
 
 pub use vars::*;
 pub use groups::*;
@@ -55,3 +62,8 @@ pub use scope::*;
 pub use misc::*;
 pub use funcs::*;
 pub use ops::*;
+pub use flow::*;
+pub use structs::*;
+pub use coroutines::*;
+pub use enums::*;
+pub use traits::*;
@@ -0,0 +1,120 @@
+use crate::*;
+use inkwell::basic_block::BasicBlock;
+use inkwell::types::AsTypeRef;
+use inkwell::values::{AnyValue, AnyValueEnum, AsValueRef, BasicValue, FunctionValue, PointerValue};
+use llvm_sys::core::*;
+use llvm_sys::prelude::*;
+// inkwell can't represent token values, so calls involving them have to go through the C API
+unsafe fn raw_call<'ctx>(ctx: &CompCtx<'ctx>, name: &str, ret: LLVMTypeRef, params: &mut [LLVMTypeRef], args: &mut [LLVMValueRef]) -> LLVMValueRef {
+    let cname = std::ffi::CString::new(name).unwrap();
+    let fty = LLVMFunctionType(ret, params.as_mut_ptr(), params.len() as u32, 0);
+    let mut f = LLVMGetNamedFunction(ctx.module.as_mut_ptr(), cname.as_ptr());
+    if f.is_null() {f = LLVMAddFunction(ctx.module.as_mut_ptr(), cname.as_ptr(), fty);}
+    LLVMBuildCall2(ctx.builder.as_mut_ptr(), fty, f, args.as_mut_ptr(), args.len() as u32, b"\0".as_ptr() as *const _)
+}
+fn last_value<'ctx>(ctx: &CompCtx<'ctx>) -> Option<AnyValueEnum<'ctx>> {
+    ctx.builder.get_insert_block()?.get_last_instruction().map(|x| x.as_any_value_enum())
+}
+fn token_type<'ctx>(ctx: &CompCtx<'ctx>) -> LLVMTypeRef {unsafe {LLVMTokenTypeInContext(ctx.context.as_ctx_ref())}}
+pub fn cr_suspend<'ctx>(target: &CoroTarget<'ctx>, is_final: bool, resume: BasicBlock<'ctx>, ctx: &CompCtx<'ctx>) -> Option<()> {
+    let tok = token_type(ctx);
+    unsafe {raw_call(ctx, "llvm.coro.suspend", ctx.context.i8_type().as_type_ref(), &mut [tok, ctx.context.bool_type().as_type_ref()], &mut [LLVMConstNull(tok), ctx.context.bool_type().const_int(is_final as u64, false).as_value_ref()]);}
+    let state = last_value(ctx)?.into_int_value();
+    let i8t = ctx.context.i8_type();
+    ctx.builder.build_switch(state, target.suspend, &[(i8t.const_zero(), resume), (i8t.const_int(1, false), target.cleanup)]);
+    Some(())
+}
+pub fn cr_prologue<'ctx>(f: FunctionValue<'ctx>, ret: Type, ctx: &CompCtx<'ctx>) -> Option<(CoroTarget<'ctx>, PointerValue<'ctx>)> {
+    let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+    let tok = token_type(ctx);
+    let entry = ctx.context.append_basic_block(f, "entry");
+    let alloc = ctx.context.append_basic_block(f, "cr_alloc");
+    let begin = ctx.context.append_basic_block(f, "cr_begin");
+    let body = ctx.context.append_basic_block(f, "cr_body");
+    let cleanup = ctx.context.append_basic_block(f, "cr_cleanup");
+    let suspend = ctx.context.append_basic_block(f, "cr_suspend");
+    ctx.builder.position_at_end(entry);
+    let promise = ret.llvm_type(ctx).map(|t| {
+        let p = ctx.builder.build_alloca(t, "promise");
+        if let Some(inst) = p.as_instruction() {inst.set_alignment(ret.align().max(1) as u32).ok();}
+        p
+    });
+    let pi8 = promise.map_or(i8p.const_null(), |p| ctx.builder.build_pointer_cast(p, i8p, ""));
+    let id = unsafe {raw_call(ctx, "llvm.coro.id", tok, &mut [ctx.context.i32_type().as_type_ref(), i8p.as_type_ref(), i8p.as_type_ref(), i8p.as_type_ref()], &mut [ctx.context.i32_type().const_zero().as_value_ref(), pi8.as_value_ref(), i8p.const_null().as_value_ref(), i8p.const_null().as_value_ref()])};
+    unsafe {raw_call(ctx, "llvm.coro.alloc", ctx.context.bool_type().as_type_ref(), &mut [tok], &mut [id]);}
+    let needs_alloc = last_value(ctx)?.into_int_value();
+    ctx.builder.build_conditional_branch(needs_alloc, alloc, begin);
+    ctx.builder.position_at_end(alloc);
+    let size = ctx.builder.build_call(types::utils::intrinsic("llvm.coro.size.i64", ctx.context.i64_type().fn_type(&[], false), ctx), &[], "").try_as_basic_value().left()?;
+    let malloc = ctx.module.get_function("malloc").unwrap_or_else(|| ctx.module.add_function("malloc", i8p.fn_type(&[ctx.context.i64_type().into()], false), None));
+    let mem = ctx.builder.build_call(malloc, &[size.into()], "").try_as_basic_value().left()?;
+    ctx.builder.build_unconditional_branch(begin);
+    ctx.builder.position_at_end(begin);
+    let frame = ctx.builder.build_phi(i8p, "");
+    frame.add_incoming(&[(&i8p.const_null() as &dyn BasicValue, entry), (&mem as &dyn BasicValue, alloc)]);
+    unsafe {raw_call(ctx, "llvm.coro.begin", i8p.as_type_ref(), &mut [tok, i8p.as_type_ref()], &mut [id, frame.as_basic_value().as_value_ref()]);}
+    let hdl = last_value(ctx)?.into_pointer_value();
+    let target = CoroTarget {id, promise, ret, cleanup, suspend};
+    cr_suspend(&target, false, body, ctx)?;
+    ctx.builder.position_at_end(cleanup);
+    unsafe {raw_call(ctx, "llvm.coro.free", i8p.as_type_ref(), &mut [tok, i8p.as_type_ref()], &mut [id, hdl.as_value_ref()]);}
+    let mem = last_value(ctx)?.into_pointer_value();
+    let free = ctx.module.get_function("free").unwrap_or_else(|| ctx.module.add_function("free", ctx.context.void_type().fn_type(&[i8p.into()], false), None));
+    ctx.builder.build_call(free, &[mem.into()], "");
+    ctx.builder.build_unconditional_branch(suspend);
+    ctx.builder.position_at_end(suspend);
+    ctx.builder.build_call(types::utils::intrinsic("llvm.coro.end", ctx.context.bool_type().fn_type(&[i8p.into(), ctx.context.bool_type().into()], false), ctx), &[hdl.into(), ctx.context.bool_type().const_zero().into()], "");
+    ctx.builder.build_return(Some(&hdl));
+    ctx.builder.position_at_end(body);
+    Some((target, hdl))
+}
+pub fn cr_epilogue<'ctx>(target: &CoroTarget<'ctx>, ctx: &CompCtx<'ctx>) -> Option<()> {
+    let f = ctx.builder.get_insert_block()?.get_parent()?;
+    let after = ctx.context.append_basic_block(f, "cr_final");
+    cr_suspend(target, true, after, ctx)?;
+    ctx.builder.position_at_end(after);
+    ctx.builder.build_unreachable();
+    Some(())
+}
+pub struct YieldAST {
+    loc: Location,
+    pub val: Box<dyn AST>
+}
+impl YieldAST {
+    pub fn new(loc: Location, val: Box<dyn AST>) -> Self {YieldAST {loc, val}}
+}
+impl AST for YieldAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let target = if let Some(target) = ctx.coro.borrow().clone() {target} else {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 350, "yield can only be used inside of a coroutine".to_string())])
+        };
+        if ctx.is_const.get() {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "cannot yield in a constant context".to_string())])
+        }
+        let (val, mut errs) = self.val.codegen(ctx);
+        if !val.good.get() {return (Variable::error(), errs)}
+        let err = format!("cannot convert value of type {} to {}", val.data_type, target.ret);
+        match types::utils::impl_convert(val, target.ret.clone(), ctx) {
+            Some(val) => if let (Some(p), Some(v)) = (target.promise, val.value(ctx)) {
+                ctx.builder.build_store(p, v);
+            },
+            None => {
+                errs.push(Error::new(self.val.loc(), 311, err));
+                return (Variable::error(), errs)
+            }
+        }
+        let f = ctx.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let after = ctx.context.append_basic_block(f, "after_yield");
+        cr_suspend(&target, false, after, ctx);
+        ctx.builder.position_at_end(after);
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {format!("yield {}", self.val.to_code())}
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "yield")?;
+        print_ast_child(f, pre, &*self.val, true)
+    }
+}
@@ -0,0 +1,326 @@
+use crate::*;
+use inkwell::values::{BasicValue, BasicValueEnum::*};
+pub struct EnumDefAST {
+    loc: Location,
+    pub name: DottedName,
+    pub variants: Vec<(String, Location, Option<ParsedType>)>
+}
+impl EnumDefAST {
+    pub fn new(loc: Location, name: DottedName, variants: Vec<(String, Location, Option<ParsedType>)>) -> Self {EnumDefAST {loc, name, variants}}
+}
+impl AST for EnumDefAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let mut variants: Vec<(String, Option<Type>)> = vec![];
+        let mut locs: Vec<&Location> = vec![];
+        for (name, loc, pt) in self.variants.iter() {
+            if let Some(n) = variants.iter().position(|(n, _)| n == name) {
+                errs.push(Error::new(loc.clone(), 346, format!("duplicate variant {name} in enum {}", self.name)).note(Note::new(locs[n].clone(), "previously defined here".to_string())));
+                continue;
+            }
+            let t = pt.as_ref().map(|pt| {
+                let (t, mut es) = pt.into_type(ctx);
+                errs.append(&mut es);
+                match t {
+                    Ok(t) => {
+                        if t.llvm_type(ctx).is_none() || !t.size().is_static() {
+                            errs.push(Error::new(loc.clone(), 347, format!("variant {name} has a payload of type {t}, which has no fixed-size runtime representation")));
+                        }
+                        t
+                    },
+                    Err(IntoTypeError::NotAnInt(name)) => {
+                        errs.push(Error::new(loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+                        Type::Null
+                    },
+                    Err(IntoTypeError::NotCompileTime) => {
+                        errs.push(Error::new(loc.clone(), 312, format!("array size cannot be determined at compile time")));
+                        Type::Null
+                    },
+                    Err(IntoTypeError::NotAModule(name)) => {
+                        errs.push(Error::new(loc.clone(), 320, format!("{name} is not a module")));
+                        Type::Null
+                    },
+                    Err(IntoTypeError::DoesNotExist(name)) => {
+                        errs.push(Error::new(loc.clone(), 321, format!("{name} does not exist")));
+                        Type::Null
+                    },
+                    Err(IntoTypeError::NotAType(name)) => {
+                        errs.push(Error::new(loc.clone(), 322, format!("{name} is not a type")));
+                        Type::Null
+                    }
+                }
+            });
+            variants.push((name.clone(), t));
+            locs.push(loc);
+        }
+        let t = Type::Enum(ctx.mangle(&self.name), variants);
+        match ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable::metaval(InterData::Type(Box::new(t)), Type::TypeData)))) {
+            Ok(_) => {},
+            Err(RedefVariable::NotAModule(x, _)) => errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(x)))),
+            Err(RedefVariable::AlreadyExists(x, _) | RedefVariable::MergeConflict(x, _)) => errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", self.name.start(x))))
+        }
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("enum {} {{", self.name);
+        let mut count = self.variants.len();
+        for (name, _, t) in self.variants.iter() {
+            if let Some(t) = t {out += &format!("{name}({t})");}
+            else {out += name;}
+            if count != 1 {out += ", ";}
+            count -= 1;
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "enum: {}", self.name)?;
+        let mut count = self.variants.len();
+        for (name, _, t) in self.variants.iter() {
+            if let Some(t) = t {writeln!(f, "{pre}{}{name}({t})", if count == 1 {"└── "} else {"├── "})?;}
+            else {writeln!(f, "{pre}{}{name}", if count == 1 {"└── "} else {"├── "})?;}
+            count -= 1;
+        }
+        Ok(())
+    }
+}
+pub enum MatchPattern {
+    Variant(String, Option<String>),
+    Wildcard
+}
+impl std::fmt::Display for MatchPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MatchPattern::Variant(name, Some(bind)) => write!(f, "{name}({bind})"),
+            MatchPattern::Variant(name, None) => write!(f, "{name}"),
+            MatchPattern::Wildcard => write!(f, "_")
+        }
+    }
+}
+pub struct MatchAST {
+    loc: Location,
+    pub val: Box<dyn AST>,
+    pub arms: Vec<(Location, MatchPattern, Box<dyn AST>)>
+}
+impl MatchAST {
+    pub fn new(loc: Location, val: Box<dyn AST>, arms: Vec<(Location, MatchPattern, Box<dyn AST>)>) -> Self {MatchAST {loc, val, arms}}
+    fn bind<'ctx>(&self, pat: &MatchPattern, payload: Option<Variable<'ctx>>, ctx: &CompCtx<'ctx>) {
+        if let (MatchPattern::Variant(_, Some(name)), Some(var)) = (pat, payload) {
+            ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(var))).map_or((), |_| ());
+        }
+    }
+}
+impl AST for MatchAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {std::iter::once(&*self.val).chain(self.arms.iter().map(|(_, _, a)| &**a)).collect()}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        let mut it = self.arms.iter().map(|(_, _, x)| x.res_type(ctx));
+        if let Some(first) = it.next() {it.try_fold(first, |t, x| types::utils::common(&t, &x)).unwrap_or(Type::Null)}
+        else {Type::Null}
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let (mut val, mut errs) = self.val.codegen(ctx);
+        if !val.good.get() {return (Variable::error(), errs)}
+        while let Type::Borrow(b) = val.data_type {val.data_type = *b;}
+        let (ty, ptr, m) = match val.data_type.clone() {
+            Type::Reference(b, m) => (*b, if let Some(PointerValue(p)) = val.comp_val {Some(p)} else {None}, m),
+            t => (t, None, false)
+        };
+        let variants = if let Type::Enum(_, variants) = &ty {variants.clone()} else {
+            errs.push(Error::new(self.val.loc(), 356, format!("cannot match on a value of type {ty}")));
+            return (Variable::error(), errs)
+        };
+        // which variant each arm matches, or None for a wildcard
+        let mut targets: Vec<Option<usize>> = Vec::with_capacity(self.arms.len());
+        let mut covered: Vec<Option<&Location>> = vec![None; variants.len()];
+        let mut wildcard: Option<&Location> = None;
+        let mut wild_arm = None;
+        let mut good = true;
+        for (loc, pat, _) in self.arms.iter() {
+            match pat {
+                MatchPattern::Wildcard => {
+                    if let Some(prev) = wildcard {
+                        errs.push(Error::new(loc.clone(), 25, "unreachable match arm".to_string()).note(Note::new(prev.clone(), "a previous arm already matches any value".to_string())));
+                    }
+                    else if covered.iter().all(Option::is_some) {
+                        errs.push(Error::new(loc.clone(), 25, "unreachable match arm".to_string()).note(Note::new(loc.clone(), "all variants are matched by previous arms".to_string())));
+                    }
+                    else {
+                        wildcard = Some(loc);
+                        wild_arm = Some(targets.len());
+                    }
+                    targets.push(None);
+                },
+                MatchPattern::Variant(name, bind) => {
+                    let idx = if let Some(idx) = variants.iter().position(|(n, _)| n == name) {idx} else {
+                        errs.push(Error::new(loc.clone(), 348, format!("enum {ty} has no variant {name}")));
+                        good = false;
+                        targets.push(None);
+                        continue
+                    };
+                    if bind.is_some() && variants[idx].1.is_none() {
+                        errs.push(Error::new(loc.clone(), 355, format!("variant {name} of {ty} has no payload to bind")));
+                        good = false;
+                    }
+                    if let Some(prev) = wildcard.or(covered[idx]) {
+                        errs.push(Error::new(loc.clone(), 25, "unreachable match arm".to_string()).note(Note::new(prev.clone(), format!("{name} is already matched here"))));
+                    }
+                    else {covered[idx] = Some(loc);}
+                    targets.push(Some(idx));
+                }
+            }
+        }
+        let missing = variants.iter().zip(covered.iter()).filter(|(_, c)| c.is_none()).map(|((n, _), _)| n.as_str()).collect::<Vec<_>>();
+        if wildcard.is_none() && !missing.is_empty() {
+            errs.push(Error::new(self.loc.clone(), 349, format!("non-exhaustive match on value of type {ty}")).note(Note::new(self.val.loc(), format!("variant{} {} {} not matched", if missing.len() == 1 {""} else {"s"}, missing.join(", "), if missing.len() == 1 {"is"} else {"are"}))));
+            good = false;
+        }
+        if !good {return (Variable::error(), errs)}
+        // the first arm in source order that matches the tag, so the code agrees with the unreachable arm warnings
+        let arm_for = |tag: usize| targets.iter().position(|t| t.map_or(true, |t| t == tag));
+        let known = match &val.inter_val {
+            Some(InterData::Int(tag)) if ty.is_c_enum() => Some(*tag as usize),
+            Some(InterData::Array(v)) => if let Some(InterData::Int(tag)) = v.first() {Some(*tag as usize)} else {None},
+            _ => None
+        };
+        if let Some(tag) = known {
+            let n = arm_for(tag).unwrap();
+            let (_, pat, body) = &self.arms[n];
+            if ctx.is_const.get() || matches!(pat, MatchPattern::Variant(_, None) | MatchPattern::Wildcard) {
+                ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+                let payload = if let (Some(InterData::Array(v)), Some((_, Some(pt)))) = (&val.inter_val, variants.get(tag)) {
+                    v.get(1).map(|x| Variable::metaval(x.clone(), pt.clone()))
+                } else {None};
+                self.bind(pat, payload, ctx);
+                let (val, mut es) = body.codegen(ctx);
+                errs.append(&mut es);
+                ctx.map_vars(|v| v.parent.unwrap());
+                return (val, errs)
+            }
+        }
+        if ctx.is_const.get() {
+            errs.push(Error::new(self.val.loc(), 312, "matched value must be known at compile time in a constant context".to_string()));
+            return (Variable::error(), errs)
+        }
+        let f = if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {f} else {
+            errs.push(Error::new(self.loc.clone(), 312, "match expression must be known at compile time outside of a function".to_string()));
+            return (Variable::error(), errs)
+        };
+        let (tag, ptr) = if ty.is_c_enum() {
+            let tag = if let Some(p) = ptr {ctx.builder.build_load(p, "")}
+            else if let Some(v) = val.value(ctx) {v}
+            else {return (Variable::error(), errs)};
+            (tag.into_int_value(), None)
+        }
+        else {
+            let p = match (ptr, val.value(ctx), ty.llvm_type(ctx)) {
+                (Some(p), ..) => p,
+                (None, Some(v), Some(llt)) => {
+                    let a = ctx.builder.build_alloca(llt, "");
+                    ctx.builder.build_store(a, v);
+                    a
+                },
+                _ => return (Variable::error(), errs)
+            };
+            (ctx.builder.build_load(ctx.builder.build_struct_gep(p, 0, "").unwrap(), "").into_int_value(), Some(p))
+        };
+        let blocks = self.arms.iter().map(|_| ctx.context.append_basic_block(f, "match_arm")).collect::<Vec<_>>();
+        let mb = ctx.context.append_basic_block(f, "match_merge");
+        let default = if let Some(n) = wild_arm {blocks[n]} else {
+            let bb = ctx.context.append_basic_block(f, "match_none");
+            let old = ctx.builder.get_insert_block().unwrap();
+            ctx.builder.position_at_end(bb);
+            ctx.builder.build_unreachable();
+            ctx.builder.position_at_end(old);
+            bb
+        };
+        let tt = tag.get_type();
+        let cases = (0..variants.len()).filter_map(|tag| {
+            let n = arm_for(tag)?;
+            if targets[n].is_none() {None} else {Some((tt.const_int(tag as u64, false), blocks[n]))}
+        }).collect::<Vec<_>>();
+        ctx.builder.build_switch(tag, default, &cases);
+        let mut vals = vec![];
+        for (n, (_, pat, body)) in self.arms.iter().enumerate() {
+            let reachable = match targets[n] {
+                Some(idx) => arm_for(idx) == Some(n),
+                None => wild_arm == Some(n)
+            };
+            if !reachable {
+                blocks[n].remove_from_function().ok();
+                continue
+            }
+            ctx.builder.position_at_end(blocks[n]);
+            ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+            let payload = match (targets[n].and_then(|idx| variants[idx].1.as_ref()), ptr) {
+                (Some(pt), Some(p)) => types::utils::enum_payload(p, pt, ctx).map(|pp| Variable::compiled(PointerValue(pp), Type::Reference(Box::new(pt.clone()), m))),
+                _ => None
+            };
+            self.bind(pat, payload, ctx);
+            let (v, mut es) = body.codegen(ctx);
+            errs.append(&mut es);
+            ctx.map_vars(|v| v.parent.unwrap());
+            if flow::is_unreachable(ctx) {
+                if ctx.builder.get_insert_block().unwrap().get_terminator().is_none() {ctx.builder.build_unreachable();}
+            }
+            else {vals.push((v, ctx.builder.get_insert_block().unwrap(), body.loc()));}
+        }
+        let mut it = vals.iter();
+        let ty = if let Some((first, ..)) = it.next() {
+            it.try_fold(first.data_type.clone(), |t, (v, ..)| types::utils::common(&t, &v.data_type)).unwrap_or_else(|| {
+                if vals.iter().all(|(v, ..)| v.data_type != Type::Null) {
+                    let mut err = Error::new(self.loc.clone(), 311, "arms of match expression have incompatible types".to_string());
+                    for (v, _, loc) in vals.iter() {err.add_note(Note::new(loc.clone(), format!("arm has type {}", v.data_type)));}
+                    errs.push(err);
+                }
+                Type::Null
+            })
+        } else {Type::Null};
+        let mut incoming = vec![];
+        for (v, bb, _) in vals {
+            ctx.builder.position_at_end(bb);
+            let v = if ty == Type::Null {None} else {types::utils::impl_convert(v, ty.clone(), ctx).and_then(|v| v.value(ctx))};
+            let bb = ctx.builder.get_insert_block().unwrap();
+            if bb.get_terminator().is_none() {
+                ctx.builder.build_unconditional_branch(mb);
+                incoming.push((v, bb));
+            }
+        }
+        ctx.builder.position_at_end(mb);
+        (match ty.llvm_type(ctx) {
+            Some(llt) if !incoming.is_empty() => {
+                let phi = ctx.builder.build_phi(llt, "");
+                for (v, bb) in incoming {
+                    let v = v.unwrap_or_else(|| llt.const_zero());
+                    phi.add_incoming(&[(&v as &dyn BasicValue, bb)]);
+                }
+                Variable::compiled(phi.as_basic_value(), ty)
+            },
+            _ => Variable::metaval(InterData::Null, Type::Null)
+        }, errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("match {} {{", self.val.to_code());
+        let mut count = self.arms.len();
+        for (_, pat, body) in self.arms.iter() {
+            out += &format!("{pat} => {}", body.to_code());
+            if count != 1 {out += ", ";}
+            count -= 1;
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "match")?;
+        print_ast_child(f, pre, &*self.val, self.arms.is_empty())?;
+        let mut count = self.arms.len();
+        for (_, pat, body) in self.arms.iter() {
+            write!(f, "{pre}{}{pat} => ", if count == 1 {"└── "} else {"├── "})?;
+            pre.push(count == 1);
+            body.print_impl(f, pre)?;
+            pre.pop();
+            count -= 1;
+        }
+        Ok(())
+    }
+}
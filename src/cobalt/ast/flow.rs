@@ -0,0 +1,557 @@
+use crate::*;
+use inkwell::values::{BasicValue, BasicValueEnum::*};
+fn to_cond<'ctx>(val: Variable<'ctx>, loc: Location, ctx: &CompCtx<'ctx>) -> Result<Variable<'ctx>, Error> {
+    let err = format!("cannot convert value of type {} to bool", val.data_type);
+    types::utils::to_bool(val, ctx).ok_or_else(|| Error::new(loc, 311, err))
+}
+pub struct IfAST {
+    loc: Location,
+    pub cond: Box<dyn AST>,
+    pub if_true: Box<dyn AST>,
+    pub if_false: Option<Box<dyn AST>>
+}
+impl IfAST {
+    pub fn new(loc: Location, cond: Box<dyn AST>, if_true: Box<dyn AST>, if_false: Option<Box<dyn AST>>) -> Self {IfAST {loc, cond, if_true, if_false}}
+}
+impl AST for IfAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {std::iter::once(&*self.cond).chain(std::iter::once(&*self.if_true)).chain(self.if_false.as_deref()).collect()}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        if let Some(if_false) = self.if_false.as_ref() {types::utils::common(&self.if_true.res_type(ctx), &if_false.res_type(ctx)).unwrap_or(Type::Null)}
+        else {Type::Null}
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let (cond, mut errs) = self.cond.codegen(ctx);
+        let cond = match to_cond(cond, self.cond.loc(), ctx) {
+            Ok(cond) => cond,
+            Err(err) => {
+                errs.push(err);
+                return (Variable::error(), errs)
+            }
+        };
+        if let Some(InterData::Int(v)) = cond.inter_val {
+            return if v != 0 {
+                let (val, mut es) = self.if_true.codegen(ctx);
+                errs.append(&mut es);
+                (if self.if_false.is_some() {val} else {Variable::metaval(InterData::Null, Type::Null)}, errs)
+            }
+            else if let Some(if_false) = self.if_false.as_ref() {
+                let (val, mut es) = if_false.codegen(ctx);
+                errs.append(&mut es);
+                (val, errs)
+            }
+            else {(Variable::metaval(InterData::Null, Type::Null), errs)}
+        }
+        if ctx.is_const.get() {
+            errs.push(Error::new(self.cond.loc(), 312, "condition must be known at compile time in a constant context".to_string()));
+            return (Variable::error(), errs)
+        }
+        let f = if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {f} else {
+            errs.push(Error::new(self.loc.clone(), 312, "if expression must be known at compile time outside of a function".to_string()));
+            return (Variable::error(), errs)
+        };
+        let cond = if let Some(IntValue(v)) = cond.value(ctx) {v} else {return (Variable::error(), errs)};
+        let itb = ctx.context.append_basic_block(f, "if_true");
+        if let Some(if_false) = self.if_false.as_ref() {
+            let ifb = ctx.context.append_basic_block(f, "if_false");
+            let mb = ctx.context.append_basic_block(f, "merge");
+            ctx.builder.build_conditional_branch(cond, itb, ifb);
+            ctx.builder.position_at_end(itb);
+            let (tv, mut es) = self.if_true.codegen(ctx);
+            errs.append(&mut es);
+            let tb = ctx.builder.get_insert_block().unwrap();
+            // a branch that already ended in a terminator doesn't reach the merge block, and can't have conversions added after it
+            let t_live = !is_unreachable(ctx) && tb.get_terminator().is_none();
+            ctx.builder.position_at_end(ifb);
+            let (fv, mut es) = if_false.codegen(ctx);
+            errs.append(&mut es);
+            let fb = ctx.builder.get_insert_block().unwrap();
+            let f_live = !is_unreachable(ctx) && fb.get_terminator().is_none();
+            // a branch that can't reach the end doesn't contribute to the type of the expression
+            let ty = match (t_live, f_live) {
+                (true, false) => tv.data_type.clone(),
+                (false, true) => fv.data_type.clone(),
+                (false, false) => Type::Null,
+                (true, true) => types::utils::common(&tv.data_type, &fv.data_type).unwrap_or_else(|| {
+                    if tv.data_type != Type::Null && fv.data_type != Type::Null {errs.push(Error::new(self.loc.clone(), 311, format!("branches of if expression have incompatible types {} and {}", tv.data_type, fv.data_type))
+                        .note(Note::new(self.if_true.loc(), format!("true branch has type {}", tv.data_type)))
+                        .note(Note::new(if_false.loc(), format!("false branch has type {}", fv.data_type))));}
+                    Type::Null
+                })
+            };
+            ctx.builder.position_at_end(tb);
+            let tv = if ty == Type::Null || !t_live {None} else {types::utils::impl_convert(tv, ty.clone(), ctx).and_then(|v| v.value(ctx))};
+            let tb = ctx.builder.get_insert_block().unwrap();
+            if tb.get_terminator().is_none() {
+                if t_live {ctx.builder.build_unconditional_branch(mb);}
+                else {ctx.builder.build_unreachable();}
+            }
+            ctx.builder.position_at_end(fb);
+            let fv = if ty == Type::Null || !f_live {None} else {types::utils::impl_convert(fv, ty.clone(), ctx).and_then(|v| v.value(ctx))};
+            let fb = ctx.builder.get_insert_block().unwrap();
+            if fb.get_terminator().is_none() {
+                if f_live {ctx.builder.build_unconditional_branch(mb);}
+                else {ctx.builder.build_unreachable();}
+            }
+            ctx.builder.position_at_end(mb);
+            (match (ty.llvm_type(ctx), tv, fv) {
+                (Some(llt), Some(tv), Some(fv)) => {
+                    let phi = ctx.builder.build_phi(llt, "");
+                    phi.add_incoming(&[(&tv as &dyn BasicValue, tb), (&fv as &dyn BasicValue, fb)]);
+                    Variable::compiled(phi.as_basic_value(), ty)
+                },
+                (Some(_), Some(v), None) if !f_live => Variable::compiled(v, ty),
+                (Some(_), None, Some(v)) if !t_live => Variable::compiled(v, ty),
+                _ => Variable::metaval(InterData::Null, Type::Null)
+            }, errs)
+        }
+        else {
+            let mb = ctx.context.append_basic_block(f, "merge");
+            ctx.builder.build_conditional_branch(cond, itb, mb);
+            ctx.builder.position_at_end(itb);
+            let (_, mut es) = self.if_true.codegen(ctx);
+            errs.append(&mut es);
+            if ctx.builder.get_insert_block().unwrap().get_terminator().is_none() {
+                if is_unreachable(ctx) {ctx.builder.build_unreachable();}
+                else {ctx.builder.build_unconditional_branch(mb);}
+            }
+            ctx.builder.position_at_end(mb);
+            (Variable::metaval(InterData::Null, Type::Null), errs)
+        }
+    }
+    fn to_code(&self) -> String {
+        if let Some(if_false) = self.if_false.as_ref() {format!("if ({}) {} else {}", self.cond.to_code(), self.if_true.to_code(), if_false.to_code())}
+        else {format!("if ({}) {}", self.cond.to_code(), self.if_true.to_code())}
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "if")?;
+        print_ast_child(f, pre, &*self.cond, false)?;
+        if let Some(if_false) = self.if_false.as_ref() {
+            print_ast_child(f, pre, &*self.if_true, false)?;
+            print_ast_child(f, pre, &**if_false, true)
+        }
+        else {print_ast_child(f, pre, &*self.if_true, true)}
+    }
+}
+pub struct WhileAST {
+    loc: Location,
+    pub label: Option<String>,
+    pub cond: Box<dyn AST>,
+    pub body: Box<dyn AST>
+}
+impl WhileAST {
+    pub fn new(loc: Location, label: Option<String>, cond: Box<dyn AST>, body: Box<dyn AST>) -> Self {WhileAST {loc, label, cond, body}}
+}
+impl AST for WhileAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.cond, &*self.body]}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        if ctx.is_const.get() {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "loops cannot be evaluated at compile time".to_string())])
+        }
+        let f = if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {f} else {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "loops cannot be used outside of a function".to_string())])
+        };
+        let cb = ctx.context.append_basic_block(f, "while_cond");
+        let bb = ctx.context.append_basic_block(f, "while_body");
+        let eb = ctx.context.append_basic_block(f, "while_exit");
+        ctx.builder.build_unconditional_branch(cb);
+        ctx.builder.position_at_end(cb);
+        let (cond, mut errs) = self.cond.codegen(ctx);
+        match to_cond(cond, self.cond.loc(), ctx) {
+            Ok(Variable {inter_val: Some(InterData::Int(v)), ..}) => {ctx.builder.build_unconditional_branch(if v != 0 {bb} else {eb});},
+            Ok(cond) => if let Some(IntValue(v)) = cond.value(ctx) {ctx.builder.build_conditional_branch(v, bb, eb);} else {ctx.builder.build_unconditional_branch(eb);},
+            Err(err) => {
+                errs.push(err);
+                ctx.builder.build_unconditional_branch(eb);
+            }
+        }
+        ctx.builder.position_at_end(bb);
+        ctx.loops.borrow_mut().push(LoopTarget {label: self.label.clone(), cont: cb, brk: eb, depth: ctx.drops.borrow().len(), moves: vec![]});
+        let (_, mut es) = self.body.codegen(ctx);
+        errs.append(&mut es);
+        errs.append(&mut pop_loop(&self.loc, ctx));
+        if ctx.builder.get_insert_block().unwrap().get_terminator().is_none() {ctx.builder.build_unconditional_branch(cb);}
+        ctx.builder.position_at_end(eb);
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        if let Some(label) = self.label.as_ref() {format!("{label}: while ({}) {}", self.cond.to_code(), self.body.to_code())}
+        else {format!("while ({}) {}", self.cond.to_code(), self.body.to_code())}
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        if let Some(label) = self.label.as_ref() {writeln!(f, "while: {label}")?}
+        else {writeln!(f, "while")?}
+        print_ast_child(f, pre, &*self.cond, false)?;
+        print_ast_child(f, pre, &*self.body, true)
+    }
+}
+pub enum ForIter {
+    Range(Box<dyn AST>, Box<dyn AST>),
+    Each(Box<dyn AST>)
+}
+pub struct ForAST {
+    loc: Location,
+    pub label: Option<String>,
+    pub name: String,
+    pub iter: ForIter,
+    pub body: Box<dyn AST>
+}
+impl ForAST {
+    pub fn new(loc: Location, label: Option<String>, name: String, iter: ForIter, body: Box<dyn AST>) -> Self {ForAST {loc, label, name, iter, body}}
+}
+impl AST for ForAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {
+        match &self.iter {
+            ForIter::Range(s, e) => vec![&**s, &**e, &*self.body],
+            ForIter::Each(x) => vec![&**x, &*self.body]
+        }
+    }
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        if ctx.is_const.get() {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "loops cannot be evaluated at compile time".to_string())])
+        }
+        let f = if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {f} else {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "loops cannot be used outside of a function".to_string())])
+        };
+        let i64t = ctx.context.i64_type();
+        let mut errs = vec![];
+        // elem holds the base pointer, element type, and mutability when iterating over an array
+        let (start, end, ty, elem) = match &self.iter {
+            ForIter::Range(start, end) => {
+                let (sv, mut es) = start.codegen(ctx);
+                errs.append(&mut es);
+                let (ev, mut es) = end.codegen(ctx);
+                errs.append(&mut es);
+                if !(sv.good.get() && ev.good.get()) {return (Variable::error(), errs)}
+                let strip = |mut t: Type| {
+                    while let Type::Reference(b, _) | Type::Borrow(b) = t {t = *b;}
+                    t
+                };
+                let ty = match types::utils::bin_type(strip(sv.data_type.clone()), strip(ev.data_type.clone()), "+") {
+                    Type::IntLiteral => Type::Int(64, false),
+                    x @ Type::Int(..) => x,
+                    _ => {
+                        errs.push(Error::new(self.loc.clone(), 332, format!("bounds of a range must be integers, not {} and {}", sv.data_type, ev.data_type))
+                            .note(Note::new(start.loc(), format!("start of range has type {}", sv.data_type)))
+                            .note(Note::new(end.loc(), format!("end of range has type {}", ev.data_type))));
+                        return (Variable::error(), errs)
+                    }
+                };
+                match (types::utils::impl_convert(sv, ty.clone(), ctx).and_then(|v| v.value(ctx)), types::utils::impl_convert(ev, ty.clone(), ctx).and_then(|v| v.value(ctx))) {
+                    (Some(IntValue(s)), Some(IntValue(e))) => (s, e, ty, None),
+                    _ => return (Variable::error(), errs)
+                }
+            },
+            ForIter::Each(iter) => {
+                let (mut val, mut es) = iter.codegen(ctx);
+                errs.append(&mut es);
+                if !val.good.get() {return (Variable::error(), errs)}
+                while let Type::Borrow(b) = val.data_type {val.data_type = *b;}
+                let err = format!("cannot iterate over a value of type {}", val.data_type);
+                let zero = i64t.const_zero();
+                let arr = match (val.data_type, val.comp_val) {
+                    (Type::Reference(b, m), Some(PointerValue(v))) => match *b {
+                        Type::Array(b, Some(n)) => Some((unsafe {ctx.builder.build_in_bounds_gep(v, &[zero, zero], "")}, i64t.const_int(n, false), *b, m)),
+                        Type::Array(b, None) => {
+                            let fp = ctx.builder.build_load(v, "").into_struct_value();
+                            let p = ctx.builder.build_extract_value(fp, 0, "").unwrap().into_pointer_value();
+                            let len = ctx.builder.build_extract_value(fp, 1, "").unwrap().into_int_value();
+                            Some((p, len, *b, m))
+                        },
+                        _ => None
+                    },
+                    (Type::Array(b, None), Some(StructValue(fp))) => {
+                        let p = ctx.builder.build_extract_value(fp, 0, "").unwrap().into_pointer_value();
+                        let len = ctx.builder.build_extract_value(fp, 1, "").unwrap().into_int_value();
+                        Some((p, len, *b, false))
+                    },
+                    (Type::Array(b, Some(n)), v) => v.or_else(|| val.inter_val.as_ref().and_then(|v| v.into_compiled(ctx))).map(|v| {
+                        let a = ctx.builder.build_alloca(v.get_type(), "");
+                        ctx.builder.build_store(a, v);
+                        (unsafe {ctx.builder.build_in_bounds_gep(a, &[zero, zero], "")}, i64t.const_int(n, false), *b, false)
+                    }),
+                    _ => None
+                };
+                match arr {
+                    Some((p, len, b, m)) => (zero, len, Type::Int(64, true), Some((p, b, m))),
+                    None => {
+                        errs.push(Error::new(iter.loc(), 333, err));
+                        return (Variable::error(), errs)
+                    }
+                }
+            }
+        };
+        let unsigned = matches!(ty, Type::Int(_, true));
+        let pre = ctx.builder.get_insert_block().unwrap();
+        let cb = ctx.context.append_basic_block(f, "for_cond");
+        let bb = ctx.context.append_basic_block(f, "for_body");
+        let ib = ctx.context.append_basic_block(f, "for_inc");
+        let eb = ctx.context.append_basic_block(f, "for_exit");
+        ctx.builder.build_unconditional_branch(cb);
+        ctx.builder.position_at_end(cb);
+        let phi = ctx.builder.build_phi(start.get_type(), self.name.as_str());
+        phi.add_incoming(&[(&start as &dyn BasicValue, pre)]);
+        let iv = phi.as_basic_value().into_int_value();
+        let cmp = ctx.builder.build_int_compare(if unsigned {inkwell::IntPredicate::ULT} else {inkwell::IntPredicate::SLT}, iv, end, "");
+        ctx.builder.build_conditional_branch(cmp, bb, eb);
+        ctx.builder.position_at_end(bb);
+        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        let var = match elem {
+            Some((p, b, m)) => Variable::compiled(PointerValue(unsafe {ctx.builder.build_in_bounds_gep(p, &[iv], "")}), Type::Reference(Box::new(b), m)),
+            None => Variable::compiled(IntValue(iv), ty)
+        };
+        ctx.with_vars(|v| v.insert(&DottedName::local(self.name.clone()), Symbol::Variable(var))).map_or((), |_| ());
+        ctx.loops.borrow_mut().push(LoopTarget {label: self.label.clone(), cont: ib, brk: eb, depth: ctx.drops.borrow().len(), moves: vec![]});
+        let (_, mut es) = self.body.codegen(ctx);
+        errs.append(&mut es);
+        errs.append(&mut pop_loop(&self.loc, ctx));
+        ctx.map_vars(|v| v.parent.unwrap());
+        if ctx.builder.get_insert_block().unwrap().get_terminator().is_none() {ctx.builder.build_unconditional_branch(ib);}
+        ctx.builder.position_at_end(ib);
+        let one = start.get_type().const_int(1, false);
+        let next = if unsigned {ctx.builder.build_int_nuw_add(iv, one, "")} else {ctx.builder.build_int_nsw_add(iv, one, "")};
+        phi.add_incoming(&[(&next as &dyn BasicValue, ib)]);
+        ctx.builder.build_unconditional_branch(cb);
+        ctx.builder.position_at_end(eb);
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        let iter = match &self.iter {
+            ForIter::Range(start, end) => format!("{}..{}", start.to_code(), end.to_code()),
+            ForIter::Each(iter) => iter.to_code()
+        };
+        if let Some(label) = self.label.as_ref() {format!("{label}: for {} in {iter} {}", self.name, self.body.to_code())}
+        else {format!("for {} in {iter} {}", self.name, self.body.to_code())}
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        if let Some(label) = self.label.as_ref() {writeln!(f, "for {}: {label}", self.name)?}
+        else {writeln!(f, "for {}", self.name)?}
+        match &self.iter {
+            ForIter::Range(start, end) => {
+                print_ast_child(f, pre, &**start, false)?;
+                print_ast_child(f, pre, &**end, false)?;
+            },
+            ForIter::Each(iter) => print_ast_child(f, pre, &**iter, false)?
+        }
+        print_ast_child(f, pre, &*self.body, true)
+    }
+}
+// the body of a loop is only generated once, so moving a value declared outside of it would move it again on the next iteration
+fn pop_loop<'ctx>(loc: &Location, ctx: &CompCtx<'ctx>) -> Vec<Error> {
+    let moves = ctx.loops.borrow_mut().pop().map_or(vec![], |l| l.moves);
+    moves.into_iter().map(|name| Error::new(loc.clone(), 373, format!("{name} is moved inside of this loop, but it's declared outside of it")).note(Note::new(loc.clone(), "it would be used after being moved from on the next iteration".to_string()))).collect()
+}
+fn loop_target<'ctx>(label: Option<&String>, kw: &str, loc: Location, ctx: &CompCtx<'ctx>) -> Result<LoopTarget<'ctx>, Error> {
+    let loops = ctx.loops.borrow();
+    if let Some(label) = label {
+        loops.iter().rev().find(|x| x.label.as_ref() == Some(label)).cloned().ok_or_else(|| {
+            let err = Error::new(loc, 331, format!("{kw} refers to a loop labeled {label}, but no such loop encloses it"));
+            if loops.is_empty() {err}
+            else {err.note(Note::new(loc, format!("enclosing loops are labeled: {}", loops.iter().filter_map(|x| x.label.clone()).collect::<Vec<_>>().join(", "))))}
+        })
+    }
+    else {
+        loops.last().cloned().ok_or_else(|| Error::new(loc, 330, format!("{kw} cannot be used outside of a loop")))
+    }
+}
+// leaving a scope runs its deferred expressions and then drops its locals, innermost scope first
+pub fn exit_scopes<'ctx>(depth: usize, ctx: &CompCtx<'ctx>) -> Vec<Error> {
+    let mut errs = vec![];
+    let len = ctx.defers.borrow().len();
+    for i in (depth..len).rev() {
+        // the frame is taken out while it runs, so a jump inside of a deferred expression doesn't run it again
+        let defers = std::mem::take(&mut ctx.defers.borrow_mut()[i]);
+        for d in defers.iter().rev() {
+            // each edge gets a fresh copy of the captured scope, so moves made by one don't leak into the others or the enclosing scope
+            let mut frames = vec![];
+            ctx.map_vars(|mut v| {
+                while let Some(p) = v.parent.take() {
+                    frames.push(std::mem::take(&mut v.symbols));
+                    v = p;
+                }
+                Box::new(VarMap {parent: Some(v), symbols: d.scope.clone()})
+            });
+            // the scope is being left, so a move here doesn't carry over to another iteration of an enclosing loop
+            let loops = ctx.loops.replace(vec![]);
+            let (_, es) = d.val.codegen(ctx);
+            ctx.loops.replace(loops);
+            ctx.map_vars(|v| frames.into_iter().rev().fold(v.parent.unwrap(), |p, symbols| Box::new(VarMap {parent: Some(p), symbols})));
+            let mut reported = d.reported.borrow_mut();
+            for e in es {
+                if !reported.iter().any(|(l, c)| *l == e.loc && *c == e.code) {
+                    reported.push((e.loc.clone(), e.code));
+                    errs.push(e);
+                }
+            }
+        }
+        if let Some(frame) = ctx.defers.borrow_mut().get_mut(i) {*frame = defers;}
+        let drops = ctx.drops.borrow().get(i).cloned().unwrap_or_default();
+        types::utils::drop_live(&drops, ctx);
+    }
+    errs
+}
+// return, break and continue leave the builder in a fresh block with no predecessors, so anything after them is in a block nothing branches to
+pub fn is_unreachable<'ctx>(ctx: &CompCtx<'ctx>) -> bool {
+    if ctx.is_const.get() {return false}
+    ctx.builder.get_insert_block().map_or(false, |bb| bb.get_terminator().is_some() || (bb.get_first_use().is_none() && bb.get_parent().and_then(|f| f.get_first_basic_block()) != Some(bb)))
+}
+// whether a function being evaluated at compile time has already returned, so the rest of its body should be skipped
+pub fn const_returned<'ctx>(ctx: &CompCtx<'ctx>) -> bool {
+    ctx.is_const.get() && matches!(&*ctx.const_ret.borrow(), Some((_, Some(_))))
+}
+pub struct ReturnAST {
+    loc: Location,
+    pub val: Box<dyn AST>
+}
+impl ReturnAST {
+    pub fn new(loc: Location, val: Box<dyn AST>) -> Self {ReturnAST {loc, val}}
+}
+impl AST for ReturnAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        if ctx.is_const.get() {
+            let ret = if let Some((ret, _)) = ctx.const_ret.borrow().clone() {ret} else {
+                return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "cannot return in a constant context".to_string())])
+            };
+            let (val, mut errs) = self.val.codegen(ctx);
+            let err = format!("cannot convert value of type {} to {ret}", val.data_type);
+            match types::utils::impl_convert(val, ret.clone(), ctx) {
+                Some(Variable {inter_val: Some(v), ..}) => {ctx.const_ret.replace(Some((ret, Some(v))));},
+                Some(_) => errs.push(Error::new(self.val.loc(), 312, "returned value must be known at compile time".to_string())),
+                None => errs.push(Error::new(self.val.loc(), 311, err).note(Note::new(self.loc.clone(), format!("function returns a value of type {ret}"))))
+            }
+            return (Variable::metaval(InterData::Null, Type::Null), errs)
+        }
+        let target = if let Some(target) = ctx.ret.borrow().clone() {target} else {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 353, "return can only be used inside of a function".to_string())])
+        };
+        let (val, mut errs) = self.val.codegen(ctx);
+        let err = format!("cannot convert value of type {} to {}", val.data_type, target.ret);
+        let good = val.good.get();
+        if let Some(e) = types::utils::check_escape(&val, &target.ret, self.val.loc(), ctx) {errs.push(e);}
+        let val = if good {types::utils::impl_convert(val, target.ret.clone(), ctx)} else {None};
+        if good && val.is_none() {
+            errs.push(Error::new(self.val.loc(), 311, err).note(Note::new(self.loc.clone(), format!("function returns a value of type {}", target.ret))));
+        }
+        let val = val.and_then(|v| v.value(ctx));
+        errs.append(&mut exit_scopes(0, ctx));
+        let bb = ctx.builder.get_insert_block().unwrap();
+        if let Some(phi) = target.phi {
+            let v = val.unwrap_or_else(|| phi.as_basic_value().get_type().const_zero());
+            phi.add_incoming(&[(&v as &dyn BasicValue, bb)]);
+        }
+        ctx.builder.build_unconditional_branch(target.exit);
+        let f = bb.get_parent().unwrap();
+        ctx.builder.position_at_end(ctx.context.append_basic_block(f, "after_return"));
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {format!("return {}", self.val.to_code())}
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "return")?;
+        print_ast_child(f, pre, &*self.val, true)
+    }
+}
+pub struct BreakAST {
+    loc: Location,
+    pub label: Option<String>
+}
+impl BreakAST {
+    pub fn new(loc: Location, label: Option<String>) -> Self {BreakAST {loc, label}}
+}
+impl AST for BreakAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        match loop_target(self.label.as_ref(), "break", self.loc.clone(), ctx) {
+            Ok(target) => {
+                let errs = exit_scopes(target.depth, ctx);
+                ctx.builder.build_unconditional_branch(target.brk);
+                let f = ctx.builder.get_insert_block().unwrap().get_parent().unwrap();
+                ctx.builder.position_at_end(ctx.context.append_basic_block(f, "after_break"));
+                (Variable::metaval(InterData::Null, Type::Null), errs)
+            },
+            Err(err) => (Variable::error(), vec![err])
+        }
+    }
+    fn to_code(&self) -> String {
+        if let Some(label) = self.label.as_ref() {format!("break {label}")}
+        else {"break".to_string()}
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, _pre: &mut TreePrefix) -> std::fmt::Result {
+        if let Some(label) = self.label.as_ref() {writeln!(f, "break: {label}")}
+        else {writeln!(f, "break")}
+    }
+}
+pub struct ContinueAST {
+    loc: Location,
+    pub label: Option<String>
+}
+impl ContinueAST {
+    pub fn new(loc: Location, label: Option<String>) -> Self {ContinueAST {loc, label}}
+}
+impl AST for ContinueAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        match loop_target(self.label.as_ref(), "continue", self.loc.clone(), ctx) {
+            Ok(target) => {
+                let errs = exit_scopes(target.depth, ctx);
+                ctx.builder.build_unconditional_branch(target.cont);
+                let f = ctx.builder.get_insert_block().unwrap().get_parent().unwrap();
+                ctx.builder.position_at_end(ctx.context.append_basic_block(f, "after_continue"));
+                (Variable::metaval(InterData::Null, Type::Null), errs)
+            },
+            Err(err) => (Variable::error(), vec![err])
+        }
+    }
+    fn to_code(&self) -> String {
+        if let Some(label) = self.label.as_ref() {format!("continue {label}")}
+        else {"continue".to_string()}
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, _pre: &mut TreePrefix) -> std::fmt::Result {
+        if let Some(label) = self.label.as_ref() {writeln!(f, "continue: {label}")}
+        else {writeln!(f, "continue")}
+    }
+}
+pub struct DeferAST {
+    loc: Location,
+    pub val: std::rc::Rc<dyn AST>
+}
+impl DeferAST {
+    pub fn new(loc: Location, val: Box<dyn AST>) -> Self {DeferAST {loc, val: val.into()}}
+}
+impl AST for DeferAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        // nothing is generated here, the expression is generated again on each edge that leaves the scope, against the locals visible here
+        let scope = ctx.with_vars(|v| {
+            let mut frames = vec![];
+            let mut cur = &*v;
+            while let Some(p) = &cur.parent {
+                frames.push(&cur.symbols);
+                cur = p;
+            }
+            frames.into_iter().rev().flat_map(|f| f.iter().map(|(k, s)| (k.clone(), s.clone()))).collect()
+        });
+        match ctx.defers.borrow_mut().last_mut() {
+            Some(frame) => {
+                frame.push(DeferTarget {val: self.val.clone(), scope, reported: std::cell::RefCell::new(vec![])});
+                (Variable::metaval(InterData::Null, Type::Null), vec![])
+            },
+            None => (Variable::error(), vec![Error::new(self.loc.clone(), 354, "defer can only be used inside of a block or function".to_string())])
+        }
+    }
+    fn to_code(&self) -> String {format!("defer {}", self.val.to_code())}
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "defer")?;
+        print_ast_child(f, pre, &*self.val, true)
+    }
+}
@@ -1,7 +1,9 @@
 use crate::*;
 use std::cell::Cell;
+use std::rc::Rc;
 use inkwell::types::{BasicType, BasicMetadataTypeEnum, BasicTypeEnum::*};
 use inkwell::values::BasicValueEnum::*;
+use inkwell::values::AsValueRef;
 use inkwell::module::Linkage::*;
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ParamType {
@@ -9,65 +11,157 @@ pub enum ParamType {
     Mutable,
     Constant
 }
+#[derive(Clone)]
 pub struct FnDefAST {
     loc: Location,
+    local: bool, // set on generic definitions inside of a function, whose instances can't be shared by name
     pub name: DottedName,
+    pub generics: Vec<(String, Vec<DottedName>)>, // type parameters and their trait bounds
     pub ret: ParsedType,
-    pub params: Vec<(String, ParamType, ParsedType, Option<Box<dyn AST>>)>, // parameter, mutable, type, default
-    pub body: Box<dyn AST>,
-    pub annotations: Vec<(String, Option<String>)>
+    pub params: Vec<(String, ParamType, ParsedType, Option<Rc<dyn AST>>)>, // parameter, mutable, type, default
+    pub body: Rc<dyn AST>,
+    pub annotations: Vec<(String, Option<String>)>,
+    pub is_cr: bool
 }
 impl FnDefAST {
-    pub fn new(loc: Location, name: DottedName, ret: ParsedType, params: Vec<(String, ParamType, ParsedType, Option<Box<dyn AST>>)>, body: Box<dyn AST>, annotations: Vec<(String, Option<String>)>) -> Self {FnDefAST {loc, name, ret, params, body, annotations}}
-}
-impl AST for FnDefAST {
-    fn loc(&self) -> Location {self.loc.clone()}
-    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        let (ret, mut errs) = self.ret.into_type(ctx);
-        let ret = match ret {
-            Ok(t) => t,
-            Err(IntoTypeError::NotAnInt(name)) => {
-                errs.push(Error::new(self.loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
-                Type::Null
-            },
-            Err(IntoTypeError::NotCompileTime) => {
-                errs.push(Error::new(self.loc.clone(), 312, format!("array size cannot be determined at compile time")));
-                Type::Null
-            },
-            Err(IntoTypeError::NotAModule(name)) => {
-                errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
-                Type::Null
+    pub fn new(loc: Location, name: DottedName, generics: Vec<(String, Vec<DottedName>)>, ret: ParsedType, params: Vec<(String, ParamType, ParsedType, Option<Box<dyn AST>>)>, body: Box<dyn AST>, annotations: Vec<(String, Option<String>)>, is_cr: bool) -> Self {
+        let params = params.into_iter().map(|(n, pt, t, d)| (n, pt, t, d.map(Rc::from))).collect();
+        FnDefAST {loc, local: false, name, generics, ret, params, body: Rc::from(body), annotations, is_cr}
+    }
+    fn instantiate<'ctx>(&self, name: &DottedName, args: Vec<Type>, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut bound = vec![None; self.generics.len()];
+        for ((_, _, pt, _), t) in self.params.iter().zip(args.iter()) {infer_generic(pt, t, &self.generics, &mut bound);}
+        let missing = self.generics.iter().zip(bound.iter()).filter(|(_, t)| t.is_none()).map(|((g, _), _)| g.as_str()).collect::<Vec<_>>();
+        if missing.len() > 0 {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 325, format!("cannot infer type parameter{} {} of {}", if missing.len() == 1 {""} else {"s"}, missing.join(", "), self.name))])
+        }
+        let types = bound.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        // instances live at an absolute path next to the generic definition, so each list of types is only generated once
+        let mut ids = if name.global {vec![]} else {ctx.prefix.borrow().clone()};
+        ids.extend(name.ids.iter().cloned());
+        let last = ids.pop().unwrap_or_default();
+        let module = DottedName::absolute(ids.clone());
+        ids.push(format!("{last}[{}]", types.iter().map(Type::to_string).collect::<Vec<_>>().join(", ")));
+        if self.local {ids.last_mut().unwrap().push_str(format!("@{}", self.loc).as_str());}
+        let name = DottedName::absolute(ids);
+        if let Some(var) = ctx.with_vars(|v| v.lookup(&name).ok().and_then(Symbol::as_var).cloned()) {return (var, vec![])}
+        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        if module.ids.len() > 0 {
+            if let Some(syms) = ctx.with_vars(|v| v.lookup(&module).ok().and_then(Symbol::as_mod).cloned()) {ctx.with_vars(|v| v.symbols = syms);}
+        }
+        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        if !name.global && name.ids.len() == 1 {
+            ctx.with_vars(|v| v.insert(&DottedName::local(last.clone()), Symbol::Variable(Variable::error()))).map_or((), |_| ()); // recursive instantiation isn't supported
+        }
+        let mut errs = vec![];
+        for ((param, bounds), t) in self.generics.iter().zip(types) {
+            for b in bounds.iter() {
+                match ParsedType::Other(b.clone()).into_type(ctx).0 {
+                    Ok(Type::Trait(tr, _)) => if !types::utils::implements(&t, &tr, ctx) {
+                        errs.push(Error::new(self.loc.clone(), 363, format!("type {t} does not implement trait {b}, which is required by type parameter {param}")));
+                    },
+                    _ => errs.push(Error::new(self.loc.clone(), 365, format!("bound {b} on type parameter {param} is not a trait")))
+                }
+            }
+            ctx.with_vars(|v| v.insert(&DottedName::local(param.clone()), Symbol::Variable(Variable::metaval(InterData::Type(Box::new(t)), Type::TypeData)))).map_or((), |_| ());
+        }
+        let out = if errs.len() > 0 {(Variable::error(), errs)} else {self.gen(&name, ctx)};
+        ctx.map_vars(|v| v.parent.unwrap());
+        ctx.map_vars(|v| v.parent.unwrap());
+        out
+    }
+    // a function named like Foo.bar, where Foo is a type, is put in the associated module of that type
+    fn assoc_name<'ctx>(&self, ctx: &CompCtx<'ctx>) -> DottedName {
+        if self.name.ids.len() < 2 {return self.name.clone()}
+        let mut ids = self.name.ids.clone();
+        let last = ids.pop().unwrap();
+        match ParsedType::from_name(DottedName::new(ids, self.name.global)).into_type(ctx).0 {
+            Ok(t) => {
+                let mut name = types::utils::assoc_module(&t);
+                name.ids.push(last);
+                name
             },
-            Err(IntoTypeError::DoesNotExist(name)) => {
-                errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
-                Type::Null
+            Err(_) => self.name.clone()
+        }
+    }
+    fn bind_params<'ctx>(&self, f: inkwell::values::FunctionValue<'ctx>, params: &[(Type, bool)], ctx: &CompCtx<'ctx>) {
+        let mut param_count = 0;
+        for (name, (ty, is_const)) in self.params.iter().map(|x| &x.0).zip(params.iter()) {
+            if name.len() == 0 {
+                if !is_const {
+                    param_count += 1;
+                }
+                continue;
             }
-        };
-        Type::Function(Box::new(ret), self.params.iter().map(|(_, pt, ty, _)| ({
-            let (ty, mut es) = ty.into_type(ctx);
-            errs.append(&mut es);
-            match ty {
-                Ok(t) => t,
-                Err(IntoTypeError::NotAnInt(name)) => {
-                    errs.push(Error::new(self.loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
-                    Type::Null
-                },
-                Err(IntoTypeError::NotCompileTime) => {
-                    errs.push(Error::new(self.loc.clone(), 312, format!("array size cannot be determined at compile time")));
-                    Type::Null
+            if !is_const {
+                let param = f.get_nth_param(param_count).unwrap();
+                param.set_name(name.as_str());
+                ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {
+                    comp_val: Some(param),
+                    inter_val: None,
+                    data_type: ty.clone(),
+                    good: Cell::new(true)
+                }))).map_or((), |x| ());
+                param_count += 1;
+            }
+            else {
+                ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {
+                    comp_val: None,
+                    inter_val: None,
+                    data_type: ty.clone(),
+                    good: Cell::new(true)
+                }))).map_or((), |x| ());
+            }
+        }
+    }
+    // mutable parameters are copied to the stack so they can be assigned to, which needs the entry block to exist
+    fn spill_mut_params<'ctx>(&self, ctx: &CompCtx<'ctx>) {
+        for (name, pt, _, _) in self.params.iter() {
+            if *pt != ParamType::Mutable || name.len() == 0 {continue}
+            if let Some(Variable {comp_val: Some(val), data_type, ..}) = ctx.with_vars(|v| v.symbols.get(name).and_then(Symbol::as_var).cloned()) {
+                let a = ctx.builder.build_alloca(val.get_type(), name.as_str());
+                ctx.builder.build_store(a, val);
+                ctx.with_vars(|v| v.symbols.insert(name.clone(), Symbol::Variable(Variable::compiled(PointerValue(a), Type::Reference(Box::new(data_type), true)))));
+            }
+        }
+    }
+    // parameters taken by value are owned by the function, so ones that need to be dropped are tracked like locals
+    fn track_params<'ctx>(&self, ctx: &CompCtx<'ctx>) {
+        for (name, pt, _, _) in self.params.iter() {
+            if *pt == ParamType::Constant || name.len() == 0 {continue}
+            let var = if let Some(var) = ctx.with_vars(|v| v.symbols.get(name).and_then(Symbol::as_var).cloned()) {var} else {continue};
+            let dn = DottedName::local(name.clone());
+            match (var.comp_val, var.data_type) {
+                (Some(PointerValue(p)), Type::Reference(b, true)) if *pt == ParamType::Mutable => {
+                    types::utils::track_drop(&dn, p, &b, ctx);
+                    types::utils::track_local(&dn, p, self.loc.clone(), ctx);
                 },
-                Err(IntoTypeError::NotAModule(name)) => {
-                    errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
-                    Type::Null
+                (Some(val), t) if types::utils::needs_drop(&t, ctx) => {
+                    let a = ctx.builder.build_alloca(val.get_type(), name.as_str());
+                    ctx.builder.build_store(a, val);
+                    types::utils::track_drop(&dn, a, &t, ctx);
+                    types::utils::track_local(&dn, a, self.loc.clone(), ctx);
+                    ctx.with_vars(|v| v.symbols.insert(name.clone(), Symbol::Variable(Variable::compiled(PointerValue(a), Type::Reference(Box::new(t), false)))));
                 },
-                Err(IntoTypeError::DoesNotExist(name)) => {
-                    errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
-                    Type::Null
-                }
+                _ => {}
             }
-        }, pt == &ParamType::Constant)).collect())
+        }
     }
-    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+    // a destructor has to be callable on a mutable reference to the type it's associated with
+    fn check_drop<'ctx>(&self, name: &DottedName, val: &Variable<'ctx>, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) {
+        let full = if name.global {name.ids.clone()} else {ctx.prefix.borrow().iter().chain(name.ids.iter()).cloned().collect()};
+        let module = if let [m, d] = full.as_slice() {if m.starts_with('<') && d == "drop" {m} else {return}} else {return};
+        let tname = &module[1..(module.len() - 1)];
+        match &val.data_type {
+            Type::Function(ret, params) if **ret == Type::Null && matches!(params.as_slice(), [(Type::Reference(b, true), false)] if format!("<{b}>") == *module) => {
+                if let [(Type::Reference(b, _), _)] = params.as_slice() {
+                    if !matches!(**b, Type::Struct(..)) {errs.push(Error::new(self.loc.clone(), 368, format!("destructors can only be defined for struct types, not {tname}")));}
+                }
+            },
+            t => errs.push(Error::new(self.loc.clone(), 367, format!("destructor of {tname} must have type fn (&mut {tname}): null, but it has type {t}")))
+        }
+    }
+    fn gen<'ctx>(&self, name: &DottedName, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let (ret, mut errs) = self.ret.into_type(ctx);
         let ret = match ret {
             Ok(t) => t,
@@ -86,8 +180,13 @@ impl AST for FnDefAST {
             Err(IntoTypeError::DoesNotExist(name)) => {
                 errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                 Type::Null
+            },
+            Err(IntoTypeError::NotAType(name)) => {
+                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                Type::Null
             }
         };
+        let ret = if self.is_cr {Type::Coroutine(Box::new(ret))} else {ret};
         let fty = Type::Function(Box::new(ret), self.params.iter().map(|(_, pt, ty, _)| ({
             let (ty, mut es) = ty.into_type(ctx);
             errs.append(&mut es);
@@ -108,6 +207,10 @@ impl AST for FnDefAST {
                 Err(IntoTypeError::DoesNotExist(name)) => {
                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                     Type::Null
+                },
+                Err(IntoTypeError::NotAType(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                    Type::Null
                 }
             }
         }, pt == &ParamType::Constant)).collect());
@@ -116,6 +219,7 @@ impl AST for FnDefAST {
         let mut linkas = None;
         let mut is_extern = false;
         let mut cconv: Option<u32> = None;
+        let mut op: Option<String> = None;
         for (ann, arg) in self.annotations.iter() {
             match ann.as_str() {
                 "link" => {
@@ -203,66 +307,104 @@ impl AST for FnDefAST {
                         }
                     });
                 },
+                "op" => {
+                    if op.is_some() {
+                        errs.push(Error::new(self.loc.clone(), 424, "respecification of @op annotation".to_string()))
+                    }
+                    match arg.as_ref().map(|x| x.trim().trim_matches('"').trim()) {
+                        None | Some("") => errs.push(Error::new(self.loc.clone(), 423, "@op annotation requires an argument".to_string())),
+                        Some(x) => op = Some(x.to_string())
+                    }
+                },
                 x => errs.push(Error::new(self.loc.clone(), 410, format!("unknown annotation {x:?} for variable definition")))
             }
         }
+        let op = op.and_then(|x| self.op_kind(x, &mut errs));
+        // gen is only reached for a generic when instantiating it, and every module that uses an instance generates its own copy, so the linker has to merge them
+        if link_type.is_none() && self.generics.len() > 0 && !is_extern {link_type = Some(LinkOnceODR);}
+        let cfn = if self.is_cr || is_extern || self.params.iter().any(|(_, pt, _, _)| *pt == ParamType::Constant) {None} else {Some(ConstFnData {
+            path: ctx.prefix.borrow().clone(),
+            params: self.params.iter().map(|(name, _, _, _)| name.clone()).collect(),
+            body: self.body.to_code(),
+            ast: Some(self.body.clone())
+        })};
         let old_ip = ctx.builder.get_insert_block();
+        let old_loops = ctx.loops.replace(vec![]);
+        let old_coro = ctx.coro.replace(None);
+        let old_ret = ctx.ret.replace(None);
+        let old_drops = ctx.drops.replace(vec![vec![]]);
+        let old_defers = ctx.defers.replace(vec![vec![]]);
+        let old_borrows = ctx.borrows.replace(vec![BorrowFrame::default()]);
         let val = if let Type::Function(ref ret, ref params) = fty {
             match if let Some(llt) = ret.llvm_type(ctx) {
                 let mut good = true;
                 let ps = params.iter().filter_map(|(x, c)| if *c {None} else {Some(BasicMetadataTypeEnum::from(x.llvm_type(ctx).unwrap_or_else(|| {good = false; IntType(ctx.context.i8_type())})))}).collect::<Vec<_>>();
                 if good && !ctx.is_const.get() {
                     let ft = llt.fn_type(ps.as_slice(), false);
-                    let f = ctx.module.add_function(format!("{}", self.name).as_str(), ft, None);
+                    let f = ctx.module.add_function(linkas.clone().unwrap_or_else(|| ctx.mangle(name)).as_str(), ft, None);
                     f.set_call_conventions(cconv.unwrap_or(8));
                     if let Some(link) = link_type {
                         f.as_global_value().set_linkage(link)
                     }
                     ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
-                    {
-                        let mut param_count = 0;
-                        for (name, (ty, is_const)) in self.params.iter().map(|x| &x.0).zip(params.iter()) {
-                            if name.len() == 0 {
-                                if !is_const {
-                                    param_count += 1;
-                                }
-                                continue;
-                            }
-                            if !is_const {
-                                let param = f.get_nth_param(param_count).unwrap();
-                                param.set_name(name.as_str());
-                                ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {
-                                    comp_val: Some(param),
-                                    inter_val: None,
-                                    data_type: ty.clone(),
-                                    good: Cell::new(true)
-                                }))).map_or((), |x| ());
-                                param_count += 1;
-                            }
+                    self.bind_params(f, params, ctx);
+                    if let (false, Type::Coroutine(yt)) = (is_extern, &**ret) {
+                        if let Some((target, _)) = coroutines::cr_prologue(f, (**yt).clone(), ctx) {
+                            ctx.coro.replace(Some(target.clone()));
+                            self.spill_mut_params(ctx);
+                            self.track_params(ctx);
+                            // returning from a coroutine finishes it
+                            let exit = ctx.context.append_basic_block(f, "cr_exit");
+                            ctx.ret.replace(Some(RetTarget {ret: Type::Null, exit, phi: None}));
+                            let (_, mut es) = self.body.codegen(ctx);
+                            errs.append(&mut es);
+                            if flow::is_unreachable(ctx) {ctx.builder.build_unreachable();}
                             else {
-                                ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {
-                                    comp_val: None,
-                                    inter_val: None,
-                                    data_type: ty.clone(),
-                                    good: Cell::new(true)
-                                }))).map_or((), |x| ());
+                                errs.append(&mut flow::exit_scopes(0, ctx));
+                                ctx.builder.build_unconditional_branch(exit);
                             }
+                            if let Some(bb) = ctx.builder.get_insert_block() {exit.move_after(bb).ok();}
+                            ctx.builder.position_at_end(exit);
+                            coroutines::cr_epilogue(&target, ctx);
                         }
+                        ctx.map_vars(|v| v.parent.unwrap());
                     }
-                    if !is_extern {
+                    else if !is_extern {
                         let entry = ctx.context.append_basic_block(f, "entry");
+                        let exit = ctx.context.append_basic_block(f, "exit");
+                        ctx.builder.position_at_end(exit);
+                        let phi = ctx.builder.build_phi(llt, "");
                         ctx.builder.position_at_end(entry);
+                        self.spill_mut_params(ctx);
+                        self.track_params(ctx);
+                        ctx.ret.replace(Some(RetTarget {ret: (**ret).clone(), exit, phi: Some(phi)}));
                         let (body, mut es) = self.body.codegen(ctx);
                         errs.append(&mut es);
+                        if flow::is_unreachable(ctx) {ctx.builder.build_unreachable();}
+                        else {
+                            let err = format!("cannot convert value of type {} to {}", body.data_type, *ret);
+                            let is_null = body.data_type == Type::Null;
+                            if let Some(e) = types::utils::check_escape(&body, ret, self.body.loc(), ctx) {errs.push(e);}
+                            let val = types::utils::impl_convert(body, (&**ret).clone(), ctx).and_then(|v| v.value(ctx)).unwrap_or_else(|| {
+                                if is_null {
+                                    errs.push(Error::new(self.loc.clone(), 352, format!("function {} can reach the end of its body without returning a value", name)).note(Note::new(self.body.loc(), format!("body does not evaluate to a value of type {}", *ret))));
+                                }
+                                else {
+                                    errs.push(Error::new(self.loc.clone(), 311, err));
+                                }
+                                llt.const_zero()
+                            });
+                            errs.append(&mut flow::exit_scopes(0, ctx));
+                            phi.add_incoming(&[(&val as &dyn inkwell::values::BasicValue, ctx.builder.get_insert_block().unwrap())]);
+                            ctx.builder.build_unconditional_branch(exit);
+                        }
                         ctx.map_vars(|v| v.parent.unwrap());
-                        let err = format!("cannot convert value of type {} to {}", body.data_type, *ret);
-                        ctx.builder.build_return(Some(&types::utils::impl_convert(body, (&**ret).clone(), ctx).and_then(|v| v.comp_val).unwrap_or_else(|| {
-                            errs.push(Error::new(self.loc.clone(), 311, err));
-                            llt.const_zero()
-                        })));
+                        if let Some(bb) = ctx.builder.get_insert_block() {exit.move_after(bb).ok();}
+                        ctx.builder.position_at_end(exit);
+                        ctx.builder.build_return(Some(&phi.as_basic_value()));
                     }
                     let cloned = params.clone(); // Rust doesn't like me using params in the following closure
-                    ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
+                    ctx.with_vars(|v| v.insert(name, Symbol::Variable(Variable {
                         comp_val: Some(PointerValue(f.as_global_value().as_pointer_value())),
                         inter_val: Some(InterData::Function(FnData {
                             defaults: self.params.iter().zip(cloned).filter_map(|((_, _, _, d), (t, _))| d.as_ref().map(|a| {
@@ -283,7 +425,8 @@ impl AST for FnDefAST {
                                     errs.push(Error::new(a.loc(), 311, err));
                                     InterData::Null
                                 }
-                            })).collect()
+                            })).collect(),
+                            cfn: cfn.clone()
                         })),
                         data_type: fty,
                         good: Cell::new(true)
@@ -291,7 +434,7 @@ impl AST for FnDefAST {
                 }
                 else {
                     let cloned = params.clone(); // Rust doesn't like me using params in the following closure
-                    ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
+                    ctx.with_vars(|v| v.insert(name, Symbol::Variable(Variable {
                         comp_val: None,
                         inter_val: Some(InterData::Function(FnData {
                             defaults: self.params.iter().zip(cloned).filter_map(|((_, _, _, d), (t, _))| d.as_ref().map(|a| {
@@ -312,7 +455,8 @@ impl AST for FnDefAST {
                                     errs.push(Error::new(a.loc(), 311, err));
                                     InterData::Null
                                 }
-                            })).collect()
+                            })).collect(),
+                            cfn: cfn.clone()
                         })),
                         data_type: fty,
                         good: Cell::new(true)
@@ -324,20 +468,34 @@ impl AST for FnDefAST {
                 let ps = params.iter().filter_map(|(x, c)| if *c {None} else {Some(BasicMetadataTypeEnum::from(x.llvm_type(ctx).unwrap_or_else(|| {good = false; IntType(ctx.context.i8_type())})))}).collect::<Vec<_>>();
                 if good && !ctx.is_const.get() {
                     let ft = ctx.context.void_type().fn_type(ps.as_slice(), false);
-                    let f = ctx.module.add_function(format!("{}", self.name).as_str(), ft, None);
+                    let f = ctx.module.add_function(linkas.clone().unwrap_or_else(|| ctx.mangle(name)).as_str(), ft, None);
                     f.set_call_conventions(cconv.unwrap_or(8));
                     if let Some(link) = link_type {
                         f.as_global_value().set_linkage(link)
                     }
                     if !is_extern {
                         let entry = ctx.context.append_basic_block(f, "entry");
+                        let exit = ctx.context.append_basic_block(f, "exit");
                         ctx.builder.position_at_end(entry);
+                        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+                        self.bind_params(f, params, ctx);
+                        self.spill_mut_params(ctx);
+                        self.track_params(ctx);
+                        ctx.ret.replace(Some(RetTarget {ret: Type::Null, exit, phi: None}));
                         let (body, mut es) = self.body.codegen(ctx);
                         errs.append(&mut es);
+                        if flow::is_unreachable(ctx) {ctx.builder.build_unreachable();}
+                        else {
+                            errs.append(&mut flow::exit_scopes(0, ctx));
+                            ctx.builder.build_unconditional_branch(exit);
+                        }
+                        ctx.map_vars(|v| v.parent.unwrap());
+                        if let Some(bb) = ctx.builder.get_insert_block() {exit.move_after(bb).ok();}
+                        ctx.builder.position_at_end(exit);
                         ctx.builder.build_return(None);
                     }
                     let cloned = params.clone(); // Rust doesn't like me using params in the following closure
-                    ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
+                    ctx.with_vars(|v| v.insert(name, Symbol::Variable(Variable {
                         comp_val: Some(PointerValue(f.as_global_value().as_pointer_value())),
                         inter_val: Some(InterData::Function(FnData {
                             defaults: self.params.iter().zip(cloned).filter_map(|((_, _, _, d), (t, _))| d.as_ref().map(|a| {
@@ -358,7 +516,8 @@ impl AST for FnDefAST {
                                     errs.push(Error::new(a.loc(), 311, err));
                                     InterData::Null
                                 }
-                            })).collect()
+                            })).collect(),
+                            cfn: cfn.clone()
                         })),
                         data_type: fty,
                         good: Cell::new(true)
@@ -366,7 +525,7 @@ impl AST for FnDefAST {
                 }
                 else {
                     let cloned = params.clone(); // Rust doesn't like me using params in the following closure
-                    ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
+                    ctx.with_vars(|v| v.insert(name, Symbol::Variable(Variable {
                         comp_val: None,
                         inter_val: Some(InterData::Function(FnData {
                             defaults: self.params.iter().zip(cloned).filter_map(|((_, _, _, d), (t, _))| d.as_ref().map(|a| {
@@ -387,7 +546,8 @@ impl AST for FnDefAST {
                                     errs.push(Error::new(a.loc(), 311, err));
                                     InterData::Null
                                 }
-                            })).collect()
+                            })).collect(),
+                            cfn: cfn.clone()
                         })),
                         data_type: fty,
                         good: Cell::new(true)
@@ -396,7 +556,7 @@ impl AST for FnDefAST {
             }
             else {
                 let cloned = params.clone(); // Rust doesn't like me using params in the following closure
-                ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
+                ctx.with_vars(|v| v.insert(name, Symbol::Variable(Variable {
                     comp_val: None,
                     inter_val: Some(InterData::Function(FnData {
                         defaults: self.params.iter().zip(cloned).filter_map(|((_, _, _, d), (t, _))| d.as_ref().map(|a| {
@@ -417,7 +577,8 @@ impl AST for FnDefAST {
                                 errs.push(Error::new(a.loc(), 311, err));
                                 InterData::Null
                             }
-                        })).collect()
+                        })).collect(),
+                        cfn: cfn.clone()
                     })),
                     data_type: fty,
                     good: Cell::new(true)
@@ -425,11 +586,11 @@ impl AST for FnDefAST {
             } {
                 Ok(x) => (x.as_var().unwrap().clone(), errs),
                 Err(RedefVariable::NotAModule(x, _)) => {
-                    errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(x))));
+                    errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", name.start(x))));
                     (Variable::error(), errs)
                 },
                 Err(RedefVariable::AlreadyExists(x, _)) => {
-                    errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", self.name.start(x))));
+                    errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", name.start(x))));
                     (Variable::error(), errs)
                 },
                 Err(RedefVariable::MergeConflict(_, _)) => panic!("merge conflicts shouldn't be reachable when inserting a variable")
@@ -439,12 +600,127 @@ impl AST for FnDefAST {
             if let Some(bb) = old_ip {ctx.builder.position_at_end(bb);}
             else {ctx.builder.clear_insertion_position();}
         }
-        val
+        ctx.loops.replace(old_loops);
+        ctx.coro.replace(old_coro);
+        ctx.ret.replace(old_ret);
+        ctx.drops.replace(old_drops);
+        ctx.defers.replace(old_defers);
+        ctx.borrows.replace(old_borrows);
+        let (val, mut errs) = val;
+        if val.good.get() {self.check_drop(name, &val, ctx, &mut errs);}
+        if let (Some((op, kind)), Type::Function(_, params), true) = (op, &val.data_type, val.good.get()) {
+            let strip = |mut t: &Type| {
+                while let Type::Reference(b, _) | Type::Borrow(b) = t {t = &**b;}
+                t.clone()
+            };
+            let mut path = types::utils::assoc_module(&strip(&params[0].0));
+            path.ids.push(types::utils::op_key(&op, kind, params.get(1).map(|(t, _)| strip(t)).as_ref()));
+            if ctx.with_vars(|v| v.insert(&path, Symbol::Variable(val.clone()))).is_err() {
+                errs.push(Error::new(self.loc.clone(), 321, format!("operator {op} has already been defined for ({})", params.iter().map(|(t, _)| t.to_string()).collect::<Vec<_>>().join(", "))));
+            }
+        }
+        (val, errs)
+    }
+    fn op_kind(&self, op: String, errs: &mut Vec<Error>) -> Option<(String, types::utils::OpKind)> {
+        use types::utils::OpKind;
+        use parser::ops::*;
+        let (post, x) = if let Some(x) = op.strip_prefix("postfix") {(true, x.trim())} else {(false, op.strip_prefix("prefix").unwrap_or(&op).trim())};
+        let n = self.params.len();
+        let kind = if post || (n == 1 && x == "?") {OpKind::Postfix} else if n == 1 {OpKind::Prefix} else {OpKind::Binary};
+        let valid = match kind {
+            OpKind::Binary => x != "&&" && x != "||" && COBALT_BIN_OPS.iter().any(|o| matches!(o, Op(o) if *o == x)),
+            OpKind::Prefix => COBALT_PRE_OPS.contains(&x),
+            OpKind::Postfix => COBALT_POST_OPS.contains(&x)
+        };
+        if !valid {
+            errs.push(Error::new(self.loc.clone(), 425, format!("{kind} operator {x} cannot be overloaded")));
+            return None
+        }
+        let expected = if kind == OpKind::Binary {2} else {1};
+        if n != expected {
+            errs.push(Error::new(self.loc.clone(), 426, format!("{kind} operator function must take {expected} parameter{}, got {n}", if expected == 1 {""} else {"s"})));
+            return None
+        }
+        Some((x.to_string(), kind))
+    }
+}
+impl AST for FnDefAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.params.iter().filter_map(|(_, _, _, d)| d.as_deref()).chain(std::iter::once(&*self.body)).collect()}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        if self.generics.len() > 0 {return Type::Generic}
+        let (ret, mut errs) = self.ret.into_type(ctx);
+        let ret = match ret {
+            Ok(t) => t,
+            Err(IntoTypeError::NotAnInt(name)) => {
+                errs.push(Error::new(self.loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotCompileTime) => {
+                errs.push(Error::new(self.loc.clone(), 312, format!("array size cannot be determined at compile time")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotAModule(name)) => {
+                errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
+                Type::Null
+            },
+            Err(IntoTypeError::DoesNotExist(name)) => {
+                errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotAType(name)) => {
+                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                Type::Null
+            }
+        };
+        let ret = if self.is_cr {Type::Coroutine(Box::new(ret))} else {ret};
+        Type::Function(Box::new(ret), self.params.iter().map(|(_, pt, ty, _)| ({
+            let (ty, mut es) = ty.into_type(ctx);
+            errs.append(&mut es);
+            match ty {
+                Ok(t) => t,
+                Err(IntoTypeError::NotAnInt(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotCompileTime) => {
+                    errs.push(Error::new(self.loc.clone(), 312, format!("array size cannot be determined at compile time")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotAModule(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
+                    Type::Null
+                },
+                Err(IntoTypeError::DoesNotExist(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotAType(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                    Type::Null
+                }
+            }
+        }, pt == &ParamType::Constant)).collect())
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let name = self.assoc_name(ctx);
+        if self.generics.len() == 0 {return self.gen(&name, ctx)}
+        if let Some(args) = ctx.generic_args.replace(None) {return self.instantiate(&name, args, ctx)}
+        let ast = Rc::new(FnDefAST {local: ctx.ret.borrow().is_some(), ..self.clone()});
+        let data = GenericData {path: ctx.prefix.borrow().clone(), params: self.generics.iter().map(|(g, _)| g.clone()).collect(), code: self.to_code(), ast: Some(ast)};
+        match ctx.with_vars(|v| v.insert(&name, Symbol::Variable(Variable::metaval(InterData::Generic(data), Type::Generic)))) {
+            Ok(x) => (x.as_var().unwrap().clone(), vec![]),
+            Err(RedefVariable::NotAModule(x, _)) => (Variable::error(), vec![Error::new(self.loc.clone(), 320, format!("{} is not a module", name.start(x)))]),
+            Err(RedefVariable::AlreadyExists(x, _)) => (Variable::error(), vec![Error::new(self.loc.clone(), 321, format!("{} has already been defined", name.start(x)))]),
+            Err(RedefVariable::MergeConflict(_, _)) => panic!("merge conflicts shouldn't be reachable when inserting a variable")
+        }
     }
     fn to_code(&self) -> String {
         let mut out = "".to_string();
         for s in self.annotations.iter().map(|(name, arg)| ("@".to_string() + name.as_str() + arg.as_ref().map(|x| format!("({x})")).unwrap_or("".to_string()).as_str() + " ").to_string()) {out += s.as_str();}
-        out += format!("fn {}(", self.name).as_str();
+        out += format!("{} {}", if self.is_cr {"cr"} else {"fn"}, self.name).as_str();
+        if self.generics.len() > 0 {out += format!("[{}]", generic_list(&self.generics)).as_str();}
+        out.push('(');
         let mut len = self.params.len();
         for (param, param_ty, ty, default) in self.params.iter() {
             out += match param_ty {
@@ -464,7 +740,9 @@ impl AST for FnDefAST {
         out + format!("): {} = {}", self.ret, self.body.to_code()).as_str()
     }
     fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
-        write!(f, "function: {}(", self.name)?;
+        write!(f, "{}: {}", if self.is_cr {"coroutine"} else {"function"}, self.name)?;
+        if self.generics.len() > 0 {write!(f, "[{}]", generic_list(&self.generics))?;}
+        write!(f, "(")?;
         let mut len = self.params.len(); 
         for (param, param_ty, ty, default) in self.params.iter() {
             write!(f, "{}", match param_ty {
@@ -488,6 +766,81 @@ impl AST for FnDefAST {
         print_ast_child(f, pre, &*self.body, true)
     }
 }
+const CONST_DEPTH_LIMIT: usize = 256;
+const CONST_STEP_LIMIT: usize = 1 << 16;
+pub fn const_call<'ctx>(data: &ConstFnData, ret: Type, args: Vec<Variable<'ctx>>, loc: Location, ctx: &CompCtx<'ctx>) -> Result<Variable<'ctx>, Error> {
+    if args.iter().any(|v| v.inter_val.is_none()) {
+        return Err(Error::new(loc, 312, "function arguments must be known at compile time in a constant context".to_string()))
+    }
+    let depth = ctx.const_depth.get();
+    let steps = if depth == 0 {0} else {ctx.const_steps.get()};
+    if depth >= CONST_DEPTH_LIMIT {
+        return Err(Error::new(loc, 357, format!("compile-time evaluation exceeded the recursion limit of {CONST_DEPTH_LIMIT}")))
+    }
+    if steps >= CONST_STEP_LIMIT {
+        return Err(Error::new(loc, 358, format!("compile-time evaluation exceeded the limit of {CONST_STEP_LIMIT} function calls")))
+    }
+    ctx.const_steps.set(steps + 1);
+    let (body, mut errs) = if let Some(ast) = data.ast.clone() {(ast, vec![])} else {
+        let (toks, mut errs) = lex(data.body.as_str(), loc.clone(), &ctx.flags);
+        let (body, mut es) = parser::parse_expression(&toks, &ctx.flags);
+        errs.append(&mut es);
+        (Rc::from(body), errs)
+    };
+    ctx.const_depth.set(depth + 1);
+    let old_prefix = ctx.prefix.replace(data.path.clone());
+    let old_ret = ctx.ret.replace(None);
+    let old_const_ret = ctx.const_ret.replace(Some((ret.clone(), None)));
+    ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+    if data.path.len() > 0 {
+        if let Some(syms) = ctx.with_vars(|v| v.lookup(&DottedName::absolute(data.path.clone())).ok().and_then(Symbol::as_mod).cloned()) {ctx.with_vars(|v| v.symbols = syms);}
+    }
+    ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+    for (name, arg) in data.params.iter().zip(args) {
+        if name.len() == 0 {continue}
+        ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {comp_val: None, ..arg}))).map_or((), |_| ());
+    }
+    let (val, mut es) = body.codegen(ctx);
+    errs.append(&mut es);
+    ctx.map_vars(|v| v.parent.unwrap());
+    ctx.map_vars(|v| v.parent.unwrap());
+    let returned = ctx.const_ret.replace(old_const_ret).and_then(|(_, v)| v);
+    ctx.ret.replace(old_ret);
+    ctx.prefix.replace(old_prefix);
+    ctx.const_depth.set(depth);
+    // limit errors are passed through as-is so that deep recursion doesn't produce a note for every frame
+    if let Some(n) = errs.iter().position(|e| e.code == 357 || e.code == 358) {return Err(errs.swap_remove(n))}
+    let mut err = Error::new(loc.clone(), 359, "function call could not be evaluated at compile time".to_string());
+    for e in errs.into_iter().filter(|e| e.code >= 100) {
+        err.add_note(Note::new(e.loc, e.message));
+        err.notes.extend(e.notes);
+    }
+    if err.notes.len() > 0 {return Err(err)}
+    if let Some(v) = returned {return Ok(Variable::metaval(v, ret))}
+    if !val.good.get() {return Err(err)}
+    let dt = val.data_type.clone();
+    match types::utils::impl_convert(val, ret.clone(), ctx) {
+        Some(Variable {inter_val: Some(v), ..}) => Ok(Variable::metaval(v, ret)),
+        Some(_) => Err(err.note(Note::new(loc, "function body does not evaluate to a constant".to_string()))),
+        None => Err(err.note(Note::new(loc, format!("cannot convert value of type {dt} to {ret}"))))
+    }
+}
+fn generic_list(generics: &[(String, Vec<DottedName>)]) -> String {
+    generics.iter().map(|(g, bounds)| if bounds.len() == 0 {g.clone()} else {format!("{g}: {}", bounds.iter().map(DottedName::to_string).collect::<Vec<_>>().join(" + "))}).collect::<Vec<_>>().join(", ")
+}
+fn infer_generic(pt: &ParsedType, t: &Type, generics: &[(String, Vec<DottedName>)], bound: &mut [Option<Type>]) {
+    match (pt, t) {
+        (ParsedType::Other(name), _) if !name.global && name.ids.len() == 1 => if let Some(n) = generics.iter().position(|(g, _)| *g == name.ids[0]) {
+            let mut t = t;
+            while let Type::Reference(b, _) | Type::Borrow(b) = t {t = &**b;}
+            if bound[n].is_none() {bound[n] = Some(if *t == Type::IntLiteral {Type::Int(64, false)} else {t.clone()});}
+        },
+        (ParsedType::Pointer(p, _), Type::Pointer(b, _)) | (ParsedType::Reference(p, _), Type::Reference(b, _)) | (ParsedType::Borrow(p), Type::Borrow(b)) |
+        (ParsedType::UnsizedArray(p), Type::Array(b, _)) | (ParsedType::SizedArray(p, _), Type::Array(b, _)) => infer_generic(p, b, generics, bound),
+        (ParsedType::Reference(p, _) | ParsedType::Borrow(p), _) => infer_generic(p, t, generics, bound),
+        _ => {}
+    }
+}
 pub struct CallAST {
     loc: Location,
     pub target: Box<dyn AST>,
@@ -498,9 +851,13 @@ impl CallAST {
 }
 impl AST for CallAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {std::iter::once(&*self.target).chain(self.args.iter().map(|a| &**a)).collect()}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        if let Type::Function(ret, _) = self.target.res_type(ctx) {*ret}
-        else {Type::Null}
+        match self.target.res_type(ctx) {
+            Type::Function(ret, _) | Type::Coroutine(ret) | Type::Closure(ret, _) => *ret,
+            Type::Borrow(b) => if let Type::Closure(ret, _) = *b {*ret} else {Type::Null}, // methods bound to trait objects
+            _ => Type::Null
+        }
     }
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let (val, mut errs) = self.target.codegen(ctx);
@@ -533,6 +890,378 @@ impl AST for CallAST {
         Ok(())
     }
 }
+pub struct MethodCallAST {
+    loc: Location,
+    pub target: Box<dyn AST>,
+    pub name: String,
+    pub args: Vec<Box<dyn AST>>,
+    pub path: Option<DottedName> // set when the target and name form a path, which could also name a function in a module
+}
+impl MethodCallAST {
+    pub fn new(loc: Location, target: Box<dyn AST>, name: String, args: Vec<Box<dyn AST>>, path: Option<DottedName>) -> Self {MethodCallAST {loc, target, name, args, path}}
+    fn gen_args<'ctx>(&self, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) -> Vec<(Variable<'ctx>, Location)> {
+        self.args.iter().map(|a| {
+            let (arg, mut es) = a.codegen(ctx);
+            errs.append(&mut es);
+            (arg, a.loc())
+        }).collect()
+    }
+    fn receiver<'ctx>(&self, recv: Variable<'ctx>, f: &Variable<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) -> Result<Variable<'ctx>, Error> {
+        let (pt, is_const) = match &f.data_type {
+            Type::Function(_, params) => if let Some(p) = params.first() {p.clone()} else {
+                return Err(Error::new(self.loc.clone(), 362, format!("{t}.{} takes no parameters, so it cannot be called as a method", self.name)).note(Note::new(self.target.loc(), format!("try calling it as {t}.{}()", self.name))))
+            },
+            _ => return Ok(recv)
+        };
+        match pt {
+            Type::Reference(_, true) => if let Type::Reference(_, true) = recv.data_type {Ok(recv)} else {
+                Err(Error::new(self.loc.clone(), 362, format!("{t}.{} requires a mutable receiver", self.name)).note(Note::new(self.target.loc(), format!("receiver has type {}, but the method takes {pt}", recv.data_type))))
+            },
+            Type::Reference(_, false) if !matches!(recv.data_type, Type::Reference(..)) && !ctx.is_const.get() => {
+                // temporaries are spilled to the stack so that they can be passed by reference
+                let v = if let Some(v) = recv.value(ctx) {v} else {return Ok(recv)};
+                let a = ctx.builder.build_alloca(v.get_type(), "");
+                ctx.builder.build_store(a, v);
+                Ok(Variable {comp_val: Some(PointerValue(a)), inter_val: recv.inter_val, data_type: Type::Reference(Box::new(recv.data_type), false), good: Cell::new(true)})
+            },
+            _ if is_const && recv.inter_val.is_none() => Err(Error::new(self.loc.clone(), 362, format!("{t}.{} requires a constant receiver", self.name)).note(Note::new(self.target.loc(), "receiver is not known at compile time".to_string()))),
+            _ => Ok(recv)
+        }
+    }
+    fn not_found<'ctx>(&self, t: &Type, is_type: bool, ctx: &CompCtx<'ctx>) -> Error {
+        let mut err = if is_type {Error::new(self.loc.clone(), 361, format!("type {t} has no variant or associated function named {}", self.name))}
+        else {Error::new(self.loc.clone(), 361, format!("no method {} found for value of type {t}", self.name))};
+        let mut methods = ctx.with_vars(|v| v.lookup(&types::utils::assoc_module(t)).ok().and_then(Symbol::as_mod).map(|m| m.keys().filter(|k| !k.contains(['[', '('])).cloned().collect::<Vec<_>>())).unwrap_or_default();
+        methods.sort();
+        if methods.len() == 0 {err.add_note(Note::new(self.loc.clone(), format!("type {t} has no associated functions")));}
+        else {err.add_note(Note::new(self.loc.clone(), format!("candidates for {t} are {}", methods.join(", "))));}
+        let mut others = ctx.with_vars(|v| v.root().symbols.iter().filter_map(|(k, s)| if k.starts_with('<') && k.ends_with('>') && s.as_mod().map_or(false, |m| m.contains_key(&self.name)) {Some(k[1..(k.len() - 1)].to_string())} else {None}).collect::<Vec<_>>());
+        others.sort();
+        if others.len() > 0 {err.add_note(Note::new(self.loc.clone(), format!("{} is defined for {}", self.name, others.join(", "))));}
+        err
+    }
+}
+impl AST for MethodCallAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {std::iter::once(&*self.target).chain(self.args.iter().map(|a| &**a)).collect()}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        let ft = self.path.as_ref().map(|p| VarGetAST::new(self.loc.clone(), p.clone()).res_type(ctx)).filter(|t| *t != Type::Null).or_else(|| {
+            let mut t = self.target.res_type(ctx);
+            if let Some(ft) = types::utils::field_type(&t, &self.name) {return Some(ft)}
+            while let Type::Reference(b, _) | Type::Borrow(b) = t {t = *b;}
+            types::utils::assoc_function(&t, &self.name, ctx).map(|v| v.data_type)
+        });
+        match ft {
+            Some(Type::Function(ret, _) | Type::Coroutine(ret) | Type::Closure(ret, _)) => *ret,
+            Some(Type::Borrow(b)) => if let Type::Closure(ret, _) = *b {*ret} else {Type::Null}, // methods bound to trait objects
+            _ => Type::Null
+        }
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        if let Some(path) = self.path.as_ref().filter(|p| matches!(ctx.with_vars(|v| v.lookup(p)), Ok(Symbol::Variable(_)))) {
+            let (f, mut errs) = VarGetAST::new(self.loc.clone(), path.clone()).codegen(ctx);
+            let args = self.gen_args(ctx, &mut errs);
+            return (types::utils::call(f, self.loc.clone(), args, ctx).unwrap_or_else(|err| {errs.push(err); Variable::error()}), errs)
+        }
+        let (recv, mut errs) = self.target.codegen(ctx);
+        if !recv.good.get() {return (Variable::error(), errs)}
+        // fields (and variants or associated functions, when the target is a type) take precedence over methods
+        if let Some(f) = types::utils::field(recv.clone(), &self.name, ctx) {
+            let args = self.gen_args(ctx, &mut errs);
+            return (types::utils::call(f, self.loc.clone(), args, ctx).unwrap_or_else(|err| {errs.push(err); Variable::error()}), errs)
+        }
+        if let (Type::TypeData, Some(InterData::Type(t))) = (&recv.data_type, &recv.inter_val) {
+            errs.push(self.not_found(t, true, ctx));
+            return (Variable::error(), errs)
+        }
+        let mut t = &recv.data_type;
+        while let Type::Reference(b, _) | Type::Borrow(b) = t {t = &**b;}
+        let t = t.clone();
+        let f = if let Some(f) = types::utils::assoc_function(&t, &self.name, ctx) {f} else {
+            errs.push(self.not_found(&t, false, ctx));
+            return (Variable::error(), errs)
+        };
+        let recv = match self.receiver(recv, &f, &t, ctx) {
+            Ok(recv) => recv,
+            Err(err) => {
+                errs.push(err);
+                return (Variable::error(), errs)
+            }
+        };
+        let mut args = vec![(recv, self.target.loc())];
+        args.extend(self.gen_args(ctx, &mut errs));
+        (types::utils::call(f, self.loc.clone(), args, ctx).unwrap_or_else(|err| {errs.push(err); Variable::error()}), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("{}.{}(", self.target.to_code(), self.name);
+        let mut count = self.args.len();
+        for arg in self.args.iter() {
+            out += arg.to_code().as_str();
+            if count > 1 {
+                out += ", ";
+            }
+            count -= 1;
+        }
+        out + ")"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "method call: {}", self.name)?;
+        let mut count = self.args.len();
+        print_ast_child(f, pre, &*self.target, count == 0)?;
+        for arg in self.args.iter() {
+            print_ast_child(f, pre, &**arg, count <= 1)?;
+            count -= 1;
+        }
+        Ok(())
+    }
+}
+pub struct LambdaAST {
+    loc: Location,
+    pub params: Vec<(String, ParamType, ParsedType)>,
+    pub ret: Option<ParsedType>,
+    pub body: Box<dyn AST>
+}
+impl LambdaAST {
+    pub fn new(loc: Location, params: Vec<(String, ParamType, ParsedType)>, ret: Option<ParsedType>, body: Box<dyn AST>) -> Self {LambdaAST {loc, params, ret, body}}
+    fn into_type<'ctx>(&self, pt: &ParsedType, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) -> Type {
+        let (t, mut es) = pt.into_type(ctx);
+        errs.append(&mut es);
+        match t {
+            Ok(t) => t,
+            Err(IntoTypeError::NotAnInt(name)) => {
+                errs.push(Error::new(self.loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotCompileTime) => {
+                errs.push(Error::new(self.loc.clone(), 312, format!("array size cannot be determined at compile time")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotAModule(name)) => {
+                errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
+                Type::Null
+            },
+            Err(IntoTypeError::DoesNotExist(name)) => {
+                errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
+                Type::Null
+            },
+            Err(IntoTypeError::NotAType(name)) => {
+                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                Type::Null
+            }
+        }
+    }
+    fn sig<'ctx>(&self, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) -> (Type, Vec<Type>) {
+        let params = self.params.iter().map(|(_, _, pt)| self.into_type(pt, ctx, errs)).collect::<Vec<_>>();
+        let ret = if let Some(ret) = &self.ret {self.into_type(ret, ctx, errs)} else {
+            ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+            for ((name, _, _), t) in self.params.iter().zip(params.iter()) {
+                ctx.with_vars(|v| v.insert(&DottedName::local(name.clone()), Symbol::Variable(Variable {comp_val: None, inter_val: None, data_type: t.clone(), good: Cell::new(true)}))).map_or((), |_| ());
+            }
+            let mut t = self.body.res_type(ctx);
+            ctx.map_vars(|v| v.parent.unwrap());
+            while let Type::Reference(b, _) = t {t = *b;}
+            if t == Type::IntLiteral {Type::Int(64, false)} else {t}
+        };
+        (ret, params)
+    }
+    // anything named in the body that resolves to a value local to the enclosing function has to be captured
+    // globals and functions are constants, so they can be used from the closure directly
+    fn captures<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Vec<(String, Variable<'ctx>)> {
+        fn names<'a>(ast: &'a dyn AST, out: &mut Vec<&'a str>) {
+            if let Some(name) = ast.var_name().filter(|n| !n.global).and_then(|n| n.ids.first()) {
+                if !out.contains(&name.as_str()) {out.push(name.as_str());}
+            }
+            for child in ast.children() {names(child, out);}
+        }
+        let mut found = vec![];
+        names(&*self.body, &mut found);
+        found.into_iter().filter(|name| !self.params.iter().any(|(n, _, _)| n == name)).filter_map(|name| {
+            let var = ctx.with_vars(|v| v.lookup(&DottedName::local(name.to_string())).ok().and_then(Symbol::as_var).cloned())?;
+            var.comp_val.map_or(false, |v| unsafe {llvm_sys::core::LLVMIsConstant(v.as_value_ref())} == 0).then(|| (name.to_string(), var))
+        }).collect()
+    }
+    // the closure's type doesn't say what it captured, so the environment holds a function that drops the captured values and frees it
+    fn env_drop<'ctx>(&self, env_t: inkwell::types::StructType<'ctx>, caps: &[(String, inkwell::values::BasicValueEnum<'ctx>, Type)], ctx: &CompCtx<'ctx>) -> inkwell::values::FunctionValue<'ctx> {
+        let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+        let f = ctx.module.add_function(ctx.mangle(&DottedName::local("{closure}.drop".to_string())).as_str(), ctx.context.void_type().fn_type(&[i8p.into()], false), Some(Private));
+        let old_ip = ctx.builder.get_insert_block();
+        ctx.builder.position_at_end(ctx.context.append_basic_block(f, "entry"));
+        let env = f.get_first_param().unwrap().into_pointer_value();
+        let p = ctx.builder.build_pointer_cast(env, env_t.ptr_type(inkwell::AddressSpace::from(0u16)), "");
+        for (n, (_, _, t)) in caps.iter().enumerate() {
+            if !types::utils::needs_drop(t, ctx) {continue}
+            if let Ok(fp) = ctx.builder.build_struct_gep(p, n as u32 + 1, "") {types::utils::drop_value(fp, t, ctx);}
+        }
+        let free = ctx.module.get_function("free").unwrap_or_else(|| ctx.module.add_function("free", ctx.context.void_type().fn_type(&[i8p.into()], false), None));
+        ctx.builder.build_call(free, &[env.into()], "");
+        ctx.builder.build_return(None);
+        if let Some(bb) = old_ip {ctx.builder.position_at_end(bb);}
+        else {ctx.builder.clear_insertion_position();}
+        f
+    }
+}
+impl AST for LambdaAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.body]}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        let (ret, params) = self.sig(ctx, &mut vec![]);
+        Type::Closure(Box::new(ret), params)
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        if ctx.is_const.get() {
+            return (Variable::error(), vec![Error::new(self.loc.clone(), 312, "closures cannot be created in a constant context".to_string())])
+        }
+        let mut errs = vec![];
+        let (ret, params) = self.sig(ctx, &mut errs);
+        let ct = Type::Closure(Box::new(ret.clone()), params.clone());
+        let st = if let Some(StructType(st)) = ct.llvm_type(ctx) {st} else {
+            errs.push(Error::new(self.loc.clone(), 360, format!("cannot create a closure of type {ct}")));
+            return (Variable::error(), errs)
+        };
+        let mut caps = vec![];
+        let mut by_ref = None;
+        for (name, var) in self.captures(ctx) {
+            if !var.good.get() {
+                errs.push(Error::new(self.loc.clone(), 90, format!("{name} has been moved from and is now in an undefined state")));
+                continue
+            }
+            match var.data_type.clone() {
+                Type::Reference(b, true) => { // mutable variables are captured by reference
+                    if by_ref.is_none() {by_ref = Some(name.clone());}
+                    caps.push((name, var.comp_val.unwrap(), Type::Reference(b, true)))
+                },
+                t => {
+                    let t = if let Type::Reference(b, false) = t {*b} else {t};
+                    let err = format!("cannot capture {name} of type {} in a closure", var.data_type);
+                    match types::utils::impl_convert(var, t.clone(), ctx).and_then(|v| v.value(ctx)) {
+                        Some(v) => caps.push((name, v, t)),
+                        None => errs.push(Error::new(self.loc.clone(), 360, err))
+                    }
+                }
+            }
+        }
+        if errs.iter().any(|e| e.code >= 100) {return (Variable::error(), errs)}
+        let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+        let dt = ctx.context.void_type().fn_type(&[i8p.into()], false).ptr_type(inkwell::AddressSpace::from(0u16));
+        let env_t = ctx.context.struct_type(&std::iter::once(dt.into()).chain(caps.iter().map(|(_, v, _)| v.get_type())).collect::<Vec<_>>(), false);
+        let captured = caps.len() > 0;
+        let env = if captured {
+            let malloc = ctx.module.get_function("malloc").unwrap_or_else(|| ctx.module.add_function("malloc", i8p.fn_type(&[ctx.context.i64_type().into()], false), None));
+            let mem = ctx.builder.build_call(malloc, &[env_t.size_of().unwrap().into()], "").try_as_basic_value().left().unwrap().into_pointer_value();
+            let p = ctx.builder.build_pointer_cast(mem, env_t.ptr_type(inkwell::AddressSpace::from(0u16)), "");
+            ctx.builder.build_store(ctx.builder.build_struct_gep(p, 0, "").unwrap(), self.env_drop(env_t, &caps, ctx).as_global_value().as_pointer_value());
+            for (n, (_, v, _)) in caps.iter().enumerate() {ctx.builder.build_store(ctx.builder.build_struct_gep(p, n as u32 + 1, "").unwrap(), *v);}
+            // a closure holding a reference to a local can't be returned from the function
+            if let (Some(name), Some(frame)) = (by_ref, ctx.borrows.borrow_mut().last_mut()) {frame.closures.push((mem, DottedName::local(name), self.loc.clone()));}
+            mem
+        } else {i8p.const_null()};
+        let ft = st.get_field_type_at_index(0).unwrap().into_pointer_type().get_element_type().into_function_type();
+        let f = ctx.module.add_function(ctx.mangle(&DottedName::local("{closure}".to_string())).as_str(), ft, Some(Private));
+        let old_ip = ctx.builder.get_insert_block();
+        let old_loops = ctx.loops.replace(vec![]);
+        let old_coro = ctx.coro.replace(None);
+        let old_ret = ctx.ret.replace(None);
+        let old_drops = ctx.drops.replace(vec![vec![]]);
+        let old_defers = ctx.defers.replace(vec![vec![]]);
+        let old_borrows = ctx.borrows.replace(vec![BorrowFrame::default()]);
+        let entry = ctx.context.append_basic_block(f, "entry");
+        let exit = ctx.context.append_basic_block(f, "exit");
+        let llt = ret.llvm_type(ctx);
+        ctx.builder.position_at_end(exit);
+        let phi = llt.map(|t| ctx.builder.build_phi(t, ""));
+        ctx.builder.position_at_end(entry);
+        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        let envp = ctx.builder.build_pointer_cast(f.get_first_param().unwrap().into_pointer_value(), env_t.ptr_type(inkwell::AddressSpace::from(0u16)), "env");
+        for (n, (name, _, t)) in caps.into_iter().enumerate() {
+            let p = ctx.builder.build_struct_gep(envp, n as u32 + 1, "").unwrap();
+            // values captured by value are owned by the environment, so the body only gets a reference to them
+            let var = if let Type::Reference(..) = t {Variable::compiled(ctx.builder.build_load(p, name.as_str()), t)} else {Variable::compiled(PointerValue(p), Type::Reference(Box::new(t), false))};
+            ctx.with_vars(|vm| vm.insert(&DottedName::local(name), Symbol::Variable(var))).map_or((), |_| ());
+        }
+        for (n, ((name, pt, _), t)) in self.params.iter().zip(params.iter()).enumerate() {
+            let param = f.get_nth_param(n as u32 + 1).unwrap();
+            param.set_name(name.as_str());
+            let dn = DottedName::local(name.clone());
+            // parameters are owned by the closure the same way they're owned by a function, so they're tracked like locals
+            let var = if *pt == ParamType::Mutable || types::utils::needs_drop(t, ctx) {
+                let a = ctx.builder.build_alloca(param.get_type(), name.as_str());
+                ctx.builder.build_store(a, param);
+                types::utils::track_drop(&dn, a, t, ctx);
+                types::utils::track_local(&dn, a, self.loc.clone(), ctx);
+                Variable::compiled(PointerValue(a), Type::Reference(Box::new(t.clone()), *pt == ParamType::Mutable))
+            } else {Variable::compiled(param, t.clone())};
+            ctx.with_vars(|v| v.insert(&dn, Symbol::Variable(var))).map_or((), |_| ());
+        }
+        ctx.ret.replace(Some(RetTarget {ret: ret.clone(), exit, phi}));
+        let (body, mut es) = self.body.codegen(ctx);
+        errs.append(&mut es);
+        ctx.map_vars(|v| v.parent.unwrap());
+        if flow::is_unreachable(ctx) {ctx.builder.build_unreachable();}
+        else {
+            if let (Some(phi), Some(llt)) = (phi, llt) {
+                let err = format!("cannot convert value of type {} to {ret}", body.data_type);
+                if let Some(e) = types::utils::check_escape(&body, &ret, self.body.loc(), ctx) {errs.push(e);}
+                let val = types::utils::impl_convert(body, ret.clone(), ctx).and_then(|v| v.value(ctx)).unwrap_or_else(|| {
+                    errs.push(Error::new(self.body.loc(), 311, err));
+                    llt.const_zero()
+                });
+                errs.append(&mut flow::exit_scopes(0, ctx));
+                phi.add_incoming(&[(&val as &dyn inkwell::values::BasicValue, ctx.builder.get_insert_block().unwrap())]);
+            }
+            else {errs.append(&mut flow::exit_scopes(0, ctx));}
+            ctx.builder.build_unconditional_branch(exit);
+        }
+        if let Some(bb) = ctx.builder.get_insert_block() {exit.move_after(bb).ok();}
+        ctx.builder.position_at_end(exit);
+        let rv = phi.map(|p| p.as_basic_value());
+        ctx.builder.build_return(rv.as_ref().map(|v| v as &dyn inkwell::values::BasicValue));
+        if let Some(bb) = old_ip {ctx.builder.position_at_end(bb);}
+        else {ctx.builder.clear_insertion_position();}
+        ctx.loops.replace(old_loops);
+        ctx.coro.replace(old_coro);
+        ctx.ret.replace(old_ret);
+        ctx.drops.replace(old_drops);
+        ctx.defers.replace(old_defers);
+        ctx.borrows.replace(old_borrows);
+        let fp = f.as_global_value().as_pointer_value();
+        let val = if captured {
+            let v = ctx.builder.build_insert_value(st.get_undef(), fp, 0, "").unwrap();
+            ctx.builder.build_insert_value(v, env, 1, "").unwrap().into_struct_value()
+        } else {st.const_named_struct(&[fp.into(), env.into()])};
+        (Variable::compiled(StructValue(val), ct), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = "fn (".to_string();
+        let mut len = self.params.len();
+        for (param, param_ty, ty) in self.params.iter() {
+            if *param_ty == ParamType::Mutable {out += "mut ";}
+            out += format!("{param}: {ty}").as_str();
+            if len > 1 {
+                out += ", ";
+            }
+            len -= 1;
+        }
+        out.push(')');
+        if let Some(ret) = &self.ret {out += format!(": {ret}").as_str();}
+        out + format!(" = {}", self.body.to_code()).as_str()
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        write!(f, "closure(")?;
+        let mut len = self.params.len();
+        for (param, param_ty, ty) in self.params.iter() {
+            if *param_ty == ParamType::Mutable {write!(f, "mut ")?;}
+            write!(f, "{param}: {ty}")?;
+            if len > 1 {
+                write!(f, ", ")?;
+            }
+            len -= 1;
+        }
+        write!(f, ")")?;
+        if let Some(ret) = &self.ret {write!(f, ": {ret}")?;}
+        writeln!(f)?;
+        print_ast_child(f, pre, &*self.body, true)
+    }
+}
 pub struct IntrinsicAST {
     loc: Location,
     pub name: String,
@@ -544,9 +1273,10 @@ impl IntrinsicAST {
 impl AST for IntrinsicAST {
     fn loc(&self) -> Location {self.loc.clone()}
     fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
-    fn codegen<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         match self.name.as_str() {
-            "asm" => todo!("inline assembly isn't yet implemented"),
+            "asm" => asm_intrinsic(self.loc.clone(), self.args.as_ref().map(|x| x.as_str()).unwrap_or(""), ctx),
+            "destroy" => destroy_intrinsic(self.loc.clone(), self.args.as_ref().map(|x| x.as_str()).unwrap_or(""), ctx),
             x => (Variable::error(), vec![Error::new(self.loc.clone(), 391, format!("unknown intrinsic {x:?}"))])
         }
     }
@@ -563,3 +1293,190 @@ impl AST for IntrinsicAST {
         Ok(())
     }
 }
+fn asm_operand_name(toks: &[Token]) -> Option<DottedName> {
+    let mut name = DottedName::new(vec![], false);
+    let mut lwp = true;
+    for (n, tok) in toks.iter().enumerate() {
+        match &tok.data {
+            Special('.') if n == 0 => name.global = true,
+            Special('.') if !lwp => lwp = true,
+            Identifier(x) if lwp => {
+                name.ids.push(x.clone());
+                lwp = false;
+            },
+            _ => return None
+        }
+    }
+    if lwp {None} else {Some(name)}
+}
+fn destroy_intrinsic<'ctx>(loc: Location, args: &str, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+    let (toks, mut errs) = lex(args, loc.clone(), &ctx.flags);
+    if errs.len() > 0 {return (Variable::error(), errs)}
+    let name = if let Some(name) = asm_operand_name(&toks) {name} else {
+        errs.push(Error::new(loc, 397, "@destroy expects the name of a coroutine handle".to_string()));
+        return (Variable::error(), errs)
+    };
+    let (val, mut es) = VarGetAST::new(loc.clone(), name.clone()).codegen(ctx);
+    errs.append(&mut es);
+    if !val.good.get() {return (Variable::error(), errs)}
+    let mut t = &val.data_type;
+    while let Type::Borrow(b) | Type::Reference(b, _) = t {t = &**b;}
+    if !matches!(t, Type::Coroutine(_)) {
+        errs.push(Error::new(loc.clone(), 397, format!("cannot destroy value of type {}", val.data_type)).note(Note::new(loc.clone(), "only coroutine handles can be destroyed".to_string())));
+        return (Variable::error(), errs)
+    }
+    if ctx.is_const.get() {
+        errs.push(Error::new(loc, 312, "coroutines cannot be destroyed in a constant context".to_string()));
+        return (Variable::error(), errs)
+    }
+    // destroying a handle moves out of it, so it can't be resumed or destroyed again
+    let t = t.clone();
+    match types::utils::impl_convert(val, t, ctx) {
+        Some(Variable {comp_val: Some(PointerValue(h)), ..}) => {
+            let f = types::utils::intrinsic("llvm.coro.destroy", ctx.context.void_type().fn_type(&[h.get_type().into()], false), ctx);
+            ctx.builder.build_call(f, &[h.into()], "");
+        },
+        Some(_) => {},
+        None => errs.push(Error::new(loc.clone(), 397, format!("cannot destroy {name} because it can't be moved out of")).note(Note::new(loc, "only coroutine handles owned by a local can be destroyed".to_string())))
+    }
+    (Variable::metaval(InterData::Null, Type::Null), errs)
+}
+fn asm_intrinsic<'ctx>(loc: Location, args: &str, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+    use TokenData::*;
+    use inkwell::InlineAsmDialect;
+    use inkwell::values::{BasicValueEnum, BasicMetadataValueEnum, CallableValue};
+    let (toks, mut errs) = lex(args, loc.clone(), &ctx.flags);
+    if errs.len() > 0 {return (Variable::error(), errs)}
+    if ctx.is_const.get() {
+        errs.push(Error::new(loc, 312, "inline assembly cannot be used in a constant context".to_string()));
+        return (Variable::error(), errs)
+    }
+    if ctx.builder.get_insert_block().is_none() {
+        errs.push(Error::new(loc, 312, "inline assembly can only be used inside of a function".to_string()));
+        return (Variable::error(), errs)
+    }
+    let mut sections = toks.split(|t| t.data == Special(';'));
+    let mut head = sections.next().unwrap_or(&[]);
+    let mut dialect = None;
+    if let Some(Token {data: Identifier(x), loc: dloc}) = head.first() {
+        dialect = match x.as_str() {
+            "intel" => Some(InlineAsmDialect::Intel),
+            "att" => Some(InlineAsmDialect::ATT),
+            x => {
+                errs.push(Error::new(dloc.clone(), 392, format!("unknown assembly dialect {x:?}")).note(Note::new(dloc.clone(), "valid dialects are intel and att".to_string())));
+                None
+            }
+        };
+        head = &head[1..];
+    }
+    let mut template = vec![];
+    for tok in head {
+        match &tok.data {
+            Str(x) => template.push(x.as_str()),
+            x => errs.push(Error::new(tok.loc.clone(), 393, format!("expected a string in assembly template, got {x:?}")))
+        }
+    }
+    if template.len() == 0 {errs.push(Error::new(loc.clone(), 393, "expected an assembly template".to_string()))}
+    let mut outs = vec![];
+    let mut ins = vec![];
+    let mut clobbers = vec![];
+    for sec in sections {
+        let kind = match sec.first().map(|x| &x.data) {
+            None => continue,
+            Some(Identifier(x) | Keyword(x)) if x == "out" || x == "in" || x == "clobber" => x.as_str(),
+            Some(x) => {
+                errs.push(Error::new(sec[0].loc.clone(), 394, format!("expected out, in, or clobber in inline assembly, got {x:?}")));
+                continue
+            }
+        };
+        for item in sec[1..].split(|t| t.data == Special(',')) {
+            if item.len() == 0 {
+                errs.push(Error::new(sec[0].loc.clone(), 394, format!("empty operand in {kind} list")));
+                continue
+            }
+            let cons = if let Str(x) = &item[0].data {x.clone()} else {
+                errs.push(Error::new(item[0].loc.clone(), 394, format!("expected a constraint string, got {:?}", item[0].data)));
+                continue
+            };
+            match kind {
+                "clobber" => {
+                    if item.len() > 1 {errs.push(Error::new(item[1].loc.clone(), 394, format!("unexpected token {:?} after clobber", item[1].data)))}
+                    clobbers.push(if cons.starts_with('~') {cons} else {format!("~{{{cons}}}")});
+                },
+                "out" => {
+                    if !cons.starts_with('=') {errs.push(Error::new(item[0].loc.clone(), 394, format!("output constraint {cons:?} must start with '='")))}
+                    if let Some(name) = asm_operand_name(&item[1..]) {outs.push((cons, name, item[0].loc.clone()))}
+                    else {errs.push(Error::new(item[0].loc.clone(), 394, "output operand must be a variable name".to_string()))}
+                },
+                _ => {
+                    if cons.starts_with('=') || cons.starts_with('~') {errs.push(Error::new(item[0].loc.clone(), 394, format!("invalid input constraint {cons:?}")))}
+                    ins.push((cons, &item[1..], item[0].loc.clone()));
+                }
+            }
+        }
+    }
+    let triple = ctx.module.get_triple().as_str().to_string_lossy().into_owned();
+    let triple = if triple.len() == 0 {inkwell::targets::TargetMachine::get_default_triple().as_str().to_string_lossy().into_owned()} else {triple};
+    let arch = triple.split('-').next().unwrap_or("");
+    let is_x86 = matches!(arch, "x86" | "x86_64" | "i386" | "i486" | "i586" | "i686");
+    if !is_x86 && !["arm", "thumb", "aarch64", "arm64", "riscv", "mips", "powerpc", "ppc", "sparc", "s390x", "systemz", "hexagon", "avr", "msp430", "bpf"].iter().any(|x| arch.starts_with(x)) {
+        errs.push(Error::new(loc.clone(), 395, format!("inline assembly is not supported for target {triple}")));
+    }
+    if matches!(dialect, Some(InlineAsmDialect::Intel)) && !is_x86 {
+        errs.push(Error::new(loc.clone(), 395, format!("Intel assembly syntax is not supported for target {triple}")).note(Note::new(loc.clone(), "Intel syntax is only available on x86 targets".to_string())));
+    }
+    let mut out_ptrs = vec![];
+    let mut out_types = vec![];
+    for (_, name, oloc) in outs.iter() {
+        let (var, mut es) = VarGetAST::new(oloc.clone(), name.clone()).codegen(ctx);
+        errs.append(&mut es);
+        match (&var.data_type, var.comp_val) {
+            (Type::Reference(b, true), Some(PointerValue(ptr))) if b.llvm_type(ctx).is_some() => {
+                out_ptrs.push(ptr);
+                out_types.push(b.llvm_type(ctx).unwrap());
+            },
+            _ if var.good.get() => errs.push(Error::new(oloc.clone(), 396, format!("output operand {name} must be a mutable variable, got a value of type {}", var.data_type))),
+            _ => {}
+        }
+    }
+    let mut in_vals: Vec<BasicValueEnum> = vec![];
+    for (_, val, iloc) in ins.iter() {
+        match val.first().map(|x| &x.data) {
+            Some(Int(x)) if val.len() == 1 => in_vals.push(ctx.context.i64_type().const_int(*x as u64, true).into()),
+            Some(Float(x)) if val.len() == 1 => in_vals.push(ctx.context.f64_type().const_float(*x).into()),
+            _ => if let Some(name) = asm_operand_name(val) {
+                let (var, mut es) = VarGetAST::new(iloc.clone(), name.clone()).codegen(ctx);
+                errs.append(&mut es);
+                let dt = if let Type::Reference(b, _) | Type::Borrow(b) = &var.data_type {(**b).clone()} else {var.data_type.clone()};
+                let good = var.good.get();
+                match types::utils::impl_convert(var, dt.clone(), ctx).and_then(|v| v.value(ctx)) {
+                    Some(v) => in_vals.push(v),
+                    None if good => errs.push(Error::new(iloc.clone(), 396, format!("input operand {name} of type {dt} cannot be passed to inline assembly"))),
+                    None => {}
+                }
+            }
+            else {errs.push(Error::new(iloc.clone(), 394, "input operand must be a variable name or a numeric literal".to_string()))}
+        }
+    }
+    if errs.len() > 0 {return (Variable::error(), errs)}
+    let constraints = outs.iter().map(|x| x.0.clone()).chain(ins.iter().map(|x| x.0.clone())).chain(clobbers.into_iter()).collect::<Vec<_>>().join(",");
+    let in_types = in_vals.iter().map(|x| BasicMetadataTypeEnum::from(x.get_type())).collect::<Vec<_>>();
+    let in_vals = in_vals.into_iter().map(BasicMetadataValueEnum::from).collect::<Vec<_>>();
+    let fty = match out_types.len() {
+        0 => ctx.context.void_type().fn_type(&in_types, false),
+        1 => out_types[0].fn_type(&in_types, false),
+        _ => ctx.context.struct_type(&out_types, false).fn_type(&in_types, false)
+    };
+    let asm = ctx.context.create_inline_asm(fty, template.join("\n"), constraints, true, false, dialect, false);
+    let res = ctx.builder.build_call(CallableValue::try_from(asm).unwrap(), &in_vals, "");
+    if let Some(res) = res.try_as_basic_value().left() {
+        if out_ptrs.len() == 1 {ctx.builder.build_store(out_ptrs[0], res);}
+        else {
+            for (n, ptr) in out_ptrs.into_iter().enumerate() {
+                let v = ctx.builder.build_extract_value(res.into_struct_value(), n as u32, "").unwrap();
+                ctx.builder.build_store(ptr, v);
+            }
+        }
+    }
+    (Variable::metaval(InterData::Null, Type::Null), errs)
+}
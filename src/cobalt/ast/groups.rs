@@ -5,16 +5,56 @@ pub struct BlockAST {
 }
 impl AST for BlockAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.vals.last().map(|x| x.res_type(ctx)).unwrap_or(Type::Null)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        ctx.drops.borrow_mut().push(vec![]);
+        ctx.defers.borrow_mut().push(vec![]);
+        ctx.borrows.borrow_mut().push(BorrowFrame::default());
         let mut out = Variable::metaval(InterData::Null, Type::Null);
         let mut errs = vec![];
+        let mut dead = if flow::is_unreachable(ctx) {None} else {Some(None)};
         for val in self.vals.iter() {
+            if flow::const_returned(ctx) {break}
+            if let Some(Some(loc)) = dead {
+                if val.loc() != Location::null() {
+                    errs.push(Error::new(val.loc(), 24, "unreachable code".to_string()).note(Note::new(loc, "any code following this expression is unreachable".to_string())));
+                    dead = None;
+                }
+            }
             let (ast, mut es) = val.codegen(ctx);
             out = ast;
             errs.append(&mut es);
+            if dead == Some(None) && flow::is_unreachable(ctx) {dead = Some(Some(val.loc()));}
         }
+        // a trait object made from one of the block's locals would point at it after it goes out of scope
+        if matches!(&out.data_type, Type::Trait(..)) || matches!(&out.data_type, Type::Reference(b, _) if matches!(**b, Type::Trait(..))) {
+            let root = types::utils::object_root(&out, ctx).filter(|(_, r, _)| ctx.borrows.borrow().last().map_or(false, |f| f.locals.iter().any(|(_, l, _)| l == r)));
+            if let Some((name, _, def)) = root {
+                errs.push(Error::new(self.vals.last().map_or(self.loc.clone(), |v| v.loc()), 371, format!("trait object pointing to {name} outlives it")).note(Note::new(def, format!("{name} is defined here"))));
+                out = Variable::error();
+            }
+        }
+        // the block's locals go out of scope here, so a reference to one of them becomes a copy, or a move if it needs to be dropped
+        if let (Type::Reference(b, _), Some(inkwell::values::BasicValueEnum::PointerValue(p))) = (&out.data_type, out.comp_val) {
+            let root = types::utils::borrow_root(p, ctx).filter(|(_, r, _)| ctx.borrows.borrow().last().map_or(false, |f| f.locals.iter().any(|(_, l, _)| l == r)));
+            if let Some((name, _, def)) = root {
+                let t = (**b).clone();
+                let loc = self.vals.last().map_or(self.loc.clone(), |v| v.loc());
+                out = if types::utils::needs_drop(&t, ctx) || t.register() {
+                    types::utils::impl_convert(out, t, ctx).unwrap_or_else(|| {
+                        errs.push(Error::new(loc, 371, format!("reference to {name} outlives it")).note(Note::new(def, format!("{name} is defined here"))));
+                        Variable::error()
+                    })
+                } else {Variable::compiled(ctx.builder.build_load(p, ""), t)};
+            }
+        }
+        let depth = ctx.drops.borrow().len() - 1;
+        if !flow::is_unreachable(ctx) {errs.append(&mut flow::exit_scopes(depth, ctx));}
+        ctx.drops.borrow_mut().pop();
+        ctx.defers.borrow_mut().pop();
+        ctx.borrows.borrow_mut().pop();
         ctx.map_vars(|v| v.parent.unwrap());
         (out, errs)
     }
@@ -47,14 +87,24 @@ pub struct GroupAST {
 }
 impl AST for GroupAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.vals.last().map(|x| x.res_type(ctx)).unwrap_or(Type::Null)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let mut out = Variable::metaval(InterData::Null, Type::Null);
         let mut errs = vec![];
+        let mut dead = if flow::is_unreachable(ctx) {None} else {Some(None)};
         for val in self.vals.iter() {
+            if flow::const_returned(ctx) {break}
+            if let Some(Some(loc)) = dead {
+                if val.loc() != Location::null() {
+                    errs.push(Error::new(val.loc(), 24, "unreachable code".to_string()).note(Note::new(loc, "any code following this expression is unreachable".to_string())));
+                    dead = None;
+                }
+            }
             let (ast, mut es) = val.codegen(ctx);
             out = ast;
             errs.append(&mut es);
+            if dead == Some(None) && flow::is_unreachable(ctx) {dead = Some(Some(val.loc()));}
         }
         (out, errs)
     }
@@ -87,6 +137,7 @@ pub struct TopLevelAST {
 }
 impl AST for TopLevelAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
     fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let mut errs = vec![];
@@ -1,5 +1,6 @@
 use crate::*;
 use inkwell::values::BasicValueEnum::*;
+use inkwell::types::BasicType;
 pub struct IntLiteralAST {
     loc: Location,
     pub val: i128,
@@ -51,6 +52,23 @@ impl AST for IntLiteralAST {
         else {writeln!(f)}
     }
 }
+pub struct BoolLiteralAST {
+    loc: Location,
+    pub val: bool
+}
+impl BoolLiteralAST {
+    pub fn new(loc: Location, val: bool) -> Self {BoolLiteralAST {loc, val}}
+}
+impl AST for BoolLiteralAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn is_const(&self) -> bool {true}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Bool}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        (Variable::interpreted(IntValue(ctx.context.bool_type().const_int(self.val as u64, false)), InterData::Int(self.val as i128), Type::Bool), vec![])
+    }
+    fn to_code(&self) -> String {self.val.to_string()}
+    fn print_impl(&self, f: &mut std::fmt::Formatter, _pre: &mut TreePrefix) -> std::fmt::Result {writeln!(f, "bool: {}", self.val)}
+}
 pub struct FloatLiteralAST {
     loc: Location,
     pub val: f64,
@@ -81,11 +99,13 @@ impl AST for FloatLiteralAST {
         }
     }
     fn to_code(&self) -> String {
+        // whole numbers are printed without a decimal point, which would read back as an integer
+        let val = if self.val.is_finite() && self.val.fract() == 0.0 {format!("{}.0", self.val)} else {self.val.to_string()};
         if let Some(ref suf) = self.suffix {
-            format!("{}{}", self.val, suf)
+            format!("{}{}", val, suf)
         }
         else {
-            self.val.to_string()
+            val
         }
     }
     fn print_impl(&self, f: &mut std::fmt::Formatter, _pre: &mut TreePrefix) -> std::fmt::Result {
@@ -182,3 +202,79 @@ impl AST for StringLiteralAST {
         else {writeln!(f)}
     }
 }
+pub struct ArrayLiteralAST {
+    loc: Location,
+    pub vals: Vec<Box<dyn AST>>
+}
+impl ArrayLiteralAST {
+    pub fn new(loc: Location, vals: Vec<Box<dyn AST>>) -> Self {ArrayLiteralAST {loc, vals}}
+}
+fn elem_type(t: Type) -> Type {
+    match t {
+        Type::IntLiteral => Type::Int(64, false),
+        Type::Reference(b, _) | Type::Borrow(b) => elem_type(*b),
+        x => x
+    }
+}
+impl AST for ArrayLiteralAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
+    fn is_const(&self) -> bool {self.vals.iter().all(|x| x.is_const())}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        let mut it = self.vals.iter().map(|x| x.res_type(ctx));
+        let first = it.next().unwrap_or(Type::Null);
+        Type::Array(Box::new(elem_type(it.fold(first, |t, x| types::utils::common(&t, &x).unwrap_or(t)))), Some(self.vals.len() as u64))
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let vals = self.vals.iter().map(|x| {
+            let (val, mut es) = x.codegen(ctx);
+            errs.append(&mut es);
+            val
+        }).collect::<Vec<_>>();
+        let mut ty = vals.first().map_or(Type::Null, |x| x.data_type.clone());
+        for (val, ast) in vals.iter().zip(self.vals.iter()).skip(1) {
+            if let Some(t) = types::utils::common(&ty, &val.data_type) {ty = t;}
+            else {
+                errs.push(Error::new(ast.loc(), 311, format!("array element of type {} is incompatible with previous elements of type {ty}", val.data_type)).note(Note::new(self.vals[0].loc(), format!("element type was deduced as {ty} here"))));
+            }
+        }
+        let ty = elem_type(ty);
+        let vals = vals.into_iter().zip(self.vals.iter()).map(|(val, ast)| {
+            let err = format!("cannot convert value of type {} to {ty}", val.data_type);
+            types::utils::impl_convert(val, ty.clone(), ctx).unwrap_or_else(|| {
+                errs.push(Error::new(ast.loc(), 311, err));
+                Variable::error()
+            })
+        }).collect::<Vec<_>>();
+        let inter_val = vals.iter().map(|x| x.inter_val.clone()).collect::<Option<Vec<_>>>().map(InterData::Array);
+        let comp_val = ty.llvm_type(ctx).and_then(|llt| {
+            let elems = vals.iter().map(|x| x.value(ctx)).collect::<Option<Vec<_>>>()?;
+            let mut arr = llt.array_type(vals.len() as u32).get_undef();
+            for (n, v) in elems.into_iter().enumerate() {
+                arr = ctx.builder.build_insert_value(arr, v, n as u32, "")?.into_array_value();
+            }
+            Some(ArrayValue(arr))
+        });
+        (Variable {comp_val, inter_val, data_type: Type::Array(Box::new(ty), Some(self.vals.len() as u64)), good: std::cell::Cell::new(true)}, errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = '['.to_string();
+        let mut count = self.vals.len();
+        for val in self.vals.iter() {
+            out += &val.to_code();
+            if count != 1 {out += ", ";}
+            count -= 1;
+        }
+        out + "]"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "array")?;
+        let mut count = self.vals.len();
+        for val in self.vals.iter() {
+            print_ast_child(f, pre, &**val, count == 1)?;
+            count -= 1;
+        }
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ impl CastAST {
 }
 impl AST for CastAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.target.into_type(ctx).0.unwrap_or(Type::Null)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let (val, mut errs) = self.val.codegen(ctx);
@@ -31,12 +32,19 @@ impl AST for CastAST {
             Err(IntoTypeError::DoesNotExist(name)) => {
                 errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                 Type::Null
+            },
+            Err(IntoTypeError::NotAType(name)) => {
+                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                Type::Null
             }
         };
+        if !val.good.get() {return (Variable::error(), errs)}
         let err = format!("cannot convert value of type {} to {t}", val.data_type);
+        let casts = types::utils::expl_casts(&val.data_type);
+        let note = if casts.is_empty() {format!("values of type {} cannot be explicitly cast", val.data_type)} else {format!("values of type {} can be cast to {}", val.data_type, casts.join(", "))};
         if let Some(val) = types::utils::expl_convert(val, t, ctx) {(val, errs)}
         else {
-            errs.push(Error::new(self.loc.clone(), 311, err));
+            errs.push(Error::new(self.loc.clone(), 311, err).note(Note::new(self.val.loc(), note)));
             (Variable::error(), errs)
         }
     }
@@ -1,4 +1,20 @@
 use crate::*;
+use std::cell::Cell;
+use inkwell::values::{BasicValue, BasicValueEnum::*};
+use types::utils::OpKind;
+fn overload<'ctx>(op: &str, kind: OpKind, loc: Location, args: Vec<(Variable<'ctx>, Location)>, errs: &mut Vec<Error>, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let f = types::utils::find_op(op, kind, &args.iter().map(|(v, _)| &v.data_type).collect::<Vec<_>>(), ctx)?;
+    Some(types::utils::call(f, loc, args, ctx).unwrap_or_else(|e| {
+        errs.push(e);
+        Variable::error()
+    }))
+}
+fn overload_type<'ctx>(op: &str, kind: OpKind, operands: &[&Type], ctx: &CompCtx<'ctx>) -> Type {
+    match types::utils::find_op(op, kind, operands, ctx).map(|f| f.data_type) {
+        Some(Type::Function(ret, _)) => *ret,
+        _ => Type::Null
+    }
+}
 pub struct BinOpAST {
     loc: Location,
     pub op: String,
@@ -10,20 +26,85 @@ impl BinOpAST {
 }
 impl AST for BinOpAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.lhs, &*self.rhs]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        if self.op == "&&" || self.op == "||" {self.rhs.res_type(ctx)}
-        else {types::utils::bin_type(self.lhs.res_type(ctx), self.rhs.res_type(ctx), self.op.as_str())}
+        let (lt, rt) = (self.lhs.res_type(ctx), self.rhs.res_type(ctx));
+        match types::utils::bin_type(lt.clone(), rt.clone(), self.op.as_str()) {
+            Type::Null => overload_type(&self.op, OpKind::Binary, &[&lt, &rt], ctx),
+            t => t
+        }
     }
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         match self.op.as_str() {
-            "&&" => todo!("short-circuiting operators aren't implemented"),
-            "||" => todo!("short-circuiting operators aren't implemented"),
+            "&&" | "||" => {
+                let is_and = self.op == "&&";
+                let (lhs, mut errs) = self.lhs.codegen(ctx);
+                let err = format!("binary operator {} isn't defined for values of {} and {}", self.op, lhs.data_type, self.rhs.res_type(ctx));
+                let lhs = if let Some(lhs) = types::utils::impl_convert(lhs, Type::Bool, ctx) {lhs} else {
+                    errs.push(Error::new(self.loc.clone(), 310, err));
+                    return (Variable::error(), errs)
+                };
+                if let Some(InterData::Int(v)) = lhs.inter_val {
+                    if (v != 0) != is_and {
+                        return (Variable::interpreted(IntValue(ctx.context.bool_type().const_int(v as u64, false)), InterData::Int(v), Type::Bool), errs)
+                    }
+                    let (rhs, mut es) = self.rhs.codegen(ctx);
+                    errs.append(&mut es);
+                    let err = format!("binary operator {} isn't defined for values of {} and {}", self.op, Type::Bool, rhs.data_type);
+                    let val = types::utils::impl_convert(rhs, Type::Bool, ctx);
+                    if val.is_none() {
+                        errs.push(Error::new(self.loc.clone(), 310, err));
+                    }
+                    return (val.unwrap_or_else(Variable::error), errs)
+                }
+                let lv = if let (Some(IntValue(lv)), false) = (lhs.value(ctx), ctx.is_const.get()) {lv} else {
+                    if !ctx.is_const.get() {return (Variable::error(), errs)}
+                    let (rhs, mut es) = self.rhs.codegen(ctx);
+                    errs.append(&mut es);
+                    let err = format!("binary operator {} isn't defined for values of {} and {}", self.op, Type::Bool, rhs.data_type);
+                    if types::utils::impl_convert(rhs, Type::Bool, ctx).is_none() {
+                        errs.push(Error::new(self.loc.clone(), 310, err));
+                    }
+                    return (Variable {comp_val: None, inter_val: None, data_type: Type::Bool, good: Cell::new(true)}, errs)
+                };
+                let lb = ctx.builder.get_insert_block().unwrap();
+                let f = lb.get_parent().unwrap();
+                let rb = ctx.context.append_basic_block(f, if is_and {"and_rhs"} else {"or_rhs"});
+                let mb = ctx.context.append_basic_block(f, "merge");
+                if is_and {ctx.builder.build_conditional_branch(lv, rb, mb);}
+                else {ctx.builder.build_conditional_branch(lv, mb, rb);}
+                ctx.builder.position_at_end(rb);
+                let (rhs, mut es) = self.rhs.codegen(ctx);
+                errs.append(&mut es);
+                let err = format!("binary operator {} isn't defined for values of {} and {}", self.op, Type::Bool, rhs.data_type);
+                let rv = if let Some(IntValue(rv)) = types::utils::impl_convert(rhs, Type::Bool, ctx).and_then(|v| v.value(ctx)) {rv} else {
+                    errs.push(Error::new(self.loc.clone(), 310, err));
+                    ctx.context.bool_type().const_zero()
+                };
+                let rb = ctx.builder.get_insert_block().unwrap();
+                ctx.builder.build_unconditional_branch(mb);
+                ctx.builder.position_at_end(mb);
+                let phi = ctx.builder.build_phi(ctx.context.bool_type(), "");
+                phi.add_incoming(&[(&ctx.context.bool_type().const_int(if is_and {0} else {1}, false) as &dyn BasicValue, lb), (&rv as &dyn BasicValue, rb)]);
+                (Variable::compiled(phi.as_basic_value(), Type::Bool), errs)
+            },
             x => {
+                // a local that's been moved from can be given a new value, which makes it usable again
+                let moved = if x == "=" {
+                    self.lhs.var_name().filter(|n| matches!(ctx.with_vars(|v| v.lookup(n)), Ok(Symbol::Variable(v)) if !v.good.get() && matches!(&v.data_type, Type::Reference(b, true) if types::utils::needs_drop(b, ctx))))
+                } else {None};
+                let revive = |good: bool| if let Some(Ok(Symbol::Variable(v))) = moved.map(|n| ctx.with_vars(|v| v.lookup(n))) {v.good.set(good);};
+                revive(true);
                 let (lhs, mut errs) = self.lhs.codegen(ctx);
+                revive(false);
+                if x.ends_with('=') && !matches!(x, "==" | "!=" | "<=" | ">=") {
+                    if let Some(err) = types::utils::check_write(&lhs, self.loc.clone(), ctx) {errs.push(err);}
+                }
                 let (rhs, mut es) = self.rhs.codegen(ctx);
                 errs.append(&mut es);
                 let err = format!("binary operator {} isn't defined for values of {} and {}", self.op, lhs.data_type, rhs.data_type);
-                let val = types::utils::bin_op(lhs, rhs, x, ctx);
+                let val = types::utils::bin_op(lhs.clone(), rhs.clone(), x, ctx).or_else(|| overload(x, OpKind::Binary, self.loc.clone(), vec![(lhs, self.lhs.loc()), (rhs, self.rhs.loc())], &mut errs, ctx));
+                revive(val.is_some());
                 if val.is_none() {
                     errs.push(Error::new(self.loc.clone(), 310, err));
                 }
@@ -47,16 +128,53 @@ pub struct PostfixAST {
 }
 impl PostfixAST {
     pub fn new(loc: Location, op: String, val: Box<dyn AST>) -> Self {PostfixAST {loc, op, val}}
+    // an empty optional is returned from the function as-is, otherwise its value is used
+    fn propagate<'ctx>(&self, val: Variable<'ctx>, mut errs: Vec<Error>, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let target = if let Some(target) = ctx.ret.borrow().clone() {target} else {
+            errs.push(Error::new(self.loc.clone(), 353, "? can only be used inside of a function".to_string()));
+            return (Variable::error(), errs)
+        };
+        if !matches!(target.ret, Type::Optional(_)) {
+            errs.push(Error::new(self.loc.clone(), 372, "? can only be used in a function that returns an optional".to_string()).note(Note::new(self.val.loc(), format!("function returns a value of type {}", target.ret))));
+            return (Variable::error(), errs)
+        }
+        let t = types::utils::post_type(val.data_type.clone(), "?");
+        if ctx.is_const.get() {return (Variable {comp_val: None, inter_val: None, data_type: t, good: Cell::new(true)}, errs)}
+        let err = format!("postfix operator ? isn't defined for value of {}", val.data_type);
+        let (present, val) = if let Some(x) = types::utils::opt_parts(val, ctx) {x} else {
+            errs.push(Error::new(self.loc.clone(), 310, err));
+            return (Variable::error(), errs)
+        };
+        let f = ctx.builder.get_insert_block().unwrap().get_parent().unwrap();
+        let eb = ctx.context.append_basic_block(f, "empty");
+        let pb = ctx.context.append_basic_block(f, "present");
+        ctx.builder.build_conditional_branch(present, pb, eb);
+        ctx.builder.position_at_end(eb);
+        errs.append(&mut flow::exit_scopes(0, ctx));
+        if let Some(phi) = target.phi {
+            let v = phi.as_basic_value().get_type().const_zero();
+            phi.add_incoming(&[(&v as &dyn BasicValue, ctx.builder.get_insert_block().unwrap())]);
+        }
+        ctx.builder.build_unconditional_branch(target.exit);
+        ctx.builder.position_at_end(pb);
+        (val, errs)
+    }
 }
 impl AST for PostfixAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        types::utils::post_type(self.val.res_type(ctx), self.op.as_str())
+        let t = self.val.res_type(ctx);
+        match types::utils::post_type(t.clone(), self.op.as_str()) {
+            Type::Null => overload_type(&self.op, OpKind::Postfix, &[&t], ctx),
+            t => t
+        }
     }
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let (v, mut errs) = self.val.codegen(ctx);
+        if self.op == "?" && types::utils::post_type(v.data_type.clone(), "?") != Type::Null {return self.propagate(v, errs, ctx)}
         let err = format!("postfix operator {} isn't defined for value of {}", self.op, v.data_type);
-        let val = types::utils::post_op(v, self.op.as_str(), ctx);
+        let val = types::utils::post_op(v.clone(), self.op.as_str(), self.loc.clone(), ctx).or_else(|| overload(&self.op, OpKind::Postfix, self.loc.clone(), vec![(v, self.val.loc())], &mut errs, ctx));
         if val.is_none() {
             errs.push(Error::new(self.loc.clone(), 310, err));
         }
@@ -80,13 +198,21 @@ impl PrefixAST {
 }
 impl AST for PrefixAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        types::utils::pre_type(self.val.res_type(ctx), self.op.as_str())
+        let t = self.val.res_type(ctx);
+        match types::utils::pre_type(t.clone(), self.op.as_str()) {
+            Type::Null => overload_type(&self.op, OpKind::Prefix, &[&t], ctx),
+            t => t
+        }
     }
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let (v, mut errs) = self.val.codegen(ctx);
+        if matches!(self.op.as_str(), "++" | "--" | "&") {
+            if let Some(err) = types::utils::check_write(&v, self.loc.clone(), ctx) {errs.push(err);}
+        }
         let err = format!("prefix operator {} isn't defined for value of {}", self.op, v.data_type);
-        let val = types::utils::pre_op(v, self.op.as_str(), ctx);
+        let val = types::utils::pre_op(v.clone(), self.op.as_str(), ctx).or_else(|| overload(&self.op, OpKind::Prefix, self.loc.clone(), vec![(v, self.val.loc())], &mut errs, ctx));
         if val.is_none() {
             errs.push(Error::new(self.loc.clone(), 310, err));
         }
@@ -100,3 +226,50 @@ impl AST for PrefixAST {
         print_ast_child(f, pre, &*self.val, true)
     }
 }
+pub struct SubscriptAST {
+    loc: Location,
+    pub target: Box<dyn AST>,
+    pub index: Box<dyn AST>
+}
+impl SubscriptAST {
+    pub fn new(loc: Location, target: Box<dyn AST>, index: Box<dyn AST>) -> Self {SubscriptAST {loc, target, index}}
+}
+fn array_len(t: &Type) -> Option<u64> {
+    match t {
+        Type::Array(_, len) => *len,
+        Type::Reference(b, _) | Type::Borrow(b) => array_len(b),
+        _ => None
+    }
+}
+impl AST for SubscriptAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.target, &*self.index]}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
+        types::utils::sub_type(self.target.res_type(ctx), self.index.res_type(ctx))
+    }
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let (target, mut errs) = self.target.codegen(ctx);
+        let (index, mut es) = self.index.codegen(ctx);
+        errs.append(&mut es);
+        if let (Some(len), Some(InterData::Int(idx))) = (array_len(&target.data_type), &index.inter_val) {
+            if *idx < 0 || *idx as u64 >= len {
+                errs.push(Error::new(self.index.loc(), 314, format!("index {idx} is out of bounds for a value of type {}", target.data_type)).note(Note::new(self.target.loc(), format!("array has a length of {len}"))));
+                return (Variable::error(), errs)
+            }
+        }
+        let err = format!("subscript isn't defined for values of {} and {}", target.data_type, index.data_type);
+        let val = types::utils::subscript(target, index, ctx);
+        if val.is_none() {
+            errs.push(Error::new(self.loc.clone(), 310, err));
+        }
+        (val.unwrap_or_else(Variable::error), errs)
+    }
+    fn to_code(&self) -> String {
+        format!("{}[{}]", self.target.to_code(), self.index.to_code())
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "subscript")?;
+        print_ast_child(f, pre, &*self.target, false)?;
+        print_ast_child(f, pre, &*self.index, true)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::*;
+use std::collections::HashMap;
 pub struct ModuleAST {
     loc: Location,
     pub name: DottedName,
@@ -6,8 +7,32 @@ pub struct ModuleAST {
 }
 impl AST for ModuleAST {
     fn loc(&self) -> Location {self.loc.clone()}
-    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {todo!("code generation has not been implemented for module definitions")}
-    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {todo!("code generation has not been implemented for module definitions")}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let old_prefix = if self.name.global {ctx.prefix.replace(self.name.ids.clone())}
+        else {
+            let old = ctx.prefix.borrow().clone();
+            ctx.prefix.borrow_mut().extend(self.name.ids.iter().cloned());
+            old
+        };
+        ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+        let mut errs = vec![];
+        for val in self.vals.iter() {
+            let mut es = val.codegen(ctx).1;
+            errs.append(&mut es);
+        }
+        let syms = ctx.with_vars(|v| std::mem::take(&mut v.symbols));
+        ctx.map_vars(|v| v.parent.unwrap());
+        ctx.prefix.replace(old_prefix);
+        match ctx.with_vars(|v| v.insert_mod(&self.name, syms)) {
+            Ok(_) => {},
+            Err(RedefVariable::NotAModule(x, _)) => errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(x)))),
+            Err(RedefVariable::AlreadyExists(x, _)) => errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", self.name.start(x)))),
+            Err(RedefVariable::MergeConflict(_, conflicts)) => errs.extend(conflicts.into_keys().map(|name| Error::new(self.loc.clone(), 321, format!("{}.{name} has already been defined", self.name))))
+        }
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
     fn to_code(&self) -> String {
         let mut out = format!("module {} {{", self.name);
         let mut count = self.vals.len();
@@ -38,8 +63,25 @@ pub struct ImportAST {
 }
 impl AST for ImportAST {
     fn loc(&self) -> Location {self.loc.clone()}
-    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {todo!("code generation has not been implemented for imports")}
-    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {todo!("code generation has not been implemented for imports")}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let mut out = HashMap::new();
+        ctx.with_vars(|v| {
+            let syms = match self.name.ids.first() {
+                Some(CompoundDottedNameSegment::Identifier(x)) if !self.name.global => {
+                    let mut scope: &VarMap = v;
+                    while !scope.symbols.contains_key(x) && scope.parent.is_some() {scope = scope.parent.as_ref().unwrap();}
+                    &scope.symbols
+                },
+                _ => &v.root().symbols
+            };
+            import_from(syms, &self.name.ids, &mut vec![], &mut out, self.loc.clone(), &mut errs);
+        });
+        let conflicts = ctx.with_vars(|v| v.merge(out));
+        errs.extend(conflicts.into_keys().map(|name| Error::new(self.loc.clone(), 321, format!("{name} has already been defined")).note(Note::new(self.loc.clone(), format!("imported from {}", self.name)))));
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
     fn to_code(&self) -> String {
         format!("import {}", self.name)
     }
@@ -50,3 +92,43 @@ impl AST for ImportAST {
 impl ImportAST {
     pub fn new(loc: Location, name: CompoundDottedName) -> Self {ImportAST {loc, name}}
 }
+fn glob_match(pat: &str, name: &str) -> bool {
+    match pat.split_once('*') {
+        None => pat == name,
+        Some((pre, rest)) => name.starts_with(pre) && {
+            let name = &name[pre.len()..];
+            (0..=name.len()).filter(|&i| name.is_char_boundary(i)).any(|i| glob_match(rest, &name[i..]))
+        }
+    }
+}
+fn import_from<'ctx>(syms: &HashMap<String, Symbol<'ctx>>, segs: &[CompoundDottedNameSegment], path: &mut Vec<String>, out: &mut HashMap<String, Symbol<'ctx>>, loc: Location, errs: &mut Vec<Error>) {
+    let (seg, rest) = if let Some(x) = segs.split_first() {x} else {return};
+    match seg {
+        CompoundDottedNameSegment::Identifier(x) => {
+            path.push(x.clone());
+            match syms.get(x) {
+                None => errs.push(Error::new(loc, 321, format!("{} does not exist", path.join(".")))),
+                Some(sym) if rest.is_empty() => {out.insert(x.clone(), sym.clone());},
+                Some(Symbol::Module(m)) => import_from(m, rest, path, out, loc, errs),
+                Some(Symbol::Variable(_)) => errs.push(Error::new(loc, 320, format!("{} is not a module", path.join("."))))
+            }
+            path.pop();
+        },
+        CompoundDottedNameSegment::Glob(p) => {
+            for (name, sym) in syms.iter().filter(|(name, _)| glob_match(p, name)) {
+                if rest.is_empty() {out.insert(name.clone(), sym.clone());}
+                else if let Symbol::Module(m) = sym {
+                    path.push(name.clone());
+                    import_from(m, rest, path, out, loc.clone(), errs);
+                    path.pop();
+                }
+            }
+        },
+        CompoundDottedNameSegment::Group(gs) => {
+            for g in gs.iter() {
+                let segs = g.iter().chain(rest.iter()).cloned().collect::<Vec<_>>();
+                import_from(syms, &segs, path, out, loc.clone(), errs);
+            }
+        }
+    }
+}
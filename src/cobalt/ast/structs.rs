@@ -0,0 +1,207 @@
+use crate::*;
+use inkwell::values::BasicValueEnum::*;
+pub struct StructDefAST {
+    loc: Location,
+    pub name: DottedName,
+    pub fields: Vec<(String, Location, ParsedType)>
+}
+impl StructDefAST {
+    pub fn new(loc: Location, name: DottedName, fields: Vec<(String, Location, ParsedType)>) -> Self {StructDefAST {loc, name, fields}}
+}
+impl AST for StructDefAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let mut fields: Vec<(String, Type)> = vec![];
+        let mut locs: Vec<&Location> = vec![];
+        for (name, loc, pt) in self.fields.iter() {
+            if let Some(n) = fields.iter().position(|(n, _)| n == name) {
+                errs.push(Error::new(loc.clone(), 340, format!("duplicate field {name} in struct {}", self.name)).note(Note::new(locs[n].clone(), "previously defined here".to_string())));
+                continue;
+            }
+            let (t, mut es) = pt.into_type(ctx);
+            errs.append(&mut es);
+            let t = match t {
+                Ok(t) => {
+                    if t.llvm_type(ctx).is_none() {
+                        errs.push(Error::new(loc.clone(), 341, format!("field {name} has type {t}, which has no runtime representation")));
+                    }
+                    t
+                },
+                Err(IntoTypeError::NotAnInt(name)) => {
+                    errs.push(Error::new(loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotCompileTime) => {
+                    errs.push(Error::new(loc.clone(), 312, format!("array size cannot be determined at compile time")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotAModule(name)) => {
+                    errs.push(Error::new(loc.clone(), 320, format!("{name} is not a module")));
+                    Type::Null
+                },
+                Err(IntoTypeError::DoesNotExist(name)) => {
+                    errs.push(Error::new(loc.clone(), 321, format!("{name} does not exist")));
+                    Type::Null
+                },
+                Err(IntoTypeError::NotAType(name)) => {
+                    errs.push(Error::new(loc.clone(), 322, format!("{name} is not a type")));
+                    Type::Null
+                }
+            };
+            fields.push((name.clone(), t));
+            locs.push(loc);
+        }
+        let t = Type::Struct(ctx.mangle(&self.name), fields);
+        match ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable::metaval(InterData::Type(Box::new(t)), Type::TypeData)))) {
+            Ok(_) => {},
+            Err(RedefVariable::NotAModule(x, _)) => errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(x)))),
+            Err(RedefVariable::AlreadyExists(x, _) | RedefVariable::MergeConflict(x, _)) => errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", self.name.start(x))))
+        }
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("struct {} {{", self.name);
+        let mut count = self.fields.len();
+        for (name, _, t) in self.fields.iter() {
+            out += &format!("{name}: {t}");
+            if count != 1 {out += ", ";}
+            count -= 1;
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "struct: {}", self.name)?;
+        let mut count = self.fields.len();
+        for (name, _, t) in self.fields.iter() {
+            writeln!(f, "{pre}{}{name}: {t}", if count == 1 {"└── "} else {"├── "})?;
+            count -= 1;
+        }
+        Ok(())
+    }
+}
+pub struct StructLiteralAST {
+    loc: Location,
+    pub name: DottedName,
+    pub fields: Vec<(String, Location, Box<dyn AST>)>
+}
+impl StructLiteralAST {
+    pub fn new(loc: Location, name: DottedName, fields: Vec<(String, Location, Box<dyn AST>)>) -> Self {StructLiteralAST {loc, name, fields}}
+}
+impl AST for StructLiteralAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.fields.iter().map(|(_, _, v)| &**v).collect()}
+    fn is_const(&self) -> bool {self.fields.iter().all(|(_, _, x)| x.is_const())}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {ParsedType::Other(self.name.clone()).into_type(ctx).0.unwrap_or(Type::Null)}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let (t, mut errs) = ParsedType::Other(self.name.clone()).into_type(ctx);
+        let (sname, ftypes) = match t {
+            Ok(Type::Struct(n, f)) => (n, f),
+            Ok(t) => {
+                errs.push(Error::new(self.loc.clone(), 342, format!("{} is not a struct type", t)));
+                return (Variable::error(), errs)
+            },
+            Err(IntoTypeError::NotAModule(name)) => {
+                errs.push(Error::new(self.loc.clone(), 320, format!("{name} is not a module")));
+                return (Variable::error(), errs)
+            },
+            Err(IntoTypeError::NotAType(name)) => {
+                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                return (Variable::error(), errs)
+            },
+            Err(_) => {
+                errs.push(Error::new(self.loc.clone(), 321, format!("{} does not exist", self.name)));
+                return (Variable::error(), errs)
+            }
+        };
+        let mut vals: Vec<Option<Variable>> = vec![None; ftypes.len()];
+        let mut locs: Vec<Option<&Location>> = vec![None; ftypes.len()];
+        for (name, loc, ast) in self.fields.iter() {
+            let (val, mut es) = ast.codegen(ctx);
+            errs.append(&mut es);
+            let idx = if let Some(idx) = ftypes.iter().position(|(n, _)| n == name) {idx} else {
+                errs.push(Error::new(loc.clone(), 343, format!("struct {sname} has no field {name}")));
+                continue
+            };
+            if let Some(prev) = locs[idx] {
+                errs.push(Error::new(loc.clone(), 344, format!("field {name} is specified more than once")).note(Note::new(prev.clone(), "previously specified here".to_string())));
+                continue
+            }
+            let err = format!("cannot convert value of type {} to {}", val.data_type, ftypes[idx].1);
+            vals[idx] = Some(types::utils::impl_convert(val, ftypes[idx].1.clone(), ctx).unwrap_or_else(|| {
+                errs.push(Error::new(ast.loc(), 311, err));
+                Variable::error()
+            }));
+            locs[idx] = Some(loc);
+        }
+        let missing = ftypes.iter().zip(vals.iter()).filter(|(_, v)| v.is_none()).map(|((n, _), _)| n.as_str()).collect::<Vec<_>>();
+        if missing.len() > 0 {
+            errs.push(Error::new(self.loc.clone(), 345, format!("missing field{} {} in literal of struct {sname}", if missing.len() == 1 {""} else {"s"}, missing.join(", "))));
+            return (Variable::error(), errs)
+        }
+        let vals = vals.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+        let data_type = Type::Struct(sname, ftypes);
+        let inter_val = vals.iter().map(|x| x.inter_val.clone()).collect::<Option<Vec<_>>>().map(InterData::Array);
+        let comp_val = if ctx.is_const.get() {None} else {data_type.llvm_type(ctx).and_then(|llt| {
+            let elems = vals.iter().map(|x| x.value(ctx)).collect::<Option<Vec<_>>>()?;
+            let mut val = llt.into_struct_type().get_undef();
+            for (n, v) in elems.into_iter().enumerate() {
+                val = ctx.builder.build_insert_value(val, v, n as u32, "")?.into_struct_value();
+            }
+            Some(StructValue(val))
+        })};
+        (Variable {comp_val, inter_val, data_type, good: std::cell::Cell::new(true)}, errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("{} {{", self.name);
+        let mut count = self.fields.len();
+        for (name, _, val) in self.fields.iter() {
+            out += &format!("{name}: {}", val.to_code());
+            if count != 1 {out += ", ";}
+            count -= 1;
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "struct literal: {}", self.name)?;
+        let mut count = self.fields.len();
+        for (name, _, val) in self.fields.iter() {
+            write!(f, "{pre}{}{name}: ", if count == 1 {"└── "} else {"├── "})?;
+            pre.push(count == 1);
+            val.print_impl(f, pre)?;
+            pre.pop();
+            count -= 1;
+        }
+        Ok(())
+    }
+}
+pub struct FieldAST {
+    loc: Location,
+    pub target: Box<dyn AST>,
+    pub name: String
+}
+impl FieldAST {
+    pub fn new(loc: Location, target: Box<dyn AST>, name: String) -> Self {FieldAST {loc, target, name}}
+}
+impl AST for FieldAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.target]}
+    fn is_const(&self) -> bool {self.target.is_const()}
+    fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {types::utils::field_type(&self.target.res_type(ctx), &self.name).unwrap_or(Type::Null)}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let (val, mut errs) = self.target.codegen(ctx);
+        if !val.good.get() {return (Variable::error(), errs)}
+        let err = format!("value of type {} has no field {}", val.data_type, self.name);
+        if let Some(val) = types::utils::field(val, &self.name, ctx) {(val, errs)}
+        else {
+            errs.push(Error::new(self.loc.clone(), 324, err));
+            (Variable::error(), errs)
+        }
+    }
+    fn to_code(&self) -> String {format!("{}.{}", self.target.to_code(), self.name)}
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "field: {}", self.name)?;
+        print_ast_child(f, pre, &*self.target, true)
+    }
+}
@@ -0,0 +1,190 @@
+use crate::*;
+use inkwell::values::BasicValueEnum::*;
+fn into_type<'ctx>(pt: &ParsedType, loc: &Location, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) -> Type {
+    let (t, mut es) = pt.into_type(ctx);
+    errs.append(&mut es);
+    match t {
+        Ok(t) => t,
+        Err(IntoTypeError::NotAnInt(name)) => {
+            errs.push(Error::new(loc.clone(), 311, format!("cannot convert value of type {name} to u64")));
+            Type::Null
+        },
+        Err(IntoTypeError::NotCompileTime) => {
+            errs.push(Error::new(loc.clone(), 312, format!("array size cannot be determined at compile time")));
+            Type::Null
+        },
+        Err(IntoTypeError::NotAModule(name)) => {
+            errs.push(Error::new(loc.clone(), 320, format!("{name} is not a module")));
+            Type::Null
+        },
+        Err(IntoTypeError::DoesNotExist(name)) => {
+            errs.push(Error::new(loc.clone(), 321, format!("{name} does not exist")));
+            Type::Null
+        },
+        Err(IntoTypeError::NotAType(name)) => {
+            errs.push(Error::new(loc.clone(), 322, format!("{name} is not a type")));
+            Type::Null
+        }
+    }
+}
+pub struct TraitDefAST {
+    loc: Location,
+    pub name: DottedName,
+    pub methods: Vec<(String, Location, bool, Vec<(String, ParsedType)>, ParsedType)> // name, location, mutable receiver, parameters, return type
+}
+impl TraitDefAST {
+    pub fn new(loc: Location, name: DottedName, methods: Vec<(String, Location, bool, Vec<(String, ParsedType)>, ParsedType)>) -> Self {TraitDefAST {loc, name, methods}}
+}
+impl AST for TraitDefAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let mut methods: Vec<(String, Type, bool)> = vec![];
+        let mut locs: Vec<&Location> = vec![];
+        for (name, loc, is_mut, params, ret) in self.methods.iter() {
+            if let Some(n) = methods.iter().position(|(n, ..)| n == name) {
+                errs.push(Error::new(loc.clone(), 366, format!("duplicate method {name} in trait {}", self.name)).note(Note::new(locs[n].clone(), "previously defined here".to_string())));
+                continue;
+            }
+            let params = params.iter().map(|(_, pt)| into_type(pt, loc, ctx, &mut errs)).collect::<Vec<_>>();
+            let ret = into_type(ret, loc, ctx, &mut errs);
+            if let Some(t) = params.iter().find(|t| t.llvm_type(ctx).is_none()) {
+                errs.push(Error::new(loc.clone(), 341, format!("parameter of method {name} has type {t}, which has no runtime representation")));
+            }
+            methods.push((name.clone(), Type::Closure(Box::new(ret), params), *is_mut));
+            locs.push(loc);
+        }
+        let t = Type::Trait(ctx.mangle(&self.name), methods);
+        match ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable::metaval(InterData::Type(Box::new(t)), Type::TypeData)))) {
+            Ok(_) => {},
+            Err(RedefVariable::NotAModule(x, _)) => errs.push(Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(x)))),
+            Err(RedefVariable::AlreadyExists(x, _) | RedefVariable::MergeConflict(x, _)) => errs.push(Error::new(self.loc.clone(), 321, format!("{} has already been defined", self.name.start(x))))
+        }
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = format!("trait {} {{", self.name);
+        for (name, _, is_mut, params, ret) in self.methods.iter() {
+            out += &format!("fn {name}({}self", if *is_mut {"mut "} else {""});
+            for (param, t) in params.iter() {out += &format!(", {param}: {t}");}
+            out += &format!("): {ret};");
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        writeln!(f, "trait: {}", self.name)?;
+        let mut count = self.methods.len();
+        for (name, _, is_mut, params, ret) in self.methods.iter() {
+            write!(f, "{pre}{}{name}({}self", if count == 1 {"└── "} else {"├── "}, if *is_mut {"mut "} else {""})?;
+            for (param, t) in params.iter() {write!(f, ", {param}: {t}")?;}
+            writeln!(f, "): {ret}")?;
+            count -= 1;
+        }
+        Ok(())
+    }
+}
+pub struct ImplAST {
+    loc: Location,
+    pub target: ParsedType,
+    pub tr: Option<DottedName>,
+    pub vals: Vec<Box<dyn AST>>
+}
+impl ImplAST {
+    pub fn new(loc: Location, target: ParsedType, tr: Option<DottedName>, vals: Vec<Box<dyn AST>>) -> Self {ImplAST {loc, target, tr, vals}}
+    // check that every method of the trait is implemented with the right signature, and emit the vtable if it is
+    fn check<'ctx>(&self, t: &Type, name: &str, methods: &[(String, Type, bool)], syms: &std::collections::HashMap<String, Symbol<'ctx>>, ctx: &CompCtx<'ctx>, errs: &mut Vec<Error>) {
+        let mut fns = vec![];
+        for (mname, mt, is_mut) in methods.iter() {
+            let (ret, params) = if let Type::Closure(ret, params) = mt {(ret, params)} else {unreachable!("trait methods are stored as closure types")};
+            let expected = Type::Function(ret.clone(), std::iter::once((Type::Reference(Box::new(t.clone()), *is_mut), false)).chain(params.iter().map(|p| (p.clone(), false))).collect());
+            match syms.get(mname).and_then(Symbol::as_var) {
+                None => errs.push(Error::new(self.loc.clone(), 363, format!("type {t} does not implement method {mname} required by trait {name}"))),
+                Some(f) if f.data_type != expected => errs.push(Error::new(self.loc.clone(), 364, format!("method {mname} of trait {name} should have type {expected}, but {t}.{mname} has type {}", f.data_type))),
+                Some(f) => fns.push(f.comp_val)
+            }
+        }
+        if fns.len() != methods.len() {return}
+        let vt = if let Some(vt) = types::utils::vtable_type(methods, ctx) {vt} else {return};
+        let entries = fns.into_iter().zip(vt.get_field_types()).map(|(f, ft)| match f {
+            Some(PointerValue(f)) => f.const_cast(ft.into_pointer_type()).into(),
+            _ => ft.into_pointer_type().const_null().into()
+        }).collect::<Vec<_>>();
+        let mut path = types::utils::assoc_module(t);
+        let gv = ctx.module.add_global(vt, None, format!("{}.{name}.vtable", path.ids[0]).as_str());
+        gv.set_constant(true);
+        gv.set_initializer(&vt.const_named_struct(&entries));
+        path.ids.push(types::utils::vtable_key(name));
+        if ctx.with_vars(|v| v.insert(&path, Symbol::Variable(Variable::compiled(PointerValue(gv.as_pointer_value()), Type::Reference(Box::new(Type::Int(8, false)), false))))).is_err() {
+            errs.push(Error::new(self.loc.clone(), 321, format!("trait {name} has already been implemented for {t}")));
+        }
+    }
+}
+impl AST for ImplAST {
+    fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {self.vals.iter().map(|v| &**v).collect()}
+    fn res_type<'ctx>(&self, _ctx: &CompCtx<'ctx>) -> Type {Type::Null}
+    fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
+        let mut errs = vec![];
+        let t = into_type(&self.target, &self.loc, ctx, &mut errs);
+        if errs.len() > 0 {return (Variable::error(), errs)}
+        let tr = self.tr.as_ref().and_then(|name| match ParsedType::Other(name.clone()).into_type(ctx).0 {
+            Ok(Type::Trait(n, m)) => Some((n, m)),
+            Ok(_) | Err(IntoTypeError::NotAType(_)) => {
+                errs.push(Error::new(self.loc.clone(), 365, format!("{name} is not a trait")));
+                None
+            },
+            Err(IntoTypeError::NotAModule(x)) => {
+                errs.push(Error::new(self.loc.clone(), 320, format!("{x} is not a module")));
+                None
+            },
+            Err(_) => {
+                errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
+                None
+            }
+        });
+        if self.tr.is_some() && tr.is_none() {return (Variable::error(), errs)}
+        let module = types::utils::assoc_module(&t);
+        let old_prefix = ctx.prefix.replace(module.ids.clone());
+        let mut defined = std::collections::HashMap::new();
+        // each definition is added to the associated module as soon as it's generated, so later ones can call it as a method
+        for val in self.vals.iter() {
+            ctx.map_vars(|v| Box::new(VarMap::new(Some(v))));
+            let mut es = val.codegen(ctx).1;
+            errs.append(&mut es);
+            let syms = ctx.with_vars(|v| std::mem::take(&mut v.symbols));
+            ctx.map_vars(|v| v.parent.unwrap());
+            defined.extend(syms.iter().map(|(k, v)| (k.clone(), v.clone())));
+            match ctx.with_vars(|v| v.insert_mod(&module, syms)) {
+                Ok(_) => {},
+                Err(RedefVariable::NotAModule(..)) => unreachable!("associated modules are always at the root"),
+                Err(RedefVariable::AlreadyExists(..)) => errs.push(Error::new(val.loc(), 321, format!("{t} has a conflicting associated definition"))),
+                Err(RedefVariable::MergeConflict(_, conflicts)) => errs.extend(conflicts.into_keys().map(|name| Error::new(val.loc(), 321, format!("{t}.{name} has already been defined"))))
+            }
+        }
+        ctx.prefix.replace(old_prefix);
+        if let Some((name, methods)) = tr {self.check(&t, &name, &methods, &defined, ctx, &mut errs);}
+        (Variable::metaval(InterData::Null, Type::Null), errs)
+    }
+    fn to_code(&self) -> String {
+        let mut out = if let Some(tr) = &self.tr {format!("impl {tr} for {} {{", self.target)} else {format!("impl {} {{", self.target)};
+        let mut count = self.vals.len();
+        for val in self.vals.iter() {
+            out += &val.to_code();
+            out += ";";
+            if count > 1 {out += " ";}
+            count -= 1;
+        }
+        out + "}"
+    }
+    fn print_impl(&self, f: &mut std::fmt::Formatter, pre: &mut TreePrefix) -> std::fmt::Result {
+        if let Some(tr) = &self.tr {writeln!(f, "impl: {tr} for {}", self.target)?;}
+        else {writeln!(f, "impl: {}", self.target)?;}
+        let mut count = self.vals.len();
+        for val in self.vals.iter() {
+            print_ast_child(f, pre, &**val, count == 1)?;
+            count -= 1;
+        }
+        Ok(())
+    }
+}
@@ -12,6 +12,7 @@ pub struct VarDefAST {
 }
 impl AST for VarDefAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.val.res_type(ctx)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let mut errs = vec![];
@@ -94,12 +95,16 @@ impl AST for VarDefAST {
                         Err(IntoTypeError::DoesNotExist(name)) => {
                             errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                             None
+                        },
+                        Err(IntoTypeError::NotAType(name)) => {
+                            errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                            None
                         }
                     }
                 }) {t} else if t2 == Type::IntLiteral {Type::Int(64, false)} else if let Type::Reference(b, _) = t2 {*b} else {t2};
                 match ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
                     comp_val: dt.llvm_type(ctx).map(|t| {
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         match link_type {
                             None => {},
                             Some(WeakAny) => gv.set_linkage(ExternalWeak),
@@ -147,6 +152,10 @@ impl AST for VarDefAST {
                         Err(IntoTypeError::DoesNotExist(name)) => {
                             errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                             None
+                        },
+                        Err(IntoTypeError::NotAType(name)) => {
+                            errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                            None
                         }
                     };
                     t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -157,7 +166,7 @@ impl AST for VarDefAST {
                     }
                     else {
                         let t = dt.llvm_type(ctx).unwrap();
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         gv.set_constant(true);
                         gv.set_initializer(&v);
                         if let Some(link) = link_type {gv.set_linkage(link)}
@@ -212,6 +221,10 @@ impl AST for VarDefAST {
                                 Err(IntoTypeError::DoesNotExist(name)) => {
                                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                     None
+                                },
+                                Err(IntoTypeError::NotAType(name)) => {
+                                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                    None
                                 }
                             };
                             t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -223,10 +236,10 @@ impl AST for VarDefAST {
                         ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {good: Cell::new(true), ..val})))
                     }
                     else {
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         gv.set_constant(false);
                         if let Some(link) = link_type {gv.set_linkage(link)}
-                        let f = ctx.module.add_function(format!("__internals.init.{}", self.name).as_str(), ctx.context.void_type().fn_type(&[], false), Some(inkwell::module::Linkage::Private));
+                        let f = ctx.module.add_function(format!("__internals.init.{}", ctx.mangle(&self.name)).as_str(), ctx.context.void_type().fn_type(&[], false), Some(inkwell::module::Linkage::Private));
                         let entry = ctx.context.append_basic_block(f, "entry");
                         let old_ip = ctx.builder.get_insert_block();
                         ctx.builder.position_at_end(entry);
@@ -253,6 +266,10 @@ impl AST for VarDefAST {
                                 Err(IntoTypeError::DoesNotExist(name)) => {
                                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                     None
+                                },
+                                Err(IntoTypeError::NotAType(name)) => {
+                                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                    None
                                 }
                             };
                             t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -306,6 +323,10 @@ impl AST for VarDefAST {
                             Err(IntoTypeError::DoesNotExist(name)) => {
                                 errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                 None
+                            },
+                            Err(IntoTypeError::NotAType(name)) => {
+                                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                None
                             }
                         };
                         t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -362,20 +383,40 @@ impl AST for VarDefAST {
                     Err(IntoTypeError::DoesNotExist(name)) => {
                         errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                         None
+                    },
+                    Err(IntoTypeError::NotAType(name)) => {
+                        errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                        None
                     }
                 };
                 t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
             }) {t} else if t2 == Type::IntLiteral {(Type::Int(64, false), "INFALLIBLE".to_string())} else if let Type::Reference(b, _) = t2 {(*b, "INFALLIBLE".to_string())} else {(t2, "INFALLIBLE".to_string())};
-            let val = types::utils::impl_convert(val, dt.clone(), ctx).unwrap_or_else(|| {
+            let borrow = match &dt {
+                Type::Reference(_, m) => Some(*m),
+                Type::Borrow(_) => Some(false),
+                _ => None
+            }.and_then(|m| types::utils::check_borrow(&val, m, self.loc.clone(), &[], ctx).unwrap_or_else(|e| {errs.push(e); None}));
+            let mut val = types::utils::impl_convert(val, dt.clone(), ctx).unwrap_or_else(|| {
                 errs.push(Error::new(self.loc.clone(), 311, err));
                 Variable::error()
             });
-            match if ctx.is_const.get() || val.data_type.register() {
+            if let Some(mut b) = borrow {
+                // the holder gets its own pointer, so using it isn't mistaken for another borrow of the local
+                if let (Some(PointerValue(p)), false) = (val.comp_val, ctx.is_const.get()) {
+                    let p = unsafe {ctx.builder.build_in_bounds_gep(p, &[ctx.context.i64_type().const_zero()], "")};
+                    val.comp_val = Some(PointerValue(p));
+                    b.via = Some(p);
+                }
+                types::utils::hold_borrow(b, ctx);
+            }
+            match if ctx.is_const.get() || (val.data_type.register() && !types::utils::needs_drop(&val.data_type, ctx)) {
                 ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {good: Cell::new(true), ..val})))
             } 
             else if let (Some(t), Some(v)) = (val.data_type.llvm_type(ctx), val.comp_val) {
                 let a = ctx.builder.build_alloca(t, self.name.ids.last().map_or("", |x| x.as_str()));
                 ctx.builder.build_store(a, v);
+                types::utils::track_drop(&self.name, a, &val.data_type, ctx);
+                types::utils::track_local(&self.name, a, self.loc.clone(), ctx);
                 ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
                     comp_val: Some(PointerValue(a)),
                     inter_val: val.inter_val,
@@ -425,6 +466,7 @@ pub struct MutDefAST {
 }
 impl AST for MutDefAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.val.res_type(ctx)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let mut errs = vec![];
@@ -507,12 +549,16 @@ impl AST for MutDefAST {
                         Err(IntoTypeError::DoesNotExist(name)) => {
                             errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                             None
+                        },
+                        Err(IntoTypeError::NotAType(name)) => {
+                            errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                            None
                         }
                     }
                 }) {t} else if t2 == Type::IntLiteral {Type::Int(64, false)} else if let Type::Reference(b, _) = t2 {*b} else {t2};
                 match ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
                     comp_val: dt.llvm_type(ctx).map(|t| {
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         match link_type {
                             None => {},
                             Some(WeakAny) => gv.set_linkage(ExternalWeak),
@@ -560,6 +606,10 @@ impl AST for MutDefAST {
                         Err(IntoTypeError::DoesNotExist(name)) => {
                             errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                             None
+                        },
+                        Err(IntoTypeError::NotAType(name)) => {
+                            errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                            None
                         }
                     };
                     t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -570,7 +620,7 @@ impl AST for MutDefAST {
                     }
                     else {
                         let t = dt.llvm_type(ctx).unwrap();
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         gv.set_constant(true);
                         gv.set_initializer(&v);
                         if let Some(link) = link_type {gv.set_linkage(link)}
@@ -625,6 +675,10 @@ impl AST for MutDefAST {
                                 Err(IntoTypeError::DoesNotExist(name)) => {
                                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                     None
+                                },
+                                Err(IntoTypeError::NotAType(name)) => {
+                                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                    None
                                 }
                             };
                             t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -636,10 +690,10 @@ impl AST for MutDefAST {
                         ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {good: Cell::new(true), ..val})))
                     }
                     else {
-                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| format!("{}", self.name)).as_str());
+                        let gv = ctx.module.add_global(t, None, linkas.unwrap_or_else(|| ctx.mangle(&self.name)).as_str());
                         gv.set_constant(false);
                         if let Some(link) = link_type {gv.set_linkage(link)}
-                        let f = ctx.module.add_function(format!("__internals.init.{}", self.name).as_str(), ctx.context.void_type().fn_type(&[], false), Some(inkwell::module::Linkage::Private));
+                        let f = ctx.module.add_function(format!("__internals.init.{}", ctx.mangle(&self.name)).as_str(), ctx.context.void_type().fn_type(&[], false), Some(inkwell::module::Linkage::Private));
                         let entry = ctx.context.append_basic_block(f, "entry");
                         let old_ip = ctx.builder.get_insert_block();
                         ctx.builder.position_at_end(entry);
@@ -666,6 +720,10 @@ impl AST for MutDefAST {
                                 Err(IntoTypeError::DoesNotExist(name)) => {
                                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                     None
+                                },
+                                Err(IntoTypeError::NotAType(name)) => {
+                                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                    None
                                 }
                             };
                             t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -719,6 +777,10 @@ impl AST for MutDefAST {
                             Err(IntoTypeError::DoesNotExist(name)) => {
                                 errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                                 None
+                            },
+                            Err(IntoTypeError::NotAType(name)) => {
+                                errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                                None
                             }
                         };
                         t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -775,20 +837,32 @@ impl AST for MutDefAST {
                     Err(IntoTypeError::DoesNotExist(name)) => {
                         errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                         None
+                    },
+                    Err(IntoTypeError::NotAType(name)) => {
+                        errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                        None
                     }
                 };
                 t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
             }) {t} else if t2 == Type::IntLiteral {(Type::Int(64, false), "INFALLIBLE".to_string())} else if let Type::Reference(b, _) = t2 {(*b, "INFALLIBLE".to_string())} else {(t2, "INFALLIBLE".to_string())};
+            let borrow = match &dt {
+                Type::Reference(_, m) => Some(*m),
+                Type::Borrow(_) => Some(false),
+                _ => None
+            }.and_then(|m| types::utils::check_borrow(&val, m, self.loc.clone(), &[], ctx).unwrap_or_else(|e| {errs.push(e); None}));
             let val = types::utils::impl_convert(val, dt.clone(), ctx).unwrap_or_else(|| {
                 errs.push(Error::new(self.loc.clone(), 311, err));
                 Variable::error()
             });
+            if let Some(b) = borrow {types::utils::hold_borrow(b, ctx);}
             match if ctx.is_const.get() {
                 ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {good: Cell::new(true), ..val})))
             } 
             else if let (Some(t), Some(v)) = (val.data_type.llvm_type(ctx), val.comp_val) {
                 let a = ctx.builder.build_alloca(t, self.name.ids.last().map_or("", |x| x.as_str()));
                 ctx.builder.build_store(a, v);
+                types::utils::track_drop(&self.name, a, &val.data_type, ctx);
+                types::utils::track_local(&self.name, a, self.loc.clone(), ctx);
                 ctx.with_vars(|v| v.insert(&self.name, Symbol::Variable(Variable {
                     comp_val: Some(PointerValue(a)),
                     inter_val: val.inter_val,
@@ -828,6 +902,8 @@ impl AST for MutDefAST {
 impl MutDefAST {
     pub fn new(loc: Location, name: DottedName, val: Box<dyn AST>, type_: Option<ParsedType>, annotations: Vec<(String, Option<String>)>, global: bool) -> Self {MutDefAST {loc, name, val, type_, annotations, global}}
 }
+// moved-from locals with destructors have already given up their value, so using them is an error rather than a warning
+fn owns_drop<'ctx>(x: &Variable<'ctx>, ctx: &CompCtx<'ctx>) -> bool {matches!(&x.data_type, Type::Reference(b, _) if types::utils::needs_drop(b, ctx))}
 pub struct VarGetAST {
     loc: Location,
     pub name: DottedName
@@ -837,17 +913,44 @@ impl VarGetAST {
 }
 impl AST for VarGetAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn var_name(&self) -> Option<&DottedName> {Some(&self.name)}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {
-        if let Ok(Symbol::Variable(x)) = ctx.with_vars(|v| v.lookup(&self.name)) {x.data_type.clone()}
-        else {Type::Null}
+        match ctx.with_vars(|v| v.lookup(&self.name)) {
+            Ok(Symbol::Variable(x)) => x.data_type.clone(),
+            Err(UndefVariable::NotAModule(idx)) => if let Ok(Symbol::Variable(x)) = ctx.with_vars(|v| v.lookup(&self.name.start(idx))) {
+                let mut ids = self.name.ids[(idx + 1)..].iter();
+                let first = if let (Type::TypeData, Some(InterData::Type(t))) = (&x.data_type, &x.inter_val) {
+                    ids.next().and_then(|f| types::utils::variant_type(t, f).or_else(|| types::utils::assoc_function(t, f, ctx).map(|v| v.data_type)))
+                } else {Some(x.data_type.clone())};
+                first.and_then(|t| ids.try_fold(t, |t, f| types::utils::field_type(&t, f))).unwrap_or(Type::Null)
+            } else {Type::Null},
+            _ => Type::Null
+        }
     }
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         match ctx.with_vars(|v| v.lookup(&self.name)) {
-            Ok(Symbol::Variable(x)) =>
-                (x.clone(), if x.good.get() {if !x.data_type.copyable() {x.good.set(false);} vec![]}
-                else {vec![Error::new(self.loc.clone(), 90, format!("{} has been moved from and is now in an undefined state", self.name))]}),
+            Ok(Symbol::Variable(x)) => if x.good.get() {
+                if !x.data_type.copyable() {x.good.set(false);}
+                (x.clone(), types::utils::check_access(&self.name, x, self.loc.clone(), ctx).into_iter().collect())
+            }
+            else if owns_drop(x, ctx) {(Variable::error(), vec![Error::new(self.loc.clone(), 369, format!("{} is used after being moved from", self.name))])}
+            else {(x.clone(), vec![Error::new(self.loc.clone(), 90, format!("{} has been moved from and is now in an undefined state", self.name))])},
             Ok(Symbol::Module(_)) => (Variable::error(), vec![Error::new(self.loc.clone(), 322, format!("{} is not a variable", self.name))]),
-            Err(UndefVariable::NotAModule(idx)) => (Variable::error(), vec![Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(idx)))]),
+            Err(UndefVariable::NotAModule(idx)) => match ctx.with_vars(|v| v.lookup(&self.name.start(idx))) {
+                Ok(Symbol::Variable(x)) => {
+                    if !x.good.get() {return (Variable::error(), vec![if owns_drop(x, ctx) {Error::new(self.loc.clone(), 369, format!("{} is used after being moved from", self.name.start(idx)))} else {Error::new(self.loc.clone(), 90, format!("{} has been moved from and is now in an undefined state", self.name.start(idx)))}])}
+                    if let Some(err) = types::utils::check_access(&self.name.start(idx), x, self.loc.clone(), ctx) {return (Variable::error(), vec![err])}
+                    let mut val = x.clone();
+                    for f in self.name.ids[(idx + 1)..].iter() {
+                        let err = format!("value of type {} has no field {f}", val.data_type);
+                        val = if let Some(v) = types::utils::field(val, f, ctx) {v} else {
+                            return (Variable::error(), vec![Error::new(self.loc.clone(), 324, err)])
+                        };
+                    }
+                    (val, vec![])
+                },
+                _ => (Variable::error(), vec![Error::new(self.loc.clone(), 320, format!("{} is not a module", self.name.start(idx)))])
+            },
             Err(UndefVariable::DoesNotExist(idx)) => (Variable::error(), vec![Error::new(self.loc.clone(), 323, format!("{} does not exist", self.name.start(idx)))])
         }
     }
@@ -867,6 +970,7 @@ pub struct ConstDefAST {
 }
 impl AST for ConstDefAST {
     fn loc(&self) -> Location {self.loc.clone()}
+    fn children(&self) -> Vec<&dyn AST> {vec![&*self.val]}
     fn res_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Type {self.val.res_type(ctx)}
     fn codegen<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Variable<'ctx>, Vec<Error>) {
         let mut errs = self.annotations.iter().map(|(x, _)| Error::new(self.loc.clone(), 410, format!("unknown annotation {x:?} for variable definition"))).collect::<Vec<_>>();
@@ -894,6 +998,10 @@ impl AST for ConstDefAST {
                 Err(IntoTypeError::DoesNotExist(name)) => {
                     errs.push(Error::new(self.loc.clone(), 321, format!("{name} does not exist")));
                     None
+                },
+                Err(IntoTypeError::NotAType(name)) => {
+                    errs.push(Error::new(self.loc.clone(), 322, format!("{name} is not a type")));
+                    None
                 }
             };
             t.map(|x| (x.clone(), format!("cannot convert value of type {} to {x}", t2)))
@@ -1,14 +1,73 @@
-use inkwell::{context::Context, module::Module, builder::Builder};
+use inkwell::{context::Context, module::Module, builder::Builder, basic_block::BasicBlock};
 use crate::*;
 use std::mem::MaybeUninit;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+#[derive(Clone)]
+pub struct LoopTarget<'ctx> {
+    pub label: Option<String>,
+    pub cont: BasicBlock<'ctx>,
+    pub brk: BasicBlock<'ctx>,
+    pub depth: usize, // number of scopes outside of the loop
+    pub moves: Vec<DottedName> // locals declared outside of the loop that are moved inside of it
+}
+#[derive(Clone)]
+pub struct RetTarget<'ctx> {
+    pub ret: Type,
+    pub exit: BasicBlock<'ctx>,
+    pub phi: Option<inkwell::values::PhiValue<'ctx>>
+}
+#[derive(Clone)]
+pub struct DropTarget<'ctx> {
+    pub name: DottedName,
+    pub ptr: inkwell::values::PointerValue<'ctx>,
+    pub flag: inkwell::values::PointerValue<'ctx>, // an i1 that's cleared when the value is moved out
+    pub data_type: Type
+}
+pub struct DeferTarget<'ctx> {
+    pub val: std::rc::Rc<dyn AST>,
+    pub scope: std::collections::HashMap<String, Symbol<'ctx>>, // the locals visible where the expression was deferred
+    pub reported: RefCell<Vec<(Location, u64)>> // errors that have already been reported, since the expression is generated on each exit
+}
+#[derive(Clone)]
+pub struct BorrowTarget<'ctx> {
+    pub name: DottedName, // the borrowed local
+    pub ptr: inkwell::values::PointerValue<'ctx>,
+    pub is_mut: bool,
+    pub loc: Location,
+    pub via: Option<inkwell::values::PointerValue<'ctx>> // the pointer the holder uses, if it holds one directly
+}
+#[derive(Clone, Default)]
+pub struct BorrowFrame<'ctx> {
+    pub locals: Vec<(DottedName, inkwell::values::PointerValue<'ctx>, Location)>,
+    pub borrows: Vec<BorrowTarget<'ctx>>, // borrows held by locals in this scope, which last until it ends
+    pub closures: Vec<(inkwell::values::PointerValue<'ctx>, DottedName, Location)> // environments of closures that capture a local by reference, and the first local they capture
+}
+#[derive(Clone)]
+pub struct CoroTarget<'ctx> {
+    pub id: llvm_sys::prelude::LLVMValueRef,
+    pub promise: Option<inkwell::values::PointerValue<'ctx>>,
+    pub ret: Type,
+    pub cleanup: BasicBlock<'ctx>,
+    pub suspend: BasicBlock<'ctx>
+}
 pub struct CompCtx<'ctx> {
     pub flags: Flags,
     vars: Cell<MaybeUninit<Box<VarMap<'ctx>>>>,
     pub context: &'ctx Context,
     pub module: Module<'ctx>,
     pub builder: Builder<'ctx>,
-    pub is_const: Cell<bool>
+    pub is_const: Cell<bool>,
+    pub loops: RefCell<Vec<LoopTarget<'ctx>>>,
+    pub coro: RefCell<Option<CoroTarget<'ctx>>>,
+    pub ret: RefCell<Option<RetTarget<'ctx>>>,
+    pub drops: RefCell<Vec<Vec<DropTarget<'ctx>>>>,
+    pub defers: RefCell<Vec<Vec<DeferTarget<'ctx>>>>,
+    pub borrows: RefCell<Vec<BorrowFrame<'ctx>>>,
+    pub prefix: RefCell<Vec<String>>,
+    pub generic_args: RefCell<Option<Vec<Type>>>,
+    pub const_depth: Cell<usize>,
+    pub const_steps: Cell<usize>,
+    pub const_ret: RefCell<Option<(Type, Option<InterData>)>> // the return type of the function being evaluated at compile time, and its value once it's returned
 }
 impl<'ctx> CompCtx<'ctx> {
     pub fn new(ctx: &'ctx Context, name: &str) -> Self {
@@ -18,7 +77,18 @@ impl<'ctx> CompCtx<'ctx> {
             context: ctx,
             module: ctx.create_module(name),
             builder: ctx.create_builder(),
-            is_const: Cell::new(false)
+            is_const: Cell::new(false),
+            loops: RefCell::new(vec![]),
+            coro: RefCell::new(None),
+            ret: RefCell::new(None),
+            drops: RefCell::new(vec![]),
+            defers: RefCell::new(vec![]),
+            borrows: RefCell::new(vec![]),
+            prefix: RefCell::new(vec![]),
+            generic_args: RefCell::new(None),
+            const_depth: Cell::new(0),
+            const_steps: Cell::new(0),
+            const_ret: RefCell::new(None)
         }
     }
     pub fn with_flags(ctx: &'ctx Context, name: &str, flags: Flags) -> Self {
@@ -28,7 +98,18 @@ impl<'ctx> CompCtx<'ctx> {
             context: ctx,
             module: ctx.create_module(name),
             builder: ctx.create_builder(),
-            is_const: Cell::new(false)
+            is_const: Cell::new(false),
+            loops: RefCell::new(vec![]),
+            coro: RefCell::new(None),
+            ret: RefCell::new(None),
+            drops: RefCell::new(vec![]),
+            defers: RefCell::new(vec![]),
+            borrows: RefCell::new(vec![]),
+            prefix: RefCell::new(vec![]),
+            generic_args: RefCell::new(None),
+            const_depth: Cell::new(0),
+            const_steps: Cell::new(0),
+            const_ret: RefCell::new(None)
         }
     }
     pub fn with_vars<R, F: FnOnce(&'ctx mut VarMap<'ctx>) -> R>(&self, f: F) -> R {
@@ -37,6 +118,11 @@ impl<'ctx> CompCtx<'ctx> {
         self.vars.set(MaybeUninit::new(val));
         out
     }
+    pub fn mangle(&self, name: &DottedName) -> String {
+        let prefix = self.prefix.borrow();
+        if name.global || prefix.is_empty() {format!("{name}")}
+        else {format!("{}.{name}", prefix.join("."))}
+    }
     pub fn map_vars<F: FnOnce(Box<VarMap<'ctx>>) -> Box<VarMap<'ctx>>>(&self, f: F) -> &Self {
         let val = self.vars.replace(MaybeUninit::uninit());
         self.vars.set(MaybeUninit::new(unsafe {f(val.assume_init())}));
@@ -5,7 +5,8 @@ pub enum IntoTypeError {
     NotAnInt(String),
     NotCompileTime,
     NotAModule(String),
-    DoesNotExist(String)
+    DoesNotExist(String),
+    NotAType(String)
 }
 pub enum ParsedType {
     Error,
@@ -17,16 +18,35 @@ pub enum ParsedType {
     Pointer(Box<ParsedType>, bool),
     Reference(Box<ParsedType>, bool),
     Borrow(Box<ParsedType>),
+    Optional(Box<ParsedType>),
     UnsizedArray(Box<ParsedType>),
     SizedArray(Box<ParsedType>, Box<dyn AST>),
     TypeOf(Box<dyn AST>),
     Other(DottedName),
 }
 impl ParsedType {
+    pub fn from_name(name: DottedName) -> Self {
+        if !name.global && name.ids.len() == 1 {
+            match name.ids[0].as_str() {
+                "isize" => return ISize,
+                "usize" => return USize,
+                "f16" => return F16,
+                "f32" => return F32,
+                "f64" => return F64,
+                "f128" => return F128,
+                "bool" => return Bool,
+                "null" => return Null,
+                x if x.len() > 1 && x.starts_with('i') && x[1..].bytes().all(|c| c.is_ascii_digit()) => if let Ok(size) = x[1..].parse() {return Int(size)},
+                x if x.len() > 1 && x.starts_with('u') && x[1..].bytes().all(|c| c.is_ascii_digit()) => if let Ok(size) = x[1..].parse() {return UInt(size)},
+                _ => {}
+            }
+        }
+        Other(name)
+    }
     pub fn into_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> (Result<Type, IntoTypeError>, Vec<super::Error>) {
         (match self {
             Error | Null => Ok(Type::Null),
-            Bool => Ok(Type::Int(1, false)),
+            Bool => Ok(Type::Bool),
             Int(size) => Ok(Type::Int(*size, false)),
             UInt(size) => Ok(Type::Int(*size, true)),
             ISize => Ok(Type::Int(64, false)),
@@ -39,6 +59,10 @@ impl ParsedType {
                 let (base, errs) = base.into_type(ctx);
                 return (base.map(Box::new).map(Type::Borrow), errs)
             },
+            Optional(base) => {
+                let (base, errs) = base.into_type(ctx);
+                return (base.map(Box::new).map(Type::Optional), errs)
+            },
             Pointer(base, m) => {
                 let (base, errs) = base.into_type(ctx);
                 return (base.map(|b| Type::Pointer(Box::new(b), *m)), errs)
@@ -75,7 +99,12 @@ impl ParsedType {
                 ctx.is_const.set(old_const);
                 return (Ok(var.data_type), errs);
             },
-            Other(name) => Err(IntoTypeError::DoesNotExist(format!("{}", name)))
+            Other(name) => match ctx.with_vars(|v| v.lookup(name)) {
+                Ok(Symbol::Variable(Variable {inter_val: Some(InterData::Type(t)), ..})) => Ok((**t).clone()),
+                Ok(_) => Err(IntoTypeError::NotAType(format!("{}", name))),
+                Err(UndefVariable::NotAModule(x)) => Err(IntoTypeError::NotAModule(format!("{}", name.start(x)))),
+                Err(UndefVariable::DoesNotExist(_)) => Err(IntoTypeError::DoesNotExist(format!("{}", name)))
+            }
         }, vec![])
     }
 }
@@ -96,6 +125,7 @@ impl std::fmt::Debug for ParsedType { // Debug isn't implemented for Box
             Pointer(base, m) => write!(f, "Pointer({}, {m})", *base),
             Reference(base, m) => write!(f, "Reference({}, {m})", *base),
             Borrow(base) => write!(f, "Borrow({})", *base),
+            Optional(base) => write!(f, "Optional({})", *base),
             UnsizedArray(base) => write!(f, "UnsizedArray({})", *base),
             SizedArray(base, ast) => write!(f, "SizedArray({base}, {})", ast.to_code()),
             TypeOf(value) => write!(f, "TypeOf({})", value.to_code()),
@@ -120,6 +150,7 @@ impl std::fmt::Display for ParsedType {
             Pointer(base, m) => write!(f, "{} {}*", *base, if *m {"mut"} else {"const"}),
             Reference(base, m) => write!(f, "{} {}&", *base, if *m {"mut"} else {"const"}),
             Borrow(base) => write!(f, "{}^", *base),
+            Optional(base) => write!(f, "{}?", *base),
             UnsizedArray(base) => write!(f, "{}[]", *base),
             SizedArray(base, ast) => write!(f, "{base}[{}]", ast.to_code()),
             TypeOf(value) => write!(f, "typeof {}", value.to_code()),
@@ -3,4 +3,4 @@ pub mod ast;
 pub mod ops;
 
 pub use lexer::{Token, TokenData, lex};
-pub use ast::parse;
+pub use ast::{parse, parse_generic, parse_expression};
@@ -31,7 +31,7 @@ fn parse_type(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Pars
                 name.ids.push(str.clone());
                 idx += 1;
             }
-            Special('&') | Special('*') | Special('^') | Special('[') => break,
+            Special('&') | Special('*') | Special('^') | Special('[') | Operator(_) => break,
             Keyword(x) if x == "const" || x == "mut" => break,
             x => {
                 errs.push(Error::new(toks[idx].loc.clone(), 210, format!("unexpected token {:?} in type", x)));
@@ -135,6 +135,7 @@ fn parse_type(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Pars
                 "&&" => {out = ParsedType::Reference(Box::new(ParsedType::Reference(Box::new(out), false)), false); idx += 1;},
                 "**" => {out = ParsedType::Pointer(Box::new(ParsedType::Pointer(Box::new(out), false)), false); idx += 1;},
                 "^^" => {out = ParsedType::Borrow(Box::new(ParsedType::Borrow(Box::new(out)))); idx += 1;},
+                "?" => {out = ParsedType::Optional(Box::new(out)); idx += 1;},
                 _ => {
                     errs.push(Error::new(toks[idx].loc, 220, format!("unexpected token {:?} in type", toks[idx].data)));
                     break;
@@ -163,15 +164,16 @@ fn parse_type(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Pars
     }
     (out, idx + 1, errs)
 }
-#[allow(unreachable_code)]
 fn parse_paths(toks: &[Token], is_nested: bool) -> (CompoundDottedName, usize, Vec<Error>) {
-    let mut idx = 1;
+    let mut idx = 0;
     let mut errs = vec![];
-    let (mut name, mut lwp) = match &toks[0].data {
-        Special('.') => (CompoundDottedName::new(vec![], true), true),
-        Identifier(str) => (CompoundDottedName::new(vec![CompoundDottedNameSegment::Identifier(str.clone())], false), false),
-        x => return (CompoundDottedName::local(CompoundDottedNameSegment::Identifier(String::new())), 2, vec![Error::new(toks[0].loc.clone(), 210, format!("unexpected token {:?} in identifier", x))])
-    };
+    if toks.len() == 0 {return (CompoundDottedName::local(CompoundDottedNameSegment::Identifier(String::new())), 1, vec![])}
+    let mut name = CompoundDottedName::new(vec![], false);
+    let mut lwp = true;
+    if toks[0].data == Special('.') {
+        name.global = true;
+        idx = 1;
+    }
     while idx < toks.len() {
         match &toks[idx].data {
             Special(';') => break,
@@ -184,16 +186,18 @@ fn parse_paths(toks: &[Token], is_nested: bool) -> (CompoundDottedName, usize, V
                 idx += 1;
             }
             Identifier(s) => {
-                if !lwp {
-                    if let Some(CompoundDottedNameSegment::Glob(ref x)) = name.ids.last() {
-                        name.ids.push(CompoundDottedNameSegment::Glob(x.to_owned() + s));
-                    }
-                    else {
-                        errs.push(Error::new(toks[idx].loc, 212, "identifier cannot contain consecutive identifiers".to_string()).note(Note::new(toks[idx].loc, "Did you forget a period?".to_string())))
+                if lwp {name.ids.push(CompoundDottedNameSegment::Identifier(s.clone()))}
+                else {
+                    match name.ids.pop() {
+                        Some(CompoundDottedNameSegment::Glob(x)) => name.ids.push(CompoundDottedNameSegment::Glob(x + s)),
+                        Some(x) => {
+                            name.ids.push(x);
+                            errs.push(Error::new(toks[idx].loc, 212, "identifier cannot contain consecutive identifiers".to_string()).note(Note::new(toks[idx].loc, "Did you forget a period?".to_string())))
+                        },
+                        None => unreachable!("if the last element was not a period, then there is at least one element in name.ids")
                     }
                 }
                 lwp = false;
-                name.ids.push(CompoundDottedNameSegment::Identifier(s.clone()));
                 idx += 1;
             }
             Operator(ref x) if x == "*" => {
@@ -204,19 +208,60 @@ fn parse_paths(toks: &[Token], is_nested: bool) -> (CompoundDottedName, usize, V
                     match name.ids.pop() {
                         Some(CompoundDottedNameSegment::Identifier(x)) |
                         Some(CompoundDottedNameSegment::Glob(x)) => name.ids.push(CompoundDottedNameSegment::Glob(x + "*")),
-                        Some(CompoundDottedNameSegment::Group(_)) => errs.push(Error::new(toks[idx].loc, 212, "identifier cannot contain consecutive identifiers".to_string()).note(Note::new(toks[idx].loc, "Did you forget a period?".to_string()))),
+                        Some(x) => {
+                            name.ids.push(x);
+                            errs.push(Error::new(toks[idx].loc, 212, "identifier cannot contain consecutive identifiers".to_string()).note(Note::new(toks[idx].loc, "Did you forget a period?".to_string())))
+                        },
                         None => unreachable!("if the last element was not a period, then there is at least one element in name.ids")
                     }
                 }
                 lwp = false;
                 idx += 1;
             },
+            Special('{') => {
+                if !lwp {
+                    errs.push(Error::new(toks[idx].loc, 212, "identifier cannot contain consecutive identifiers".to_string()).note(Note::new(toks[idx].loc, "Did you forget a period?".to_string())))
+                }
+                let start = toks[idx].loc;
+                let mut group = vec![];
+                idx += 1;
+                loop {
+                    if idx >= toks.len() {
+                        errs.push(Error::new(start, 254, "unmatched '{' in import group".to_string()));
+                        break;
+                    }
+                    if toks[idx].data == Special('}') {
+                        idx += 1;
+                        break;
+                    }
+                    let (n, i, mut es) = parse_paths(&toks[idx..], true);
+                    errs.append(&mut es);
+                    group.push(n.ids);
+                    idx += i - 1;
+                    match toks.get(idx).map(|x| &x.data) {
+                        Some(Special(',')) => idx += 1,
+                        Some(Special('}')) => {
+                            idx += 1;
+                            break;
+                        },
+                        _ => {
+                            errs.push(Error::new(start, 254, "unmatched '{' in import group".to_string()));
+                            break;
+                        }
+                    }
+                }
+                name.ids.push(CompoundDottedNameSegment::Group(group));
+                lwp = false;
+            },
             x => {
                 errs.push(Error::new(toks[idx].loc, 210, format!("unexpected token {:?} in identifier", x)));
                 break;
             }
         }
     }
+    if lwp && errs.is_empty() {
+        errs.push(Error::new(toks[idx.min(toks.len() - 1)].loc, 213, "import path cannot end with a period".to_string()));
+    }
     (name, idx + 1, errs)
 }
 fn parse_path(toks: &[Token], terminators: &'static str) -> (DottedName, usize, Vec<Error>) {
@@ -255,6 +300,234 @@ fn parse_path(toks: &[Token], terminators: &'static str) -> (DottedName, usize,
     }
     (name, idx + 1, errs)
 }
+fn parse_generics(toks: &[Token]) -> (Vec<(String, Vec<DottedName>)>, usize, Vec<Error>) {
+    let mut out: Vec<(String, Vec<DottedName>)> = vec![];
+    let mut errs = vec![];
+    let mut lwc = true;
+    let mut idx = 1;
+    while idx < toks.len() {
+        match &toks[idx].data {
+            Special(']') => {
+                if lwc && out.len() > 0 {errs.push(Error::new(toks[idx].loc.clone(), 299, "expected a type parameter after ','".to_string()))}
+                if out.len() == 0 {errs.push(Error::new(toks[0].loc.clone(), 299, "type parameter list cannot be empty".to_string()))}
+                return (out, idx + 1, errs)
+            },
+            Special(',') if !lwc => lwc = true,
+            Identifier(x) if lwc => {
+                if out.iter().any(|(g, _)| g == x) {errs.push(Error::new(toks[idx].loc.clone(), 299, format!("type parameter {x} is already defined")))}
+                else {out.push((x.clone(), vec![]))}
+                lwc = false;
+            },
+            Special(':') if !lwc => { // trait bounds, separated by '+'
+                loop {
+                    let (name, i, mut es) = parse_path(&toks[(idx + 1)..], ",]+");
+                    errs.append(&mut es);
+                    if let Some((_, bounds)) = out.last_mut() {bounds.push(name);}
+                    idx += i;
+                    if toks.get(idx).map(|x| &x.data) != Some(&Operator("+".to_string())) {break}
+                }
+                continue;
+            },
+            x => errs.push(Error::new(toks[idx].loc.clone(), 299, format!("unexpected token {x:?} in type parameter list")))
+        }
+        idx += 1;
+    }
+    errs.push(Error::new(toks[0].loc.clone(), 299, "unterminated type parameter list".to_string()));
+    (out, idx, errs)
+}
+fn parse_struct(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let (name, mut i, mut errs) = parse_path(&toks[1..], "{;");
+    if toks.get(i).map(|x| &x.data) != Some(&Special('{')) {
+        errs.push(Error::new(start, 287, "expected '{' after struct name".to_string()));
+        return (null(), i.min(toks.len()), errs)
+    }
+    let open = toks[i].loc.clone();
+    i += 1;
+    let mut fields = vec![];
+    loop {
+        match toks.get(i).map(|x| &x.data) {
+            None => {
+                errs.push(Error::new(open, 254, "unmatched '{'".to_string()));
+                break
+            },
+            Some(Special('}')) => {
+                i += 1;
+                break
+            },
+            Some(Special(',') | Special(';')) => i += 1,
+            Some(Identifier(f)) if toks.get(i + 1).map(|x| &x.data) == Some(&Special(':')) => {
+                let (ty, idx, mut es) = parse_type(&toks[(i + 2)..], ",;}", flags);
+                errs.append(&mut es);
+                fields.push((f.clone(), toks[i].loc.clone(), ty));
+                i += idx + 1;
+            },
+            Some(x) => {
+                errs.push(Error::new(toks[i].loc.clone(), 288, format!("expected a field name followed by ':', got {x:?}")));
+                i += 1;
+            }
+        }
+    }
+    (Box::new(StructDefAST::new(start, name, fields)), i, errs)
+}
+fn parse_enum(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let (name, mut i, mut errs) = parse_path(&toks[1..], "{;");
+    if toks.get(i).map(|x| &x.data) != Some(&Special('{')) {
+        errs.push(Error::new(start, 287, "expected '{' after enum name".to_string()));
+        return (null(), i.min(toks.len()), errs)
+    }
+    let open = toks[i].loc.clone();
+    i += 1;
+    let mut variants = vec![];
+    loop {
+        match toks.get(i).map(|x| &x.data) {
+            None => {
+                errs.push(Error::new(open, 254, "unmatched '{'".to_string()));
+                break
+            },
+            Some(Special('}')) => {
+                i += 1;
+                break
+            },
+            Some(Special(',') | Special(';')) => i += 1,
+            Some(Identifier(v)) if toks.get(i + 1).map(|x| &x.data) == Some(&Special('(')) => {
+                let (ty, idx, mut es) = parse_type(&toks[(i + 2)..], ")", flags);
+                errs.append(&mut es);
+                if toks.get(i + 1 + idx).map(|x| &x.data) != Some(&Special(')')) {
+                    errs.push(Error::new(toks[i + 1].loc.clone(), 250, "unmatched '('".to_string()));
+                }
+                variants.push((v.clone(), toks[i].loc.clone(), Some(ty)));
+                i += idx + 2;
+            },
+            Some(Identifier(v)) => {
+                variants.push((v.clone(), toks[i].loc.clone(), None));
+                i += 1;
+            },
+            Some(x) => {
+                errs.push(Error::new(toks[i].loc.clone(), 296, format!("expected a variant name, got {x:?}")));
+                i += 1;
+            }
+        }
+    }
+    (Box::new(EnumDefAST::new(start, name, variants)), i, errs)
+}
+fn parse_trait(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let (name, mut i, mut errs) = parse_path(&toks[1..], "{;");
+    if toks.get(i).map(|x| &x.data) != Some(&Special('{')) {
+        errs.push(Error::new(start, 287, "expected '{' after trait name".to_string()));
+        return (null(), i.min(toks.len()), errs)
+    }
+    let open = toks[i].loc.clone();
+    i += 1;
+    let mut methods = vec![];
+    // on an error, skip to the end of the current method declaration
+    let skip = |i: usize| toks.iter().skip(i).position(|x| matches!(x.data, Special(';') | Special('}'))).map_or(toks.len(), |p| i + p);
+    loop {
+        match toks.get(i).map(|x| &x.data) {
+            None => {
+                errs.push(Error::new(open, 254, "unmatched '{'".to_string()));
+                break
+            },
+            Some(Special('}')) => {
+                i += 1;
+                break
+            },
+            Some(Special(',') | Special(';')) => i += 1,
+            Some(Keyword(k)) if k == "fn" => {
+                let loc = toks[i].loc.clone();
+                let mname = if let Some(Identifier(n)) = toks.get(i + 1).map(|x| &x.data) {n.clone()} else {
+                    errs.push(Error::new(loc, 262, "expected a method name after 'fn'".to_string()));
+                    i = skip(i);
+                    continue
+                };
+                i += 2;
+                if toks.get(i).map(|x| &x.data) != Some(&Special('(')) {
+                    errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 262, format!("expected parameters after method name {mname}")));
+                    i = skip(i);
+                    continue
+                }
+                i += 1;
+                let is_mut = toks.get(i).map(|x| &x.data) == Some(&Keyword("mut".to_string()));
+                if is_mut {i += 1;}
+                if toks.get(i).map(|x| &x.data) == Some(&Identifier("self".to_string())) {i += 1;}
+                else {errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 263, format!("the first parameter of trait method {mname} must be self")));}
+                let mut params = vec![];
+                while toks.get(i).map(|x| &x.data) == Some(&Special(',')) {
+                    i += 1;
+                    let pname = if let Some(Identifier(n)) = toks.get(i).map(|x| &x.data) {
+                        i += 1;
+                        n.clone()
+                    }
+                    else {
+                        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 277, "expected a parameter name".to_string()));
+                        String::new()
+                    };
+                    if toks.get(i).map(|x| &x.data) == Some(&Special(':')) {
+                        let (ty, idx, mut es) = parse_type(&toks[(i + 1)..], ",)", flags);
+                        i += idx;
+                        errs.append(&mut es);
+                        params.push((pname, ty));
+                    }
+                    else {
+                        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 240, "function parameters must have explicit types".to_string()));
+                        params.push((pname, ParsedType::Error));
+                    }
+                }
+                match toks.get(i).map(|x| &x.data) {
+                    Some(Special(')')) => i += 1,
+                    x => {
+                        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 242, format!("expected ',' or ')' after parameter, got {x:?}")));
+                        i = skip(i);
+                        continue
+                    }
+                }
+                let ret = if toks.get(i).map(|x| &x.data) == Some(&Special(':')) {
+                    let (ty, idx, mut es) = parse_type(&toks[(i + 1)..], ";}", flags);
+                    i += idx;
+                    errs.append(&mut es);
+                    ty
+                } else {ParsedType::Null};
+                methods.push((mname, loc, is_mut, params, ret));
+            },
+            Some(x) => {
+                errs.push(Error::new(toks[i].loc.clone(), 262, format!("expected a method declaration, got {x:?}")));
+                i += 1;
+            }
+        }
+    }
+    (Box::new(TraitDefAST::new(start, name, methods)), i.min(toks.len()), errs)
+}
+fn parse_impl(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let mut errs = vec![];
+    let open = if let Some(open) = toks.iter().position(|x| x.data == Special('{')) {open} else {
+        errs.push(Error::new(start, 264, "expected '{' after type in impl block".to_string()));
+        return (null(), toks.len(), errs)
+    };
+    let for_idx = toks[..open].iter().position(|x| x.data == Keyword("for".to_string()));
+    let tr = for_idx.map(|f| {
+        if f == 1 {errs.push(Error::new(toks[f].loc.clone(), 264, "expected a trait name before 'for'".to_string()));}
+        let (name, _, mut es) = parse_path(&toks[1..f], "");
+        errs.append(&mut es);
+        name
+    });
+    let ts = for_idx.map_or(1, |f| f + 1);
+    if ts >= open {
+        errs.push(Error::new(toks[open].loc.clone(), 264, "expected a type before '{' in impl block".to_string()));
+        return (null(), open + 1, errs)
+    }
+    let (target, _, mut es) = parse_type(&toks[ts..=open], "{", flags);
+    errs.append(&mut es);
+    let (vals, idx, mut es) = parse_tl(&toks[(open + 1)..], flags);
+    errs.append(&mut es);
+    let i = if let Some(idx) = idx {open + idx + 1} else {
+        errs.push(Error::new(toks[open].loc.clone(), 254, "unmatched '{' of impl body".to_string()));
+        toks.len()
+    };
+    (Box::new(ImplAST::new(start, target, tr, vals)), i, errs)
+}
 fn parse_literals(toks: &[Token]) -> (Box<dyn AST>, Vec<Error>) {
     if toks.len() == 0 {return (Box::new(NullAST::new(Location::new("<anonymous>", 0, 0, 0))), vec![])}
     match &toks[0].data {
@@ -299,6 +572,7 @@ fn parse_literals(toks: &[Token]) -> (Box<dyn AST>, Vec<Error>) {
             (Box::new(StringLiteralAST::new(toks[0].loc.clone(), x.clone(), suf.cloned())), errs)
         },
         Identifier(x) if x == "null" => (Box::new(NullAST::new(toks[0].loc.clone())), toks.iter().skip(1).map(|tok| Error::new(tok.loc.clone(), 273, format!("unexpected token {:?} after null", tok.data))).collect()),
+        Identifier(x) if x == "true" || x == "false" => (Box::new(BoolLiteralAST::new(toks[0].loc.clone(), x == "true")), toks.iter().skip(1).map(|tok| Error::new(tok.loc.clone(), 274, format!("unexpected token {:?} after {x}", tok.data))).collect()),
         Identifier(_) | Special('.') => {
             let (name, mut idx, mut errs) = parse_path(toks, "");
             while idx < toks.len() {
@@ -394,6 +668,25 @@ fn parse_groups(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>)
                 ast
             }).collect())), errs)
         },
+        Some(Special('[')) => {
+            let start = toks[0].loc.clone();
+            let mut errs = if toks.last().unwrap().data == Special(']') {vec![]}
+            else {vec![Error::new(toks[0].loc.clone(), 252, "unmatched '['".to_string())]};
+            toks = &toks[1..];
+            let mut vals = vec![];
+            while toks.len() > 0 && toks[0].data != Special(']') {
+                if toks[0].data == Special(',') {
+                    errs.push(Error::new(toks[0].loc.clone(), 285, "expected an array element before ','".to_string()));
+                    toks = &toks[1..];
+                    continue;
+                }
+                let (ast, idx, mut es) = parse_expr(toks, ",]", flags);
+                errs.append(&mut es);
+                vals.push(ast);
+                toks = &toks[idx.min(toks.len())..];
+            }
+            (Box::new(ArrayLiteralAST::new(start, vals)), errs)
+        },
         Some(_) => parse_literals(toks),
         None => (null(), vec![])
     }
@@ -423,11 +716,106 @@ fn parse_calls(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>)
                     args.push(ast);
                     toks = &toks[idx..];
                 }
+                let n = target.len();
+                if let (true, Some(Identifier(name))) = (n > 2 && target[n - 2].data == Special('.'), target.last().map(|x| &x.data)) {
+                    let recv = &target[..(n - 2)];
+                    let loc = target[n - 1].loc.clone();
+                    if recv.iter().all(|x| matches!(x.data, Identifier(_) | Special('.'))) {
+                        let (path, _, mut es) = parse_path(target, "");
+                        errs.append(&mut es);
+                        let mut rpath = path.clone();
+                        rpath.ids.pop();
+                        if rpath.ids.len() > 0 {
+                            return (Box::new(MethodCallAST::new(loc, Box::new(VarGetAST::new(recv[0].loc.clone(), rpath)), name.clone(), args, Some(path))), errs)
+                        }
+                    }
+                    else if matches!(recv.last().map(|x| &x.data), Some(Special(')') | Special(']') | Special('}'))) {
+                        let (recv, mut es) = parse_postfix(recv, flags);
+                        errs.append(&mut es);
+                        return (Box::new(MethodCallAST::new(loc, recv, name.clone(), args, None)), errs)
+                    }
+                }
                 let (target, _, mut es) = parse_expr(target, "", flags);
                 errs.append(&mut es);
                 (Box::new(CallAST::new(target.loc().clone(), target, args)), errs)
             }
         },
+        Some(Special(']')) => {
+            let mut depth = 1;
+            let mut idx = toks.len() - 1;
+            while idx > 0 && depth > 0 {
+                idx -= 1;
+                match &toks[idx].data {
+                    Special(']') => depth += 1,
+                    Special('[') => depth -= 1,
+                    _ => {}
+                }
+            }
+            if idx == 0 || depth > 0 {parse_groups(toks, flags)}
+            else {
+                let (target, ts) = toks.split_at(idx);
+                let loc = ts[0].loc.clone();
+                let mut errs = vec![];
+                let index = if ts.len() == 2 {
+                    errs.push(Error::new(loc, 286, "expected an index between '[' and ']'".to_string()));
+                    null()
+                }
+                else {
+                    let (ast, _, mut es) = parse_expr(&ts[1..(ts.len() - 1)], "", flags);
+                    errs.append(&mut es);
+                    ast
+                };
+                let (target, mut es) = parse_postfix(target, flags);
+                errs.append(&mut es);
+                (Box::new(SubscriptAST::new(loc, target, index)), errs)
+            }
+        },
+        Some(Special('}')) => {
+            let mut depth = 1;
+            let mut idx = toks.len() - 1;
+            while idx > 0 && depth > 0 {
+                idx -= 1;
+                match &toks[idx].data {
+                    Special('}') => depth += 1,
+                    Special('{') => depth -= 1,
+                    _ => {}
+                }
+            }
+            if idx == 0 || depth > 0 || !toks[..idx].iter().all(|x| matches!(x.data, Identifier(_) | Special('.'))) {parse_groups(toks, flags)}
+            else {
+                let (name, _, mut errs) = parse_path(&toks[..idx], "");
+                let mut body = &toks[(idx + 1)..(toks.len() - 1)];
+                let mut fields = vec![];
+                while body.len() > 0 {
+                    match (&body[0].data, body.get(1).map(|x| &x.data), body.get(2).map(|x| &x.data)) {
+                        (Identifier(f), Some(Special(':')), Some(x)) if x != &Special(',') => {
+                            let (ast, i, mut es) = parse_expr(&body[2..], ",", flags);
+                            errs.append(&mut es);
+                            fields.push((f.clone(), body[0].loc.clone(), ast));
+                            body = &body[(i + 2).min(body.len())..];
+                        },
+                        (Identifier(f), Some(Special(':')), _) => {
+                            errs.push(Error::new(body[1].loc.clone(), 289, format!("expected a value for field {f}")));
+                            body = &body[2..];
+                            if body.len() > 0 {body = &body[1..];}
+                        },
+                        (Special(','), _, _) => {
+                            errs.push(Error::new(body[0].loc.clone(), 289, "expected a field before ','".to_string()));
+                            body = &body[1..];
+                        },
+                        (x, _, _) => {
+                            errs.push(Error::new(body[0].loc.clone(), 289, format!("expected a field name followed by ':', got {x:?}")));
+                            body = &body[body.iter().position(|x| x.data == Special(',')).map_or(body.len(), |x| x + 1)..];
+                        }
+                    }
+                }
+                (Box::new(StructLiteralAST::new(toks[0].loc.clone(), name, fields)), errs)
+            }
+        },
+        Some(Identifier(f)) if toks.len() > 2 && toks[toks.len() - 2].data == Special('.') && matches!(toks[toks.len() - 3].data, Special(')') | Special(']') | Special('}')) => {
+            let (target, errs) = parse_postfix(&toks[..(toks.len() - 2)], flags);
+            (Box::new(FieldAST::new(toks[toks.len() - 2].loc.clone(), target, f.clone())), errs)
+        },
         Some(_) => parse_groups(toks, flags),
         None => (null(), vec![]) // technically unreachable
     }
@@ -445,16 +833,65 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
             Keyword(ref x) => match x.as_str() {
                 "module" => {errs.push(Error::new(toks[0].loc.clone(), 275, "local module definitions are not allowed".to_string())); null()},
                 "import" => {
+                    let loc = toks[0].loc;
                     let (name, idx, mut es) = parse_paths(&toks[1..], false);
-                    toks = &toks[idx..];
+                    toks = &toks[idx.min(toks.len())..];
                     errs.append(&mut es);
-                    Box::new(ImportAST::new(toks[0].loc, name))
+                    Box::new(ImportAST::new(loc, name))
                 },
-                "fn" => {
+                "struct" => {
+                    toks = &toks[start_idx..];
+                    let (ast, idx, mut es) = parse_struct(toks, flags);
+                    toks = &toks[idx.min(toks.len())..];
+                    errs.append(&mut es);
+                    ast
+                },
+                "enum" => {
+                    toks = &toks[start_idx..];
+                    let (ast, idx, mut es) = parse_enum(toks, flags);
+                    toks = &toks[idx.min(toks.len())..];
+                    errs.append(&mut es);
+                    ast
+                },
+                "defer" => {
+                    toks = &toks[start_idx..];
+                    let start = toks[0].loc.clone();
+                    if toks.len() == 1 {
+                        errs.push(Error::new(start, 256, "expected an expression after defer".to_string()));
+                        break 'main null();
+                    }
+                    let (ast, idx, mut es) = parse_expr(&toks[1..], ";", flags);
+                    toks = &toks[idx.min(toks.len())..];
+                    errs.append(&mut es);
+                    Box::new(DeferAST::new(start, ast))
+                },
+                "trait" => {
+                    toks = &toks[start_idx..];
+                    let (ast, idx, mut es) = parse_trait(toks, flags);
+                    toks = &toks[idx.min(toks.len())..];
+                    errs.append(&mut es);
+                    ast
+                },
+                "impl" => {
+                    toks = &toks[start_idx..];
+                    let (ast, idx, mut es) = parse_impl(toks, flags);
+                    toks = &toks[idx.min(toks.len())..];
+                    errs.append(&mut es);
+                    ast
+                },
+                "fn" if toks.get(start_idx + 1).map(|x| &x.data) == Some(&Special('(')) => { // closure expression
+                    toks = &toks[start_idx..];
+                    let (ast, idx, mut es) = parse_expr(toks, ";", flags);
+                    errs.append(&mut es);
+                    toks = &toks[(idx - 1)..];
+                    ast
+                },
+                "fn" | "cr" => {
                     let annotations = toks.iter().take(start_idx).filter_map(|x| if let Macro(name, args) = &x.data {Some((name.clone(), args.clone()))} else {None}).collect::<Vec<_>>();
                     toks = &toks[start_idx..];
                     let start = toks[0].loc.clone();
-                    let (mut name, idx, mut es) = parse_path(&toks[1..], "(=;");
+                    let is_cr = toks[0].data == Keyword("cr".to_string());
+                    let (mut name, idx, mut es) = parse_path(&toks[1..], "(=;[");
                     if name.global || name.ids.len() > 1 {
                         errs.push(Error::new(toks[0].loc.clone(), 276, "local function definitions cannot have global names".to_string()));
                         name.global = false;
@@ -462,6 +899,12 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
                     }
                     toks = &toks[idx..];
                     errs.append(&mut es);
+                    let generics = if toks.first().map(|x| &x.data) == Some(&Special('[')) {
+                        let (generics, idx, mut es) = parse_generics(toks);
+                        toks = &toks[idx..];
+                        errs.append(&mut es);
+                        generics
+                    } else {vec![]};
                     if toks.len() == 0 {
                         errs.push(Error::new(unsafe {(*toks.as_ptr().offset(-1)).loc.clone()}, 234, "expected parameters or assignment after function definition".to_string()));
                         break 'main null() as Box<dyn AST>;
@@ -552,7 +995,7 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
                                 Special(';') => {
                                     errs.push(Error::new(toks[0].loc.clone(), 243, "function declaration requires an explicit return type".to_string()));
                                     toks = &toks[1..];
-                                    Box::new(FnDefAST::new(start, name, ParsedType::Error, params, Box::new(NullAST::new(toks[0].loc.clone())), annotations))
+                                    Box::new(FnDefAST::new(start, name, generics.clone(), ParsedType::Error, params, Box::new(NullAST::new(toks[0].loc.clone())), annotations, is_cr))
                                 },
                                 Special(':') => {
                                     let (ty, idx, mut es) = parse_type(&toks[1..], "=;", flags);
@@ -561,22 +1004,22 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
                                     if toks.len() == 0 {
                                         let last = unsafe {(*toks.as_ptr().offset(-1)).loc.clone()};
                                         errs.push(Error::new(last.clone(), 244, "expected function body or semicolon".to_string()));
-                                        break 'main Box::new(FnDefAST::new(start, name, ty, params, Box::new(NullAST::new(last)), annotations));
+                                        break 'main Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, Box::new(NullAST::new(last)), annotations, is_cr));
                                     }
                                     match &toks[0].data {
-                                        Special(';') => break 'main Box::new(FnDefAST::new(start, name, ty, params, Box::new(NullAST::new(toks[0].loc.clone())), annotations)),
+                                        Special(';') => break 'main Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, Box::new(NullAST::new(toks[0].loc.clone())), annotations, is_cr)),
                                         Special('{') => {
                                             errs.push(Error::new(toks[0].loc.clone(), 245, "functions are defined with an '='".to_string()).note(Note::new(toks[0].loc.clone(), "try inserting an '='".to_string())));
                                             let (ast, idx, mut es) = parse_expr(toks, ";", flags);
                                             toks = &toks[idx..];
                                             errs.append(&mut es);
-                                            Box::new(FnDefAST::new(start, name, ty, params, ast, annotations)) as Box<dyn AST>
+                                            Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, ast, annotations, is_cr)) as Box<dyn AST>
                                         },
                                         Operator(x) if x == "=" => {
                                             let (ast, idx, mut es) = parse_expr(&toks[1..], ";", flags);
                                             toks = &toks[(idx + 1)..];
                                             errs.append(&mut es);
-                                            Box::new(FnDefAST::new(start, name, ty, params, ast, annotations)) as Box<dyn AST>
+                                            Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, ast, annotations, is_cr)) as Box<dyn AST>
                                         },
                                         x => {errs.push(Error::new(toks[0].loc.clone(), 244, format!("expected function body or semicolon, got {x:?}"))); null() as Box<dyn AST>}
                                     }
@@ -585,7 +1028,7 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
                                     let (ast, idx, mut es) = parse_expr(&toks[1..], ";", flags);
                                     toks = &toks[(idx + 1)..];
                                     errs.append(&mut es);
-                                    Box::new(FnDefAST::new(start, name, ParsedType::Error, params, ast, annotations))
+                                    Box::new(FnDefAST::new(start, name, generics.clone(), ParsedType::Error, params, ast, annotations, is_cr))
                                 },
                                 x => {errs.push(Error::new(toks[0].loc.clone(), 244, format!("expected function return type or body, got {x:?}"))); null()}
                             }
@@ -595,7 +1038,6 @@ fn parse_statement(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Erro
                         _ => {errs.push(Error::new(toks[0].loc.clone(), 236, format!("expected function parameters, got {:?}", toks[0].data))); null()}
                     }
                 },
-                "cr" => null(),
                 "let" => {
                     let annotations = toks.iter().take(start_idx).filter_map(|x| if let Macro(name, args) = &x.data {Some((name.clone(), args.clone()))} else {None}).collect::<Vec<_>>();
                     toks = &toks[start_idx..];
@@ -909,6 +1351,33 @@ fn parse_binary<'a, F: Clone + for<'r> FnMut(&'r parser::ops::OpType) -> bool>(t
         (ast, errs)
     }
 }
+fn parse_casts(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>) {
+    let mut depth = 0;
+    let mut colon = None;
+    for (n, tok) in toks.iter().enumerate() {
+        match tok.data {
+            Special('(' | '[' | '{') => depth += 1,
+            Special(')' | ']' | '}') => depth -= 1,
+            Special(':') if depth == 0 => colon = Some(n),
+            _ => {}
+        }
+    }
+    if let Some(idx) = colon {
+        if idx == 0 {
+            let (t, _, mut errs) = parse_type(&toks[1..], "", flags);
+            errs.insert(0, Error::new(toks[0].loc.clone(), 292, "expected an expression before ':'".to_string()));
+            return (Box::new(CastAST::new(toks[0].loc.clone(), null(), t)), errs)
+        }
+        let (val, mut errs) = parse_casts(&toks[..idx], flags);
+        let (t, _, mut es) = parse_type(&toks[(idx + 1)..], "", flags);
+        errs.append(&mut es);
+        (Box::new(CastAST::new(toks[idx].loc.clone(), val, t)), errs)
+    }
+    else {
+        let mut it = COBALT_BIN_OPS.split_inclusive(|&x| x == Ltr || x == Rtl);
+        parse_binary(toks, it.next().unwrap(), it, flags)
+    }
+}
 fn parse_splits(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>) {
     let start = toks[0].loc.clone();
     let mut errs = vec![];
@@ -975,8 +1444,7 @@ fn parse_splits(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>)
     match slices.len() {
         0 => (null(), errs),
         1 => {
-            let mut it = COBALT_BIN_OPS.split_inclusive(|&x| x == Ltr || x == Rtl);
-            let (ast, mut es) = parse_binary(slices[0], it.next().unwrap(), it, flags);
+            let (ast, mut es) = parse_casts(slices[0], flags);
             errs.append(&mut es);
             (ast, errs)
         },
@@ -987,7 +1455,451 @@ fn parse_splits(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>)
         }).collect())), errs)
     }
 }
+fn parse_cond(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Option<Box<dyn AST>>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let mut errs = vec![];
+    let mut i = 1;
+    let cond = match toks.get(1).map(|x| &x.data) {
+        Some(Special('(')) => {
+            let mut depth = 1;
+            i += 1;
+            while i < toks.len() && depth > 0 {
+                match &toks[i].data {
+                    Special('(') => depth += 1,
+                    Special(')') => depth -= 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+            if depth > 0 {
+                errs.push(Error::new(toks[1].loc.clone(), 250, "unmatched '('".to_string()));
+                return (None, i, errs);
+            }
+            if i == 3 {
+                errs.push(Error::new(toks[1].loc.clone(), 282, format!("expected a condition after {:?}", toks[0].data)));
+                null()
+            }
+            else {
+                let (ast, _, mut es) = parse_expr(&toks[2..(i - 1)], "", flags);
+                errs.append(&mut es);
+                ast
+            }
+        },
+        Some(_) => {
+            while i < toks.len() {
+                match &toks[i].data {
+                    Special('{') => break,
+                    Special(c) if terminators.contains(*c) => break,
+                    Special(c @ ('(' | '[')) => {
+                        let close = if *c == '(' {')'} else {']'};
+                        let mut depth = 1;
+                        i += 1;
+                        while i < toks.len() && depth > 0 {
+                            match &toks[i].data {
+                                Special(x) if x == c => depth += 1,
+                                Special(x) if *x == close => depth -= 1,
+                                _ => {}
+                            }
+                            i += 1;
+                        }
+                    },
+                    _ => i += 1
+                }
+            }
+            if i == toks.len() || toks[i].data != Special('{') {
+                errs.push(Error::new(start, 283, format!("expected a body after the condition of {:?}", toks[0].data)));
+                return (None, i, errs);
+            }
+            if i == 1 {
+                errs.push(Error::new(toks[1].loc.clone(), 282, format!("expected a condition after {:?}", toks[0].data)));
+                null()
+            }
+            else {
+                let (ast, _, mut es) = parse_expr(&toks[1..i], "", flags);
+                errs.append(&mut es);
+                ast
+            }
+        },
+        None => {
+            errs.push(Error::new(start, 282, format!("expected a condition after {:?}", toks[0].data)));
+            return (None, 1, errs);
+        }
+    };
+    (Some(cond), i, errs)
+}
+fn parse_body(toks: &[Token], mut i: usize, terminators: &'static str, is_if: bool) -> usize {
+    let mut nested = 0;
+    while i < toks.len() {
+        match &toks[i].data {
+            Special(c) if terminators.contains(*c) => break,
+            Keyword(k) if is_if && k == "if" => {nested += 1; i += 1;},
+            Keyword(k) if is_if && k == "else" => if nested == 0 {break} else {nested -= 1; i += 1;},
+            Special(c @ ('(' | '[' | '{')) => {
+                let close = match c {'(' => ')', '[' => ']', _ => '}'};
+                let mut depth = 1;
+                i += 1;
+                while i < toks.len() && depth > 0 {
+                    match &toks[i].data {
+                        Special(x) if x == c => depth += 1,
+                        Special(x) if *x == close => depth -= 1,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            },
+            _ => i += 1
+        }
+    }
+    i
+}
+fn parse_if(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let (cond, body_start, mut errs) = parse_cond(toks, terminators, flags);
+    let cond = if let Some(cond) = cond {cond} else {return (null(), body_start + 1, errs)};
+    let i = parse_body(toks, body_start, terminators, true);
+    if i == body_start {
+        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 283, "expected a body after the condition of 'if'".to_string()));
+        return (null(), i + 1, errs);
+    }
+    let (if_true, _, mut es) = parse_expr(&toks[body_start..i], "", flags);
+    errs.append(&mut es);
+    if toks.get(i).map_or(false, |x| x.data == Keyword("else".to_string())) {
+        if i + 1 == toks.len() || matches!(toks[i + 1].data, Special(c) if terminators.contains(c)) {
+            errs.push(Error::new(toks[i].loc.clone(), 283, "expected a body after 'else'".to_string()));
+            return (Box::new(IfAST::new(start, cond, if_true, None)), i + 2, errs);
+        }
+        let (if_false, idx, mut es) = parse_expr(&toks[(i + 1)..], terminators, flags);
+        errs.append(&mut es);
+        (Box::new(IfAST::new(start, cond, if_true, Some(if_false))), i + 1 + idx, errs)
+    }
+    else {(Box::new(IfAST::new(start, cond, if_true, None)), i + 1, errs)}
+}
+fn parse_while(toks: &[Token], terminators: &'static str, label: Option<String>, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let (cond, body_start, mut errs) = parse_cond(toks, terminators, flags);
+    let cond = if let Some(cond) = cond {cond} else {return (null(), body_start + 1, errs)};
+    let i = parse_body(toks, body_start, terminators, false);
+    if i == body_start {
+        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 283, "expected a body after the condition of 'while'".to_string()));
+        return (null(), i + 1, errs);
+    }
+    let (body, _, mut es) = parse_expr(&toks[body_start..i], "", flags);
+    errs.append(&mut es);
+    (Box::new(WhileAST::new(start, label, cond, body)), i + 1, errs)
+}
+fn parse_for(toks: &[Token], terminators: &'static str, label: Option<String>, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let mut errs = vec![];
+    let (head, body_start) = if toks.get(1).map(|x| &x.data) == Some(&Special('(')) {
+        let mut depth = 1;
+        let mut i = 2;
+        while i < toks.len() && depth > 0 {
+            match &toks[i].data {
+                Special('(') => depth += 1,
+                Special(')') => depth -= 1,
+                _ => {}
+            }
+            i += 1;
+        }
+        if depth > 0 {
+            errs.push(Error::new(toks[1].loc.clone(), 250, "unmatched '('".to_string()));
+            return (null(), i, errs);
+        }
+        (&toks[2..(i - 1)], i)
+    }
+    else {
+        let mut i = 1;
+        while i < toks.len() {
+            match &toks[i].data {
+                Special('{') => break,
+                Special(c) if terminators.contains(*c) => break,
+                Special(c @ ('(' | '[')) => {
+                    let close = if *c == '(' {')'} else {']'};
+                    let mut depth = 1;
+                    i += 1;
+                    while i < toks.len() && depth > 0 {
+                        match &toks[i].data {
+                            Special(x) if x == c => depth += 1,
+                            Special(x) if *x == close => depth -= 1,
+                            _ => {}
+                        }
+                        i += 1;
+                    }
+                },
+                _ => i += 1
+            }
+        }
+        if i == toks.len() || toks[i].data != Special('{') {
+            errs.push(Error::new(start, 283, "expected a body after the header of 'for'".to_string()));
+            return (null(), i + 1, errs);
+        }
+        (&toks[1..i], i)
+    };
+    let i = parse_body(toks, body_start, terminators, false);
+    if i == body_start {
+        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 283, "expected a body after the header of 'for'".to_string()));
+        return (null(), i + 1, errs);
+    }
+    let name = if let Some(Identifier(name)) = head.first().map(|x| &x.data) {name.clone()} else {
+        errs.push(Error::new(head.first().map_or(start, |x| x.loc.clone()), 293, "expected a loop variable after 'for'".to_string()));
+        return (null(), i + 1, errs);
+    };
+    if head.get(1).map(|x| &x.data) != Some(&Keyword("in".to_string())) {
+        errs.push(Error::new(head.get(1).unwrap_or(&head[0]).loc.clone(), 294, format!("expected 'in' after loop variable {name}")));
+        return (null(), i + 1, errs);
+    }
+    let iter = &head[2..];
+    if iter.is_empty() {
+        errs.push(Error::new(head[1].loc.clone(), 295, "expected a value to iterate over after 'in'".to_string()));
+        return (null(), i + 1, errs);
+    }
+    let mut depth = 0;
+    let split = iter.iter().position(|x| match &x.data {
+        Special('(' | '[' | '{') => {depth += 1; false},
+        Special(')' | ']' | '}') => {depth -= 1; false},
+        Operator(op) => depth == 0 && op == "..",
+        _ => false
+    });
+    let iter = if let Some(n) = split {
+        if n == 0 || n + 1 == iter.len() {
+            errs.push(Error::new(iter[n].loc.clone(), 295, "expected a start and an end for the range".to_string()));
+            return (null(), i + 1, errs);
+        }
+        let (s, _, mut es) = parse_expr(&iter[..n], "", flags);
+        errs.append(&mut es);
+        let (e, _, mut es) = parse_expr(&iter[(n + 1)..], "", flags);
+        errs.append(&mut es);
+        ForIter::Range(s, e)
+    }
+    else {
+        let (ast, _, mut es) = parse_expr(iter, "", flags);
+        errs.append(&mut es);
+        ForIter::Each(ast)
+    };
+    let (body, _, mut es) = parse_expr(&toks[body_start..i], "", flags);
+    errs.append(&mut es);
+    (Box::new(ForAST::new(start, label, name, iter, body)), i + 1, errs)
+}
+fn parse_match_arm(toks: &[Token], flags: &Flags) -> (Option<(Location, MatchPattern, Box<dyn AST>)>, Vec<Error>) {
+    let mut errs = vec![];
+    let arrow = if let Some(idx) = toks.iter().position(|x| x.data == Operator("=>".to_string())) {idx} else {
+        errs.push(Error::new(toks[0].loc.clone(), 297, "expected '=>' after match pattern".to_string()));
+        return (None, errs)
+    };
+    let pat = match toks[..arrow].iter().map(|x| &x.data).collect::<Vec<_>>().as_slice() {
+        [Identifier(n)] if n == "_" => MatchPattern::Wildcard,
+        [Identifier(n)] => MatchPattern::Variant(n.clone(), None),
+        [Identifier(n), Special('('), Identifier(b), Special(')')] => MatchPattern::Variant(n.clone(), Some(b.clone())),
+        _ => {
+            errs.push(Error::new(toks[0].loc.clone(), 298, "expected a variant name, optionally followed by a binding in parentheses, or '_'".to_string()));
+            return (None, errs)
+        }
+    };
+    if arrow + 1 == toks.len() {
+        errs.push(Error::new(toks[arrow].loc.clone(), 283, "expected a body after '=>'".to_string()));
+        return (None, errs)
+    }
+    let (body, _, mut es) = parse_expr(&toks[(arrow + 1)..], "", flags);
+    errs.append(&mut es);
+    (Some((toks[0].loc.clone(), pat, body)), errs)
+}
+fn parse_match(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let mut errs = vec![];
+    let mut i = 1;
+    while i < toks.len() {
+        match &toks[i].data {
+            Special('{') => break,
+            Special(c) if terminators.contains(*c) => break,
+            Special(c @ ('(' | '[')) => {
+                let close = if *c == '(' {')'} else {']'};
+                let mut depth = 1;
+                i += 1;
+                while i < toks.len() && depth > 0 {
+                    match &toks[i].data {
+                        Special(x) if x == c => depth += 1,
+                        Special(x) if *x == close => depth -= 1,
+                        _ => {}
+                    }
+                    i += 1;
+                }
+            },
+            _ => i += 1
+        }
+    }
+    if i == toks.len() || toks[i].data != Special('{') {
+        errs.push(Error::new(start, 283, "expected arms after the value of 'match'".to_string()));
+        return (null(), i + 1, errs);
+    }
+    let val = if i == 1 {
+        errs.push(Error::new(toks[1].loc.clone(), 282, "expected a value after 'match'".to_string()));
+        null()
+    }
+    else {
+        let (ast, _, mut es) = parse_expr(&toks[1..i], "", flags);
+        errs.append(&mut es);
+        ast
+    };
+    let open = toks[i].loc.clone();
+    let mut arms = vec![];
+    let mut depth = 1;
+    i += 1;
+    let mut arm_start = i;
+    while i < toks.len() {
+        let split = match &toks[i].data {
+            Special('(' | '[' | '{') => {depth += 1; false},
+            Special(')' | ']' | '}') => {depth -= 1; depth == 0},
+            Special(',') => depth == 1,
+            _ => false
+        };
+        if split {
+            if i > arm_start {
+                let (arm, mut es) = parse_match_arm(&toks[arm_start..i], flags);
+                errs.append(&mut es);
+                arms.extend(arm);
+            }
+            arm_start = i + 1;
+        }
+        i += 1;
+        if depth == 0 {break}
+    }
+    if depth > 0 {
+        errs.push(Error::new(open, 254, "unmatched '{'".to_string()));
+        return (Box::new(MatchAST::new(start, val, arms)), i + 1, errs)
+    }
+    let end = parse_body(toks, i, terminators, false);
+    if end > i {
+        errs.push(Error::new(toks[i].loc.clone(), 280, "expected a ';' before the next expression".to_string()));
+    }
+    (Box::new(MatchAST::new(start, val, arms)), end + 1, errs)
+}
+fn parse_jump(toks: &[Token], terminators: &'static str) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let mut errs = vec![];
+    let (label, mut i) = if let Some(Identifier(label)) = toks.get(1).map(|x| &x.data) {(Some(label.clone()), 2)} else {(None, 1)};
+    while i < toks.len() {
+        match &toks[i].data {
+            Special(c) if terminators.contains(*c) => break,
+            x => errs.push(Error::new(toks[i].loc.clone(), 284, format!("unexpected token {:?} after {:?}", x, toks[0].data)))
+        }
+        i += 1;
+    }
+    (if toks[0].data == Keyword("break".to_string()) {Box::new(BreakAST::new(toks[0].loc.clone(), label))}
+    else {Box::new(ContinueAST::new(toks[0].loc.clone(), label))}, i + 1, errs)
+}
+fn parse_lambda(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    let start = toks[0].loc.clone();
+    let mut errs = vec![];
+    let mut params = vec![];
+    let mut i = 1;
+    loop {
+        if i + 1 >= toks.len() {
+            errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 238, "unexpected end of parameter list".to_string()));
+            return (null(), toks.len() + 1, errs)
+        }
+        if toks[i + 1].data == Special(')') {
+            i += 2;
+            break;
+        }
+        i += 1;
+        let param_type = match &toks[i].data {
+            Keyword(x) if x == "mut" => {
+                i += 1;
+                ParamType::Mutable
+            },
+            Keyword(x) if x == "const" => {
+                errs.push(Error::new(toks[i].loc.clone(), 278, "closure parameters cannot be const".to_string()));
+                i += 1;
+                ParamType::Normal
+            },
+            _ => ParamType::Normal
+        };
+        let name = if let Some(Identifier(name)) = toks.get(i).map(|x| &x.data) {
+            i += 1;
+            name.clone()
+        }
+        else {
+            errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 277, "expected a parameter name".to_string()));
+            String::new()
+        };
+        let ty = if toks.get(i).map(|x| &x.data) == Some(&Special(':')) {
+            let (ty, idx, mut es) = parse_type(&toks[(i + 1)..], ",)=", flags);
+            i += idx;
+            errs.append(&mut es);
+            ty
+        }
+        else {
+            errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 240, "function parameters must have explicit types".to_string()));
+            ParsedType::Error
+        };
+        if toks.get(i).map(|x| &x.data) == Some(&Operator("=".to_string())) {
+            errs.push(Error::new(toks[i].loc.clone(), 278, "closure parameters cannot have default values".to_string()));
+            let (_, idx, mut es) = parse_expr(&toks[(i + 1)..], ",)", flags);
+            i += idx;
+            errs.append(&mut es);
+        }
+        params.push((name, param_type, ty));
+        match toks.get(i).map(|x| &x.data) {
+            Some(Special(')')) => {
+                i += 1;
+                break;
+            },
+            Some(Special(',')) => {},
+            Some(x) => {
+                errs.push(Error::new(toks[i].loc.clone(), 242, format!("expected ',' or ')' after parameter, got {x:?}")));
+                return (null(), parse_body(toks, i, terminators, false) + 1, errs)
+            },
+            None => {
+                errs.push(Error::new(toks[toks.len() - 1].loc.clone(), 238, "unexpected end of parameter list".to_string()));
+                return (null(), toks.len() + 1, errs)
+            }
+        }
+    }
+    let ret = if toks.get(i).map(|x| &x.data) == Some(&Special(':')) {
+        let (ty, idx, mut es) = parse_type(&toks[(i + 1)..], "=", flags);
+        i += idx;
+        errs.append(&mut es);
+        Some(ty)
+    } else {None};
+    if toks.get(i).map(|x| &x.data) != Some(&Operator("=".to_string())) {
+        errs.push(Error::new(toks[i.min(toks.len() - 1)].loc.clone(), 279, "expected '=' before closure body".to_string()));
+        return (null(), parse_body(toks, i, terminators, false) + 1, errs)
+    }
+    let (body, idx, mut es) = parse_expr(&toks[(i + 1)..], terminators, flags);
+    errs.append(&mut es);
+    (Box::new(LambdaAST::new(start, params, ret, body)), i + 1 + idx, errs)
+}
 fn parse_expr(toks: &[Token], terminators: &'static str, flags: &Flags) -> (Box<dyn AST>, usize, Vec<Error>) {
+    match toks.first().map(|x| &x.data) {
+        Some(Keyword(k)) if k == "if" => return parse_if(toks, terminators, flags),
+        Some(Keyword(k)) if k == "while" => return parse_while(toks, terminators, None, flags),
+        Some(Keyword(k)) if k == "for" => return parse_for(toks, terminators, None, flags),
+        Some(Keyword(k)) if k == "match" => return parse_match(toks, terminators, flags),
+        Some(Keyword(k)) if k == "break" || k == "continue" => return parse_jump(toks, terminators),
+        Some(Keyword(k)) if k == "fn" && toks.get(1).map(|x| &x.data) == Some(&Special('(')) => return parse_lambda(toks, terminators, flags),
+        Some(Keyword(k)) if k == "yield" => {
+            if toks.len() == 1 || matches!(toks[1].data, Special(c) if terminators.contains(c)) {
+                return (Box::new(YieldAST::new(toks[0].loc.clone(), Box::new(NullAST::new(toks[0].loc.clone())))), 2, vec![])
+            }
+            let (ast, idx, errs) = parse_expr(&toks[1..], terminators, flags);
+            return (Box::new(YieldAST::new(toks[0].loc.clone(), ast)), idx + 1, errs)
+        },
+        Some(Keyword(k)) if k == "return" => {
+            if toks.len() == 1 || matches!(toks[1].data, Special(c) if terminators.contains(c)) {
+                return (Box::new(ReturnAST::new(toks[0].loc.clone(), Box::new(NullAST::new(toks[0].loc.clone())))), 2, vec![])
+            }
+            let (ast, idx, errs) = parse_expr(&toks[1..], terminators, flags);
+            return (Box::new(ReturnAST::new(toks[0].loc.clone(), ast)), idx + 1, errs)
+        },
+        Some(Identifier(label)) if toks.get(1).map(|x| &x.data) == Some(&Special(':')) && toks.get(2).map(|x| &x.data) == Some(&Keyword("while".to_string())) => {
+            let (ast, idx, errs) = parse_while(&toks[2..], terminators, Some(label.clone()), flags);
+            return (ast, idx + 2, errs)
+        },
+        Some(Identifier(label)) if toks.get(1).map(|x| &x.data) == Some(&Special(':')) && toks.get(2).map(|x| &x.data) == Some(&Keyword("for".to_string())) => {
+            let (ast, idx, errs) = parse_for(&toks[2..], terminators, Some(label.clone()), flags);
+            return (ast, idx + 2, errs)
+        },
+        _ => {}
+    }
     let mut i = 0;
     let mut errs = vec![];
     while i < toks.len() {
@@ -1103,17 +2015,19 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                             }
                         },
                         Operator(s) if s == "=" => {
-                            let (oname, idx, mut es) = parse_path(toks, ";");
+                            let loc = toks[0].loc;
+                            let (oname, idx, mut es) = parse_path(&toks[1..], ";");
+                            let idx = idx.min(toks.len());
                             i += idx;
                             toks = &toks[idx..];
                             errs.append(&mut es);
-                            if toks.last().map(|x| &x.data) == Some(&Special(';')) {
+                            if toks.first().map(|x| &x.data) != Some(&Special(';')) {
                                 errs.push(Error::new(val.loc, 202, "expected semicolon after module assignment".to_string()));
                                 break;
                             }
                             let mut cname: CompoundDottedName = oname.into();
                             cname.ids.push(CompoundDottedNameSegment::Glob('*'.to_string()));
-                            outs.push(Box::new(ModuleAST::new(toks[0].loc, name, vec![Box::new(ImportAST::new(toks[0].loc, cname))])));
+                            outs.push(Box::new(ModuleAST::new(loc, name, vec![Box::new(ImportAST::new(loc, cname))])));
                         },
                         Special(';') => {
                             outs.push(Box::new(ModuleAST::new(toks[0].loc, name, vec![])));
@@ -1132,12 +2046,64 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                     i += idx + 1;
                     toks = &toks[(idx + 1)..];
                 },
-                "fn" => {
+                "struct" => {
+                    if annotations.len() > 0 {
+                        errs.push(Error::new(val.loc.clone(), 281, "annotations cannot be used on a struct definition".to_string()));
+                        annotations = vec![];
+                    }
+                    let (ast, idx, mut es) = parse_struct(toks, flags);
+                    outs.push(ast);
+                    errs.append(&mut es);
+                    i += idx;
+                    toks = &toks[idx..];
+                },
+                "enum" => {
+                    if annotations.len() > 0 {
+                        errs.push(Error::new(val.loc.clone(), 281, "annotations cannot be used on an enum definition".to_string()));
+                        annotations = vec![];
+                    }
+                    let (ast, idx, mut es) = parse_enum(toks, flags);
+                    outs.push(ast);
+                    errs.append(&mut es);
+                    i += idx;
+                    toks = &toks[idx..];
+                },
+                "trait" => {
+                    if annotations.len() > 0 {
+                        errs.push(Error::new(val.loc.clone(), 281, "annotations cannot be used on a trait definition".to_string()));
+                        annotations = vec![];
+                    }
+                    let (ast, idx, mut es) = parse_trait(toks, flags);
+                    outs.push(ast);
+                    errs.append(&mut es);
+                    i += idx;
+                    toks = &toks[idx..];
+                },
+                "impl" => {
+                    if annotations.len() > 0 {
+                        errs.push(Error::new(val.loc.clone(), 281, "annotations cannot be used on an impl block".to_string()));
+                        annotations = vec![];
+                    }
+                    let (ast, idx, mut es) = parse_impl(toks, flags);
+                    outs.push(ast);
+                    errs.append(&mut es);
+                    i += idx;
+                    toks = &toks[idx..];
+                },
+                "fn" | "cr" => {
                     let start = toks[0].loc.clone();
-                    let (name, idx, mut es) = parse_path(&toks[1..], "(=;");
+                    let is_cr = toks[0].data == Keyword("cr".to_string());
+                    let (name, idx, mut es) = parse_path(&toks[1..], "(=;[");
                     toks = &toks[idx..];
                     i = idx;
                     errs.append(&mut es);
+                    let generics = if toks.first().map(|x| &x.data) == Some(&Special('[')) {
+                        let (generics, idx, mut es) = parse_generics(toks);
+                        toks = &toks[idx..];
+                        i += idx;
+                        errs.append(&mut es);
+                        generics
+                    } else {vec![]};
                     let mut anns = vec![];
                     std::mem::swap(&mut annotations, &mut anns);
                     if toks.len() == 0 {
@@ -1238,7 +2204,7 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                             match &toks[0].data {
                                 Special(';') => {
                                     errs.push(Error::new(toks[0].loc.clone(), 243, "function declaration requires an explicit return type".to_string()));
-                                    outs.push(Box::new(FnDefAST::new(start, name, ParsedType::Error, params, Box::new(NullAST::new(toks[0].loc.clone())), anns)));
+                                    outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ParsedType::Error, params, Box::new(NullAST::new(toks[0].loc.clone())), anns, is_cr)));
                                     toks = &toks[1..];
                                     i += 1;
                                 },
@@ -1250,25 +2216,25 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                                     if toks.len() == 0 {
                                         let last = unsafe {(*toks.as_ptr().offset(-1)).loc.clone()};
                                         errs.push(Error::new(last.clone(), 244, "expected function body or semicolon".to_string()));
-                                        outs.push(Box::new(FnDefAST::new(start, name, ty, params, Box::new(NullAST::new(last)), anns)));
+                                        outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, Box::new(NullAST::new(last)), anns, is_cr)));
                                         break;
                                     }
                                     match &toks[0].data {
-                                        Special(';') => outs.push(Box::new(FnDefAST::new(start, name, ty, params, Box::new(NullAST::new(toks[0].loc.clone())), anns))),
+                                        Special(';') => outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, Box::new(NullAST::new(toks[0].loc.clone())), anns, is_cr))),
                                         Special('{') => {
                                             errs.push(Error::new(toks[0].loc.clone(), 245, "functions are defined with an '='".to_string()).note(Note::new(toks[0].loc.clone(), "try inserting an '='".to_string())));
                                             let (ast, idx, mut es) = parse_expr(toks, ";", flags);
                                             toks = &toks[idx..];
                                             i += idx;
                                             errs.append(&mut es);
-                                            outs.push(Box::new(FnDefAST::new(start, name, ty, params, ast, anns)));
+                                            outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, ast, anns, is_cr)));
                                         },
                                         Operator(x) if x == "=" => {
                                             let (ast, idx, mut es) = parse_expr(&toks[1..], ";", flags);
                                             toks = &toks[(idx + 1)..];
                                             i += idx + 1;
                                             errs.append(&mut es);
-                                            outs.push(Box::new(FnDefAST::new(start, name, ty, params, ast, anns)));
+                                            outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ty, params, ast, anns, is_cr)));
                                         },
                                         x => errs.push(Error::new(toks[0].loc.clone(), 244, format!("expected function body or semicolon, got {x:?}")))
                                     }
@@ -1278,7 +2244,7 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                                     toks = &toks[(idx + 1)..];
                                     i += idx + 1;
                                     errs.append(&mut es);
-                                    outs.push(Box::new(FnDefAST::new(start, name, ParsedType::Error, params, ast, anns)));
+                                    outs.push(Box::new(FnDefAST::new(start, name, generics.clone(), ParsedType::Error, params, ast, anns, is_cr)));
                                 },
                                 x => errs.push(Error::new(toks[0].loc.clone(), 244, format!("expected function return type or body, got {x:?}")))
                             }
@@ -1288,7 +2254,6 @@ fn parse_tl(mut toks: &[Token], flags: &Flags) -> (Vec<Box<dyn AST>>, Option<usi
                         _ => errs.push(Error::new(toks[0].loc.clone(), 236, format!("expected function parameters, got {:?}", toks[0].data)))
                     }
                 },
-                "cr" => {},
                 "let" => {
                     let start = toks[0].loc.clone();
                     let (name, idx, mut es) = parse_path(&toks[1..], ":=");
@@ -1473,3 +2438,12 @@ pub fn parse(mut toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>) {
     }
     return (Box::new(TopLevelAST::new(start, out)), errs);
 }
+pub fn parse_generic(toks: &[Token], flags: &Flags) -> (Option<Box<dyn AST>>, Vec<Error>) {
+    let (mut out, _, errs) = parse_tl(toks, flags);
+    (if out.len() == 1 {out.pop()} else {None}, errs)
+}
+pub fn parse_expression(toks: &[Token], flags: &Flags) -> (Box<dyn AST>, Vec<Error>) {
+    if toks.len() == 0 {return (null(), vec![])}
+    let (ast, _, errs) = parse_expr(toks, "", flags);
+    (ast, errs)
+}
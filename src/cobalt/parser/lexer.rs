@@ -64,7 +64,7 @@ fn parse_num(it: &mut std::iter::Peekable<std::str::Chars>, c: char, loc: &mut L
                         val *= 10;
                         val += c.to_digit(10).unwrap() as i128;
                     },
-                    Some('.') => {
+                    Some('.') if it.clone().nth(1) != Some('.') => {
                         let mut val = val as f64;
                         let mut dec_places = -1.0;
                         it.next();
@@ -778,7 +778,7 @@ pub fn lex(data: &str, mut loc: Location, flags: &Flags) -> (Vec<Token>, Vec<Err
                     it.next();
                 }
                 outs.push(Token::new(start, match s.as_str() {
-                    "let" | "mut" | "const" | "fn" | "cr" | "module" | "import" | "if" | "else" | "while" => Keyword(s),
+                    "let" | "mut" | "const" | "fn" | "cr" | "module" | "import" | "if" | "else" | "while" | "break" | "continue" | "struct" | "yield" | "return" | "for" | "in" | "enum" | "match" | "trait" | "impl" | "defer" => Keyword(s),
                     _ => Identifier(s)
                 }));
             },
@@ -792,13 +792,21 @@ pub fn lex(data: &str, mut loc: Location, flags: &Flags) -> (Vec<Token>, Vec<Err
                     Ok(val) => outs.push(val),
                     Err(val) => errs.push(val)
                 },
+                Some('.') => {
+                    it.next();
+                    outs.push(Token::new(loc.clone(), Operator("..".to_string())));
+                },
                 _ => outs.push(Token::new(loc.clone(), Special('.')))
             },
             '?' | '~' => { // operator of the from @
                 outs.push(Token::new(loc.clone(), Operator(c.to_string())));
             },
-            '=' | '!' | '%' | '*' => { // operator of the form @, @=
-                if it.peek() == Some(&'=') {
+            '=' | '!' | '%' | '*' => { // operator of the form @, @=, or =>
+                if c == '=' && it.peek() == Some(&'>') {
+                    it.next();
+                    outs.push(Token::new(loc.clone(), Operator("=>".to_string())));
+                }
+                else if it.peek() == Some(&'=') {
                     it.next();
                     outs.push(Token::new(loc.clone(), Operator([c, '='].iter().collect())));
                 }
@@ -806,13 +814,19 @@ pub fn lex(data: &str, mut loc: Location, flags: &Flags) -> (Vec<Token>, Vec<Err
                     outs.push(Token::new(loc.clone(), Operator(c.to_string())));
                 }
             },
-            '+' | '-' | '&' | '|' | '^' => { // operator of the form @, @@, @=
+            '+' | '-' | '&' | '|' | '^' => { // operator of the form @, @@, @=, or ^^=
                 if let Some(c2) = it.peek() {
                     let c3 = *c2;
                     drop(c2);
                     if c3 == '=' || c3 == c {
                         it.next();
-                        outs.push(Token::new(loc.clone(), Operator([c, c3].iter().collect())));
+                        if c == '^' && c3 == '^' && it.peek() == Some(&'=') {
+                            it.next();
+                            outs.push(Token::new(loc.clone(), Operator("^^=".to_string())));
+                        }
+                        else {
+                            outs.push(Token::new(loc.clone(), Operator([c, c3].iter().collect())));
+                        }
                     }
                     else {
                         outs.push(Token::new(loc.clone(), Operator(c.to_string())));
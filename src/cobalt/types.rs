@@ -19,17 +19,24 @@ impl SizeType {
 }
 #[derive(PartialEq, Eq, Clone)]
 pub enum Type {
-    IntLiteral, Char,
+    IntLiteral, Bool, Char,
     Int(u64, bool),
     Float16, Float32, Float64, Float128,
     Pointer(Box<Type>, bool), Reference(Box<Type>, bool), Borrow(Box<Type>),
-    Null, Module, TypeData, Array(Box<Type>, Option<u64>),
-    Function(Box<Type>, Vec<(Type, bool)>)
+    Null, Module, TypeData, Generic, Array(Box<Type>, Option<u64>),
+    Function(Box<Type>, Vec<(Type, bool)>),
+    Struct(String, Vec<(String, Type)>),
+    Coroutine(Box<Type>),
+    Closure(Box<Type>, Vec<Type>),
+    Trait(String, Vec<(String, Type, bool)>), // methods are stored as the closure type of the bound method and whether the receiver is mutable
+    Enum(String, Vec<(String, Option<Type>)>),
+    Optional(Box<Type>)
 }
 impl Display for Type {
     fn fmt(&self, f: &mut Formatter) -> Result {
         match self {
             IntLiteral => write!(f, "<int literal>"),
+            Bool => write!(f, "bool"),
             Int(size, false) => write!(f, "i{size}"),
             Int(size, true) => write!(f, "u{size}"),
             Char => write!(f, "char"),
@@ -45,6 +52,7 @@ impl Display for Type {
             Null => write!(f, "null"),
             Module => write!(f, "module"),
             TypeData => write!(f, "type"),
+            Generic => write!(f, "generic"),
             Array(x, None) => write!(f, "{}[]", *x),
             Array(x, Some(s)) => write!(f, "{}[{s}]", *x),
             Function(ret, args) => {
@@ -58,7 +66,20 @@ impl Display for Type {
                     if len > 1 {write!(f, ", ")?}
                 }
                 write!(f, "): {}", *ret)
-            }
+            },
+            Struct(name, _) => write!(f, "{name}"),
+            Coroutine(x) => write!(f, "cr {}", *x),
+            Closure(ret, args) => {
+                write!(f, "closure (")?;
+                for (n, arg) in args.iter().enumerate() {
+                    if n > 0 {write!(f, ", ")?}
+                    write!(f, "{arg}")?;
+                }
+                write!(f, "): {}", *ret)
+            },
+            Trait(name, _) => write!(f, "dyn {name}"),
+            Enum(name, _) => write!(f, "{name}"),
+            Optional(x) => write!(f, "{}?", *x)
         }
     }
 }
@@ -66,6 +87,7 @@ impl Type {
     pub fn size(&self) -> SizeType {
         match self {
             IntLiteral => Static(8),
+            Bool => Static(1),
             Int(size, _) => Static((size + 7) / 8),
             Char => Static(4),
             Float16 => Static(2),
@@ -74,15 +96,48 @@ impl Type {
             Float128 => Static(16),
             Null => Static(0),
             Array(b, Some(s)) => b.size().map_static(|x| x * s),
-            Array(_, None) => Dynamic,
-            Function(..) | Module | TypeData => Meta,
-            Pointer(..) | Reference(..) => Static(8),
-            Borrow(b) => b.size()
+            Function(..) | Module | TypeData | Generic => Meta,
+            Pointer(..) | Reference(..) | Coroutine(_) => Static(8),
+            Array(_, None) | Closure(..) | Trait(..) => Static(16),
+            Borrow(b) => b.size(),
+            Optional(b) if b.null_niche() => b.size(),
+            Optional(b) => b.size().map_static(|s| {
+                let a = b.align().max(1);
+                (s + a) / a * a
+            }),
+            Struct(_, fields) => {
+                let mut size = 0;
+                for (_, t) in fields.iter() {
+                    let s = match t.size() {
+                        Static(s) => s,
+                        x => return x
+                    };
+                    let a = t.align().max(1);
+                    size = (size + a - 1) / a * a + s;
+                }
+                let a = self.align().max(1);
+                Static((size + a - 1) / a * a)
+            },
+            Enum(_, variants) => {
+                let tag = (self.enum_tag_bits() / 8) as u64;
+                let mut size = 0;
+                for t in variants.iter().filter_map(|(_, t)| t.as_ref()) {
+                    match t.size() {
+                        Static(s) => size = size.max(s),
+                        x => return x
+                    }
+                }
+                if size == 0 {return Static(tag)}
+                let pa = self.enum_payload_align();
+                let a = self.align().max(1);
+                Static(((tag + pa - 1) / pa * pa + size + a - 1) / a * a)
+            }
         }
     }
     pub fn align(&self) -> u64 {
         match self {
             IntLiteral => 8,
+            Bool => 1,
             Int(size, _) => match size {
                 0..=8 => 1,
                 9..=16 => 2,
@@ -94,38 +149,88 @@ impl Type {
             Float32 => 4,
             Float64 | Float128 => 8,
             Null => 1,
-            Array(b, _) => b.align(),
-            Function(..) | Module | TypeData => 0,
-            Pointer(..) | Reference(..) => 8,
-            Borrow(b) => b.align()
+            Array(b, Some(_)) => b.align(),
+            Function(..) | Module | TypeData | Generic => 0,
+            Pointer(..) | Reference(..) | Array(_, None) | Coroutine(_) | Closure(..) | Trait(..) => 8,
+            Borrow(b) => b.align(),
+            Optional(b) => b.align().max(1),
+            Struct(_, fields) => fields.iter().map(|(_, t)| t.align()).max().unwrap_or(1),
+            Enum(..) => Int(self.enum_tag_bits() as u64, true).align().max(self.enum_payload_align())
         }
     }
+    pub fn is_c_enum(&self) -> bool {
+        if let Enum(_, variants) = self {variants.iter().all(|(_, t)| t.is_none())} else {false}
+    }
+    pub fn enum_tag_bits(&self) -> u32 {
+        match self {
+            Enum(_, variants) if variants.len() <= 1 << 8 => 8,
+            Enum(_, variants) if variants.len() <= 1 << 16 => 16,
+            _ => 32
+        }
+    }
+    // an empty optional of a pointer or a reference is stored as a null pointer, so a null pointer wrapped in an optional reads as empty
+    pub fn null_niche(&self) -> bool {matches!(self, Pointer(..) | Reference(..))}
+    pub fn enum_payload_align(&self) -> u64 {
+        if let Enum(_, variants) = self {variants.iter().filter_map(|(_, t)| t.as_ref()).map(Type::align).max().unwrap_or(1).max(1)} else {1}
+    }
     pub fn llvm_type<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Option<BasicTypeEnum<'ctx>> {
         match self {
             IntLiteral => Some(IntType(ctx.context.i64_type())),
+            Bool => Some(IntType(ctx.context.bool_type())),
             Int(size, _) => Some(IntType(ctx.context.custom_width_int_type(*size as u32))),
             Char => Some(IntType(ctx.context.i32_type())),
             Float16 => Some(FloatType(ctx.context.f16_type())),
             Float32 => Some(FloatType(ctx.context.f32_type())),
             Float64 => Some(FloatType(ctx.context.f64_type())),
             Float128 => Some(FloatType(ctx.context.f128_type())),
-            Null | Function(..) | Module | TypeData => None,
-            Array(_, Some(_)) => todo!("arrays aren't implemented yet"),
-            Array(_, None) => todo!("arrays aren't implemented yet"),
+            Null | Function(..) | Module | TypeData | Generic => None,
+            Array(b, Some(s)) => Some(ArrayType(b.llvm_type(ctx)?.array_type(*s as u32))),
+            Array(b, None) => Some(StructType(ctx.context.struct_type(&[PointerType(b.llvm_type(ctx)?.ptr_type(inkwell::AddressSpace::from(0u16))), IntType(ctx.context.i64_type())], false))),
             Pointer(b, _) | Reference(b, _) => Some(PointerType(b.llvm_type(ctx)?.ptr_type(inkwell::AddressSpace::from(0u16)))),
-            Borrow(b) => b.llvm_type(ctx)
+            Borrow(b) => b.llvm_type(ctx),
+            Optional(b) if b.null_niche() => b.llvm_type(ctx),
+            Optional(b) => Some(StructType(ctx.context.struct_type(&[b.llvm_type(ctx)?, IntType(ctx.context.bool_type())], false))), // {T, i1}, where the flag is set if there's a value
+            Coroutine(_) => Some(PointerType(ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16)))),
+            Closure(ret, params) => { // {fn(i8*, params...), i8*}, where the first argument is the environment
+                let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+                let ps = std::iter::once(Some(i8p.into())).chain(params.iter().map(|t| t.llvm_type(ctx).map(inkwell::types::BasicMetadataTypeEnum::from))).collect::<Option<Vec<_>>>()?;
+                let ft = if let Some(r) = ret.llvm_type(ctx) {r.fn_type(&ps, false)} else if **ret == Null {ctx.context.void_type().fn_type(&ps, false)} else {return None};
+                Some(StructType(ctx.context.struct_type(&[PointerType(ft.ptr_type(inkwell::AddressSpace::from(0u16))), PointerType(i8p)], false)))
+            },
+            Trait(..) => { // {i8*, i8*}, a pointer to the data and a pointer to the vtable
+                let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+                Some(StructType(ctx.context.struct_type(&[PointerType(i8p), PointerType(i8p)], false)))
+            },
+            Struct(_, fields) => Some(StructType(ctx.context.struct_type(&fields.iter().map(|(_, t)| t.llvm_type(ctx)).collect::<Option<Vec<_>>>()?, false))),
+            Enum(_, variants) => {
+                let tag = ctx.context.custom_width_int_type(self.enum_tag_bits());
+                if self.is_c_enum() {return Some(IntType(tag))}
+                // payloads share a block of storage after the tag, sized for the largest and aligned for the strictest
+                let mut size = 0;
+                for t in variants.iter().filter_map(|(_, t)| t.as_ref()) {
+                    t.llvm_type(ctx)?;
+                    size = size.max(t.size().as_static()?);
+                }
+                let pa = self.enum_payload_align();
+                let storage = ctx.context.custom_width_int_type(pa as u32 * 8).array_type(((size + pa - 1) / pa) as u32);
+                Some(StructType(ctx.context.struct_type(&[IntType(tag), ArrayType(storage)], false)))
+            }
         }
     }
     pub fn register(&self) -> bool {
         match self {
-            IntLiteral | Int(_, _) | Char | Float16 | Float32 | Float64 | Float128 | Null | Function(..) | Pointer(..) | Reference(..) => true,
-            Borrow(b) => b.register(),
+            IntLiteral | Bool | Int(_, _) | Char | Float16 | Float32 | Float64 | Float128 | Null | Function(..) | Pointer(..) | Reference(..) | Array(_, None) | Coroutine(_) | Closure(..) | Trait(..) => true,
+            Borrow(b) | Optional(b) => b.register(),
+            Enum(..) => self.is_c_enum(),
             _ => false
         }
     }
     pub fn copyable(&self) -> bool {
         match self {
-            IntLiteral | Int(_, _) | Char | Float16 | Float32 | Float64 | Float128 | Null | Function(..) | Pointer(..) | Reference(..) | Borrow(_) => true,
+            IntLiteral | Bool | Int(_, _) | Char | Float16 | Float32 | Float64 | Float128 | Null | Function(..) | Pointer(..) | Reference(..) | Borrow(_) | Array(_, None) | Trait(..) => true,
+            Array(b, Some(_)) | Optional(b) => b.copyable(),
+            Struct(_, fields) => fields.iter().all(|(_, t)| t.copyable()),
+            Enum(_, variants) => variants.iter().filter_map(|(_, t)| t.as_ref()).all(Type::copyable),
             _ => false
         }
     }
@@ -175,8 +280,74 @@ impl Type {
                 Ok(())
             },
             Module => todo!("Modules can't be stored in variables yet!"),
-            TypeData => todo!("Types can't be stored in variables yet!"),
-            Array(..) => todo!("Arrays aren't implemented yet!")
+            TypeData => out.write_all(&[17]),
+            Generic => out.write_all(&[20]),
+            Bool => out.write_all(&[21]),
+            Array(b, None) => {
+                out.write_all(&[14])?;
+                b.save(out)
+            },
+            Array(b, Some(s)) => {
+                out.write_all(&[15])?;
+                out.write_all(&s.to_be_bytes())?; // length
+                b.save(out)
+            },
+            Struct(name, fields) => {
+                out.write_all(&[16])?;
+                out.write_all(name.as_bytes())?; // name, null-terminated
+                out.write_all(&[0])?;
+                out.write_all(&(fields.len() as u64).to_be_bytes())?; // # of fields
+                for (name, t) in fields.iter() {
+                    out.write_all(name.as_bytes())?; // field name, null-terminated
+                    out.write_all(&[0])?;
+                    t.save(out)?;
+                }
+                Ok(())
+            },
+            Coroutine(b) => {
+                out.write_all(&[18])?;
+                b.save(out)
+            },
+            Closure(b, p) => {
+                out.write_all(&[22])?;
+                out.write_all(&(p.len() as u64).to_be_bytes())?; // # of params
+                b.save(out)?;
+                for par in p {par.save(out)?;}
+                Ok(())
+            },
+            Trait(name, methods) => {
+                out.write_all(&[23])?;
+                out.write_all(name.as_bytes())?; // name, null-terminated
+                out.write_all(&[0])?;
+                out.write_all(&(methods.len() as u64).to_be_bytes())?; // # of methods
+                for (name, t, m) in methods.iter() {
+                    out.write_all(name.as_bytes())?; // method name, null-terminated
+                    out.write_all(&[0])?;
+                    out.write_all(&[if *m {1} else {0}])?; // receiver is mutable
+                    t.save(out)?;
+                }
+                Ok(())
+            },
+            Enum(name, variants) => {
+                out.write_all(&[19])?;
+                out.write_all(name.as_bytes())?; // name, null-terminated
+                out.write_all(&[0])?;
+                out.write_all(&(variants.len() as u64).to_be_bytes())?; // # of variants
+                for (name, t) in variants.iter() {
+                    out.write_all(name.as_bytes())?; // variant name, null-terminated
+                    out.write_all(&[0])?;
+                    if let Some(t) = t {
+                        out.write_all(&[1])?; // has payload
+                        t.save(out)?;
+                    }
+                    else {out.write_all(&[0])?;}
+                }
+                Ok(())
+            },
+            Optional(b) => {
+                out.write_all(&[24])?;
+                b.save(out)
+            }
         }
     }
     pub fn load<R: Read + BufRead>(buf: &mut R) -> io::Result<Self> {
@@ -212,8 +383,79 @@ impl Type {
                     vec.push((t, c != 0));
                 }
                 Type::Function(Box::new(ret), vec)
-            }
-            x => panic!("read type value expecting value in 1..=13, got {x}")
+            },
+            14 => Type::Array(Box::new(Type::load(buf)?), None),
+            15 => {
+                let mut bytes = [0; 8];
+                buf.read_exact(&mut bytes)?;
+                let v = u64::from_be_bytes(bytes);
+                Type::Array(Box::new(Type::load(buf)?), Some(v))
+            },
+            16 => {
+                let mut name = vec![];
+                buf.read_until(0, &mut name)?;
+                if name.last() == Some(&0) {name.pop();}
+                let mut bytes = [0; 8];
+                buf.read_exact(&mut bytes)?;
+                let v = u64::from_be_bytes(bytes);
+                let mut fields = Vec::with_capacity(v as usize);
+                for _ in 0..v {
+                    let mut field = vec![];
+                    buf.read_until(0, &mut field)?;
+                    if field.last() == Some(&0) {field.pop();}
+                    fields.push((String::from_utf8(field).expect("Cobalt symbols should be valid UTF-8"), Type::load(buf)?));
+                }
+                Type::Struct(String::from_utf8(name).expect("Cobalt symbols should be valid UTF-8"), fields)
+            },
+            17 => Type::TypeData,
+            18 => Type::Coroutine(Box::new(Type::load(buf)?)),
+            19 => {
+                let mut name = vec![];
+                buf.read_until(0, &mut name)?;
+                if name.last() == Some(&0) {name.pop();}
+                let mut bytes = [0; 8];
+                buf.read_exact(&mut bytes)?;
+                let v = u64::from_be_bytes(bytes);
+                let mut variants = Vec::with_capacity(v as usize);
+                for _ in 0..v {
+                    let mut variant = vec![];
+                    buf.read_until(0, &mut variant)?;
+                    if variant.last() == Some(&0) {variant.pop();}
+                    buf.read_exact(std::slice::from_mut(&mut c))?;
+                    variants.push((String::from_utf8(variant).expect("Cobalt symbols should be valid UTF-8"), if c != 0 {Some(Type::load(buf)?)} else {None}));
+                }
+                Type::Enum(String::from_utf8(name).expect("Cobalt symbols should be valid UTF-8"), variants)
+            },
+            20 => Type::Generic,
+            21 => Type::Bool,
+            22 => {
+                let mut bytes = [0; 8];
+                buf.read_exact(&mut bytes)?;
+                let v = u64::from_be_bytes(bytes);
+                let ret = Type::load(buf)?;
+                let mut vec = Vec::with_capacity(v as usize);
+                for _ in 0..v {vec.push(Type::load(buf)?);}
+                Type::Closure(Box::new(ret), vec)
+            },
+            23 => {
+                let mut name = vec![];
+                buf.read_until(0, &mut name)?;
+                if name.last() == Some(&0) {name.pop();}
+                let mut bytes = [0; 8];
+                buf.read_exact(&mut bytes)?;
+                let v = u64::from_be_bytes(bytes);
+                let mut methods = Vec::with_capacity(v as usize);
+                for _ in 0..v {
+                    let mut method = vec![];
+                    buf.read_until(0, &mut method)?;
+                    if method.last() == Some(&0) {method.pop();}
+                    buf.read_exact(std::slice::from_mut(&mut c))?;
+                    methods.push((String::from_utf8(method).expect("Cobalt symbols should be valid UTF-8"), Type::load(buf)?, c != 0));
+                }
+                Type::Trait(String::from_utf8(name).expect("Cobalt symbols should be valid UTF-8"), methods)
+            },
+            24 => Type::Optional(Box::new(Type::load(buf)?)),
+            x => panic!("read type value expecting value in 1..=24, got {x}")
         })
     }
 }
@@ -8,46 +8,76 @@ use inkwell::{
     FloatPredicate::{OLT, OGT, OLE, OGE, OEQ, ONE}
 };
 pub fn bin_type(lhs: Type, rhs: Type, op: &str) -> Type {
+    if op == "&&" || op == "||" {
+        return match (bin_type(lhs, Type::Bool, "=="), bin_type(rhs, Type::Bool, "==")) {
+            (Type::Bool, Type::Bool) => Type::Bool,
+            _ => Type::Null
+        }
+    }
     match (lhs, rhs) {
         (l, Type::Reference(x, _) | Type::Borrow(x)) => bin_type(l, *x, op),
+        (Type::Bool, Type::Bool) => match op {
+            "==" | "!=" | "&" | "|" | "^" => Type::Bool,
+            _ => Type::Null
+        },
         (Type::Int(ls, lu), Type::Int(rs, ru)) => match op {
             "+" | "-" | "*" | "/" | "%" | "&" | "|" | "^" | "<<" | ">>" | "^^" => Type::Int(max(ls, rs), lu && ru),
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (x @ Type::Int(..), Type::IntLiteral) | (Type::IntLiteral, x @ Type::Int(..)) => match op {
             "+" | "-" | "*" | "/" | "%" | "&" | "|" | "^" | "<<" | ">>" | "^^" => x,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::IntLiteral, Type::IntLiteral) => match op {
             "+" | "-" | "*" | "/" | "%" | "&" | "|" | "^" | "<<" | ">>" | "^^" => Type::IntLiteral,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::Int(..) | Type::IntLiteral, x @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) | (x @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), Type::Int(..) | Type::IntLiteral) => match op {
-            "+" | "-" | "*" | "/" | "%" => x,
+            "+" | "-" | "*" | "/" | "%" | "^^" => x,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::Float16, Type::Float16) => match op {
-            "+" | "-" | "*" | "/" | "%" => Type::Float16,
+            "+" | "-" | "*" | "/" | "%" | "^^" => Type::Float16,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::Float32, Type::Float16 | Type::Float32) | (Type::Float16, Type::Float32) => match op {
-            "+" | "-" | "*" | "/" | "%" => Type::Float32,
+            "+" | "-" | "*" | "/" | "%" | "^^" => Type::Float32,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::Float64, Type::Float16 | Type::Float32 | Type::Float64) | (Type::Float16 | Type::Float32, Type::Float64) => match op {
-            "+" | "-" | "*" | "/" | "%" => Type::Float64,
+            "+" | "-" | "*" | "/" | "%" | "^^" => Type::Float64,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (Type::Float128, Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128) | (Type::Float16 | Type::Float32 | Type::Float64, Type::Float128) => match op {
-            "+" | "-" | "*" | "/" | "%" => Type::Float128,
+            "+" | "-" | "*" | "/" | "%" | "^^" => Type::Float128,
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
+            _ => Type::Null
+        },
+        (Type::Pointer(..), Type::Pointer(..)) => match op {
+            "==" | "!=" | "<" | ">" | "<=" | ">=" => Type::Bool,
             _ => Type::Null
         },
         (x @ Type::Pointer(..), Type::IntLiteral | Type::Int(..)) | (Type::IntLiteral | Type::Int(..), x @ Type::Pointer(..)) => match op {
             "+" | "-" => x,
             _ => Type::Null
         }
+        (x @ Type::Enum(..), y @ Type::Enum(..)) if x == y && x.is_c_enum() => match op {
+            "==" | "!=" => Type::Bool,
+            _ => Type::Null
+        }
         (Type::Reference(x, true), r) => match (*x, r) {
             (Type::IntLiteral, _) => panic!("There shouldn't be a reference to an integer literal"),
+            (Type::Bool, Type::Bool) => match op {
+                "=" | "&=" | "|=" | "^=" => Type::Reference(Box::new(Type::Bool), true),
+                _ => bin_type(Type::Bool, Type::Bool, op)
+            },
             (x @ Type::Int(..), r @ (Type::IntLiteral | Type::Int(..))) => match op {
                 "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "&=" | "|=" | "^=" | "<<=" | ">>=" | "^^=" => Type::Reference(Box::new(x), true),
                 _ => bin_type(x, r, op)
@@ -64,6 +94,11 @@ pub fn bin_type(lhs: Type, rhs: Type, op: &str) -> Type {
                 "=" => Type::Reference(Box::new(x), true),
                 _ => bin_type(x, y, op)
             },
+            (x @ Type::Enum(..), y @ Type::Enum(..)) if x == y => match op {
+                "=" => Type::Reference(Box::new(x), true),
+                _ => bin_type(x, y, op)
+            },
+            (x @ Type::Struct(..), y @ Type::Struct(..)) if x == y && op == "=" => Type::Reference(Box::new(x), true),
             (x, r) => bin_type(x, r, op)
         },
         (Type::Reference(x, false) | Type::Borrow(x), r) => bin_type(*x, r, op),
@@ -81,6 +116,10 @@ pub fn pre_type(val: Type, op: &str) -> Type {
             x => pre_type(x, op)
         }
         Type::Reference(x, false) | Type::Borrow(x) => pre_type(*x, op),
+        Type::Bool => match op {
+            "!" => Type::Bool,
+            _ => Type::Null
+        },
         x @ (Type::IntLiteral | Type::Int(..)) => match op {
             "+" | "-" | "~" => x,
             _ => Type::Null
@@ -99,7 +138,26 @@ pub fn pre_type(val: Type, op: &str) -> Type {
 }
 pub fn post_type(val: Type, op: &str) -> Type {
     match val {
-        Type::Reference(x, _) | Type::Borrow(x) => post_type(*x, op),
+        Type::Reference(x, _) => post_type(*x, op),
+        Type::Borrow(x) => match post_type(*x, op) {
+            Type::Null => Type::Null,
+            t => Type::Borrow(Box::new(t))
+        },
+        Type::Optional(x) if op == "?" || op == "!" => *x,
+        _ => Type::Null
+    }
+}
+pub fn sub_type(val: Type, idx: Type) -> Type {
+    match (val, idx) {
+        (Type::Borrow(x), i) => sub_type(*x, i),
+        (v, Type::Reference(x, _) | Type::Borrow(x)) => sub_type(v, *x),
+        (Type::Reference(x, m), i @ (Type::IntLiteral | Type::Int(..))) => match *x {
+            Type::Array(b, _) => Type::Reference(b, m),
+            x => sub_type(x, i)
+        },
+        (Type::Array(b, Some(_)), Type::IntLiteral | Type::Int(..)) => *b,
+        (Type::Array(b, None), Type::IntLiteral | Type::Int(..)) => Type::Reference(b, false),
+        (Type::Pointer(b, m), Type::IntLiteral | Type::Int(..)) => Type::Reference(b, m),
         _ => Type::Null
     }
 }
@@ -313,8 +371,9 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     Some(lhs)
                 },
                 "^^=" => {
-                    return None;
-                    // TODO: implement exponents
+                    rhs.data_type = r;
+                    lhs.data_type = Type::Null;
+                    compound_assign(lhs, rhs, l, op, ctx)
                 },
                 _ => {
                     lhs.data_type = l;
@@ -421,10 +480,40 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     lhs.data_type = Type::Reference(Box::new(x), true);
                     Some(lhs)
                 },
-                "%=" => None, // TODO: implement fmod
-                "^^=" => None, // TODO: implement powf
+                "%=" | "^^=" => {
+                    rhs.data_type = r;
+                    lhs.data_type = Type::Null;
+                    compound_assign(lhs, rhs, x, op, ctx)
+                },
                 _ => None
             },
+            (x @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), r @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) => match op {
+                "=" => {
+                    rhs.data_type = r;
+                    let rhs = float_resize(rhs, x.clone(), ctx)?;
+                    if let (Some(PointerValue(l)), Some(r), false) = (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                        ctx.builder.build_store(l, r);
+                    }
+                    lhs.inter_val = None;
+                    lhs.data_type = Type::Reference(Box::new(x), true);
+                    Some(lhs)
+                },
+                "+=" | "-=" | "*=" | "/=" | "%=" | "^^=" => {
+                    rhs.data_type = r;
+                    lhs.data_type = Type::Null;
+                    compound_assign(lhs, rhs, x, op, ctx)
+                },
+                _ => {
+                    lhs.data_type = x;
+                    rhs.data_type = r;
+                    if !ctx.is_const.get() {
+                        if let Some(v) = lhs.comp_val {
+                            lhs.comp_val = Some(ctx.builder.build_load(v.into_pointer_value(), ""));
+                        }
+                    }
+                    bin_op(lhs, rhs, op, ctx)
+                }
+            },
             (x @ Type::Pointer(..), y) if x == y => match op {
                 "=" => {
                     lhs.data_type = x;
@@ -451,6 +540,46 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     bin_op(lhs, rhs, op, ctx)
                 }
             }
+            (Type::Bool, Type::Bool) if matches!(op, "=" | "&=" | "|=" | "^=") => {
+                if let (Some(PointerValue(l)), Some(IntValue(r)), false) = (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    let v = match op {
+                        "=" => r,
+                        "&=" => ctx.builder.build_and(ctx.builder.build_load(l, "").into_int_value(), r, ""),
+                        "|=" => ctx.builder.build_or(ctx.builder.build_load(l, "").into_int_value(), r, ""),
+                        _ => ctx.builder.build_xor(ctx.builder.build_load(l, "").into_int_value(), r, "")
+                    };
+                    ctx.builder.build_store(l, v);
+                }
+                lhs.inter_val = None;
+                lhs.data_type = Type::Reference(Box::new(Type::Bool), true);
+                Some(lhs)
+            },
+            (x @ Type::Struct(..), y) if x == y && op == "=" => {
+                if let (Some(PointerValue(l)), Some(r), false) = (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    let r = match r {
+                        PointerValue(r) => {
+                            if needs_drop(&x, ctx) && !move_out(r, ctx) {return None}
+                            ctx.builder.build_load(r, "")
+                        },
+                        r => r
+                    };
+                    if needs_drop(&x, ctx) {replace_value(l, &x, ctx);}
+                    ctx.builder.build_store(l, r);
+                }
+                lhs.inter_val = None;
+                lhs.data_type = Type::Reference(Box::new(x), true);
+                Some(lhs)
+            },
+            (x @ Type::Enum(..), y) if x == y && op == "=" => {
+                match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    (Some(PointerValue(l)), Some(PointerValue(r)), false) if !y.register() => {ctx.builder.build_store(l, ctx.builder.build_load(r, ""));},
+                    (Some(PointerValue(l)), Some(r), false) => {ctx.builder.build_store(l, r);},
+                    _ => {}
+                }
+                lhs.inter_val = None;
+                lhs.data_type = Type::Reference(Box::new(x), true);
+                Some(lhs)
+            },
             (Type::Pointer(b, m), r @ (Type::IntLiteral | Type::Int(..))) => match op {
                 "+=" => {
                     match (lhs.comp_val, rhs.comp_val, b.size(), ctx.is_const.get()) {
@@ -660,7 +789,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l < r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">" => Some(Variable {
@@ -672,7 +801,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l > r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "<=" => Some(Variable {
@@ -684,7 +813,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l <= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">=" => Some(Variable {
@@ -696,7 +825,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l >= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "==" => Some(Variable {
@@ -708,7 +837,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l == r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "!=" => Some(Variable {
@@ -720,10 +849,21 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l < r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
+                good: Cell::new(true)
+            }),
+            "^^" => Some(Variable {
+                comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    (Some(IntValue(l)), Some(IntValue(r)), false) => int_pow(l, r, ru, ctx).map(IntValue),
+                    _ => None
+                },
+                inter_val: match (lhs.inter_val, rhs.inter_val) {
+                    (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if r < 0 {0} else {l.wrapping_pow(r.min(u32::MAX as i128) as u32)})),
+                    _ => None
+                },
+                data_type: Type::Int(max(ls, rs), lu && ru),
                 good: Cell::new(true)
             }),
-            "^^" => None, // TODO: implement exponents
             _ => None
         },
         (x @ Type::Int(..), Type::IntLiteral) => bin_op(Variable {data_type: x.clone(), ..lhs}, impl_convert(Variable {data_type: Type::IntLiteral, ..rhs}, x, ctx)?, op, ctx),
@@ -853,7 +993,18 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                 data_type: Type::IntLiteral,
                 good: Cell::new(true)
             }),
-            "^^" => None, // TODO: implement exponents
+            "^^" => Some(Variable {
+                comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    (Some(IntValue(l)), Some(IntValue(r)), false) => int_pow(l, r, false, ctx).map(IntValue),
+                    _ => None
+                },
+                inter_val: match (lhs.inter_val, rhs.inter_val) {
+                    (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if r < 0 {0} else {l.wrapping_pow(r.min(u32::MAX as i128) as u32)})),
+                    _ => None
+                },
+                data_type: Type::IntLiteral,
+                good: Cell::new(true)
+            }),
             "<" => Some(Variable {
                 comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
                     (Some(IntValue(l)), Some(IntValue(r)), false) => Some(IntValue(ctx.builder.build_int_compare(SLT, l, r, ""))),
@@ -863,7 +1014,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l < r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">" => Some(Variable {
@@ -875,7 +1026,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l > r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "<=" => Some(Variable {
@@ -887,7 +1038,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l <= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">=" => Some(Variable {
@@ -899,7 +1050,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l >= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "==" => Some(Variable {
@@ -911,7 +1062,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l == r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "!=" => Some(Variable {
@@ -923,7 +1074,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Int(l)), Some(InterData::Int(r))) => Some(InterData::Int(if l < r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             _ => None
@@ -979,6 +1130,43 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
             }),
             _ => None
         },
+        (Type::Bool, Type::Bool) => match op {
+            "==" | "!=" | "&" | "|" | "^" => Some(Variable {
+                comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    (Some(IntValue(l)), Some(IntValue(r)), false) => Some(IntValue(match op {
+                        "==" => ctx.builder.build_int_compare(EQ, l, r, ""),
+                        "&" => ctx.builder.build_and(l, r, ""),
+                        "|" => ctx.builder.build_or(l, r, ""),
+                        _ => ctx.builder.build_xor(l, r, "")
+                    })),
+                    _ => None
+                },
+                inter_val: if let (Some(InterData::Int(l)), Some(InterData::Int(r))) = (lhs.inter_val, rhs.inter_val) {
+                    let (l, r) = (l != 0, r != 0);
+                    Some(InterData::Int(match op {
+                        "==" => l == r,
+                        "&" => l && r,
+                        "|" => l || r,
+                        _ => l != r
+                    } as i128))
+                } else {None},
+                data_type: Type::Bool,
+                good: Cell::new(true)
+            }),
+            _ => None
+        },
+        (l @ Type::Enum(..), r @ Type::Enum(..)) if l == r && l.is_c_enum() => match op {
+            "==" | "!=" => Some(Variable {
+                comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                    (Some(IntValue(l)), Some(IntValue(r)), false) => Some(IntValue(ctx.builder.build_int_compare(if op == "==" {EQ} else {NE}, l, r, ""))),
+                    _ => None
+                },
+                inter_val: if let (Some(InterData::Int(l)), Some(InterData::Int(r))) = (lhs.inter_val, rhs.inter_val) {Some(InterData::Int(((l == r) == (op == "==")) as i128))} else {None},
+                data_type: Type::Bool,
+                good: Cell::new(true)
+            }),
+            _ => None
+        },
         (l @ Type::Pointer(..), r @ Type::Pointer(..)) => match op {
             "-" if l == r => Some(Variable {
                 comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
@@ -1005,7 +1193,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">" => Some(Variable {
@@ -1019,7 +1207,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "<=" => Some(Variable {
@@ -1033,7 +1221,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">=" => Some(Variable {
@@ -1047,7 +1235,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "==" => Some(Variable {
@@ -1061,7 +1249,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "!=" => Some(Variable {
@@ -1075,7 +1263,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     _ => None
                 },
                 inter_val: None,
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             _ => None
@@ -1149,11 +1337,11 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
             }),
             "%" => Some(Variable {
                 comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
-                    (Some(FloatValue(l)), Some(FloatValue(r)), false) => None, // TODO: implement fmod
+                    (Some(FloatValue(l)), Some(FloatValue(r)), false) => Some(FloatValue(ctx.builder.build_float_rem(l, r, ""))),
                     _ => None
                 },
                 inter_val: match (lhs.inter_val, rhs.inter_val) {
-                    (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Float(l.rem_euclid(r))),
+                    (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Float(l % r)),
                     _ => None
                 },
                 data_type: l,
@@ -1161,14 +1349,18 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
             }),
             "^^" => Some(Variable {
                 comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
-                    (Some(FloatValue(l)), Some(FloatValue(r)), false) => None, // TODO: implement powf
+                    (Some(FloatValue(lv)), Some(FloatValue(rv)), false) => {
+                        let ft = lv.get_type();
+                        let f = intrinsic(&format!("llvm.pow.{}", float_suffix(&l)), ft.fn_type(&[ft.into(), ft.into()], false), ctx);
+                        ctx.builder.build_call(f, &[lv.into(), rv.into()], "").try_as_basic_value().left()
+                    },
                     _ => None
                 },
                 inter_val: match (lhs.inter_val, rhs.inter_val) {
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Float(l.powf(r))),
                     _ => None
                 },
-                data_type: bin_type(l, r, op),
+                data_type: l,
                 good: Cell::new(true)
             }),
             "<" => Some(Variable {
@@ -1180,7 +1372,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l < r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">" => Some(Variable {
@@ -1192,7 +1384,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l > r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "<=" => Some(Variable {
@@ -1204,7 +1396,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l <= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             ">=" => Some(Variable {
@@ -1216,7 +1408,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l >= r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "==" => Some(Variable {
@@ -1228,7 +1420,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l == r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             "!=" => Some(Variable {
@@ -1240,12 +1432,31 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
                     (Some(InterData::Float(l)), Some(InterData::Float(r))) => Some(InterData::Int(if l != r {1} else {0})),
                     _ => None
                 },
-                data_type: Type::Int(1, false),
+                data_type: Type::Bool,
                 good: Cell::new(true)
             }),
             _ => None
         },
+        (l @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), r @ (Type::IntLiteral | Type::Int(..))) if op == "^^" => Some(Variable {
+            comp_val: match (lhs.comp_val, rhs.comp_val, ctx.is_const.get()) {
+                (Some(FloatValue(lv)), Some(IntValue(rv)), false) => {
+                    let ft = lv.get_type();
+                    let it = ctx.context.i32_type();
+                    let rv = if matches!(r, Type::Int(s, true) if s < 32) {ctx.builder.build_int_z_extend(rv, it, "")} else {ctx.builder.build_int_cast(rv, it, "")};
+                    let f = intrinsic(&format!("llvm.powi.{}.i32", float_suffix(&l)), ft.fn_type(&[ft.into(), it.into()], false), ctx);
+                    ctx.builder.build_call(f, &[lv.into(), rv.into()], "").try_as_basic_value().left()
+                },
+                _ => None
+            },
+            inter_val: match (lhs.inter_val, rhs.inter_val) {
+                (Some(InterData::Float(l)), Some(InterData::Int(r))) => Some(InterData::Float(l.powi(r as i32))),
+                _ => None
+            },
+            data_type: l,
+            good: Cell::new(true)
+        }),
         (l @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), r @ (Type::IntLiteral | Type::Int(..))) => {
+            if let Some(InterData::Int(v)) = rhs.inter_val {rhs.inter_val = Some(InterData::Float(v as f64));}
             if let (Some(IntValue(rv)), false) = (rhs.comp_val, ctx.is_const.get()) {
                 rhs.comp_val = Some(FloatValue(match r {
                     Type::IntLiteral | Type::Int(_, false) => ctx.builder.build_signed_int_to_float(rv, l.llvm_type(ctx).unwrap().into_float_type(), ""),
@@ -1257,6 +1468,7 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
             bin_op(lhs, rhs, op, ctx)
         },
         (l @ (Type::IntLiteral | Type::Int(..)), r @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) => {
+            if let Some(InterData::Int(v)) = lhs.inter_val {lhs.inter_val = Some(InterData::Float(v as f64));}
             if let (Some(IntValue(lv)), false) = (lhs.comp_val, ctx.is_const.get()) {
                 lhs.comp_val = Some(FloatValue(match l {
                     Type::IntLiteral | Type::Int(_, false) => ctx.builder.build_signed_int_to_float(lv, r.llvm_type(ctx).unwrap().into_float_type(), ""),
@@ -1270,6 +1482,85 @@ pub fn bin_op<'ctx>(mut lhs: Variable<'ctx>, mut rhs: Variable<'ctx>, op: &str,
         _ => None
     }
 }
+pub fn intrinsic<'ctx>(name: &str, ty: inkwell::types::FunctionType<'ctx>, ctx: &CompCtx<'ctx>) -> inkwell::values::FunctionValue<'ctx> {
+    ctx.module.get_function(name).unwrap_or_else(|| ctx.module.add_function(name, ty, None))
+}
+fn float_suffix(t: &Type) -> &'static str {
+    match t {
+        Type::Float16 => "f16",
+        Type::Float32 => "f32",
+        Type::Float64 => "f64",
+        _ => "f128"
+    }
+}
+fn float_resize<'ctx>(val: Variable<'ctx>, target: Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    match (&val.data_type, &target) {
+        (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128, Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(FloatValue(v)), false) => Some(FloatValue(ctx.builder.build_float_cast(v, target.llvm_type(ctx)?.into_float_type(), ""))),
+                _ => None
+            },
+            data_type: target,
+            ..val
+        }),
+        _ => impl_convert(val, target, ctx)
+    }
+}
+fn compound_assign<'ctx>(mut lhs: Variable<'ctx>, rhs: Variable<'ctx>, target: Type, op: &str, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let ptr = lhs.comp_val;
+    let cur = Variable {
+        comp_val: match (ptr, ctx.is_const.get()) {
+            (Some(PointerValue(p)), false) => Some(ctx.builder.build_load(p, "")),
+            _ => None
+        },
+        inter_val: lhs.inter_val.take(),
+        data_type: target.clone(),
+        good: Cell::new(true)
+    };
+    let val = float_resize(bin_op(cur, rhs, &op[..(op.len() - 1)], ctx)?, target.clone(), ctx)?;
+    if let (Some(PointerValue(p)), Some(v), false) = (ptr, val.comp_val, ctx.is_const.get()) {
+        ctx.builder.build_store(p, v);
+    }
+    lhs.data_type = Type::Reference(Box::new(target), true);
+    Some(lhs)
+}
+fn int_pow<'ctx>(l: inkwell::values::IntValue<'ctx>, r: inkwell::values::IntValue<'ctx>, unsigned: bool, ctx: &CompCtx<'ctx>) -> Option<inkwell::values::IntValue<'ctx>> {
+    use inkwell::values::BasicValue;
+    let start = ctx.builder.get_insert_block()?;
+    let f = start.get_parent()?;
+    let lt = l.get_type();
+    let rt = r.get_type();
+    let (res0, exp0) = if unsigned {(lt.const_int(1, false), r)} else {
+        let neg = ctx.builder.build_int_compare(SLT, r, rt.const_zero(), "");
+        (ctx.builder.build_select(neg, lt.const_zero(), lt.const_int(1, false), "").into_int_value(), ctx.builder.build_select(neg, rt.const_zero(), r, "").into_int_value())
+    };
+    let cond_bb = ctx.context.append_basic_block(f, "pow_cond");
+    let body_bb = ctx.context.append_basic_block(f, "pow_body");
+    let exit_bb = ctx.context.append_basic_block(f, "pow_exit");
+    ctx.builder.build_unconditional_branch(cond_bb);
+    ctx.builder.position_at_end(cond_bb);
+    let res = ctx.builder.build_phi(lt, "");
+    let base = ctx.builder.build_phi(lt, "");
+    let exp = ctx.builder.build_phi(rt, "");
+    let rv = res.as_basic_value().into_int_value();
+    let bv = base.as_basic_value().into_int_value();
+    let ev = exp.as_basic_value().into_int_value();
+    let cmp = ctx.builder.build_int_compare(NE, ev, rt.const_zero(), "");
+    ctx.builder.build_conditional_branch(cmp, body_bb, exit_bb);
+    ctx.builder.position_at_end(body_bb);
+    let bit = ctx.builder.build_and(ev, rt.const_int(1, false), "");
+    let odd = ctx.builder.build_int_compare(NE, bit, rt.const_zero(), "");
+    let mul = ctx.builder.build_int_mul(rv, bv, "");
+    let res1 = ctx.builder.build_select(odd, mul, rv, "").into_int_value();
+    let base1 = ctx.builder.build_int_mul(bv, bv, "");
+    let exp1 = ctx.builder.build_right_shift(ev, rt.const_int(1, false), false, "");
+    ctx.builder.build_unconditional_branch(cond_bb);
+    res.add_incoming(&[(&res0 as &dyn BasicValue, start), (&res1 as &dyn BasicValue, body_bb)]);
+    base.add_incoming(&[(&l as &dyn BasicValue, start), (&base1 as &dyn BasicValue, body_bb)]);
+    exp.add_incoming(&[(&exp0 as &dyn BasicValue, start), (&exp1 as &dyn BasicValue, body_bb)]);
+    ctx.builder.position_at_end(exit_bb);
+    Some(rv)
+}
 pub fn pre_op<'ctx>(mut val: Variable<'ctx>, op: &str, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
     match val.data_type {
         Type::Borrow(x) => {
@@ -1426,6 +1717,15 @@ pub fn pre_op<'ctx>(mut val: Variable<'ctx>, op: &str, ctx: &CompCtx<'ctx>) -> O
                 }
             }
         },
+        Type::Bool => match op {
+            "!" => Some(Variable {
+                comp_val: if let (Some(IntValue(v)), false) = (val.comp_val, ctx.is_const.get()) {Some(IntValue(ctx.builder.build_not(v, "")))} else {None},
+                inter_val: if let Some(InterData::Int(v)) = val.inter_val {Some(InterData::Int((v == 0) as i128))} else {None},
+                data_type: Type::Bool,
+                good: Cell::new(true)
+            }),
+            _ => None
+        },
         Type::IntLiteral => match op {
             "+" => {
                 val.data_type = Type::IntLiteral;
@@ -1487,23 +1787,731 @@ pub fn pre_op<'ctx>(mut val: Variable<'ctx>, op: &str, ctx: &CompCtx<'ctx>) -> O
         _ => None
     }
 }
-pub fn post_op<'ctx>(val: Variable<'ctx>, _op: &str, _ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
-    match val.data_type { // The only posfix operators are ? and !, and they're for error handling
+pub fn post_op<'ctx>(val: Variable<'ctx>, op: &str, loc: Location, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    match op { // The only posfix operators are ? and !, and they're for error handling
+        "!" if ctx.is_const.get() => match post_type(val.data_type, op) {
+            Type::Null => None,
+            t => Some(Variable {comp_val: None, inter_val: None, data_type: t, good: Cell::new(true)})
+        },
+        "!" => {
+            let (present, val) = opt_parts(val, ctx)?;
+            trap_unless(present, &format!("{loc}: unwrapped an empty optional\n"), ctx);
+            Some(val)
+        },
+        _ => None
+    }
+}
+// splits an optional into whether it holds a value and the value itself, which is only meaningful if it's there
+pub fn opt_parts<'ctx>(val: Variable<'ctx>, ctx: &CompCtx<'ctx>) -> Option<(inkwell::values::IntValue<'ctx>, Variable<'ctx>)> {
+    if ctx.is_const.get() {return None}
+    let (t, v, borrowed) = match val.data_type {
+        Type::Reference(t, _) if matches!(*t, Type::Optional(_)) => {
+            let p = if let Some(PointerValue(p)) = val.comp_val {p} else {return None};
+            // an optional that needs to be dropped can only be unwrapped by moving out of it
+            if needs_drop(&t, ctx) && !move_out(p, ctx) {return None}
+            (*t, ctx.builder.build_load(p, ""), false)
+        },
+        Type::Borrow(t) => (*t, val.comp_val?, true),
+        t => (t, val.comp_val?, false)
+    };
+    let b = if let Type::Optional(b) = t {*b} else {return None};
+    let (present, v) = match v {
+        PointerValue(p) if b.null_niche() => (ctx.builder.build_is_not_null(p, ""), v),
+        StructValue(s) if !b.null_niche() => (ctx.builder.build_extract_value(s, 1, "")?.into_int_value(), ctx.builder.build_extract_value(s, 0, "")?),
+        _ => return None
+    };
+    Some((present, Variable::compiled(v, if borrowed {Type::Borrow(Box::new(b))} else {b})))
+}
+// writes a message to stderr and traps unless the condition holds
+fn trap_unless<'ctx>(cond: inkwell::values::IntValue<'ctx>, msg: &str, ctx: &CompCtx<'ctx>) {
+    if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {
+        let fb = ctx.context.append_basic_block(f, "check_failed");
+        let ob = ctx.context.append_basic_block(f, "check_passed");
+        ctx.builder.build_conditional_branch(cond, ob, fb);
+        ctx.builder.position_at_end(fb);
+        let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+        let write = ctx.module.get_function("write").unwrap_or_else(|| ctx.module.add_function("write", ctx.context.i64_type().fn_type(&[ctx.context.i32_type().into(), i8p.into(), ctx.context.i64_type().into()], false), None));
+        let s = ctx.builder.build_global_string_ptr(msg, "");
+        ctx.builder.build_call(write, &[ctx.context.i32_type().const_int(2, false).into(), s.as_pointer_value().into(), ctx.context.i64_type().const_int(msg.len() as u64, false).into()], "");
+        let trap = ctx.module.get_function("llvm.trap").unwrap_or_else(|| ctx.module.add_function("llvm.trap", ctx.context.void_type().fn_type(&[], false), None));
+        ctx.builder.build_call(trap, &[], "");
+        ctx.builder.build_unreachable();
+        ctx.builder.position_at_end(ob);
+    }
+}
+fn index_value<'ctx>(idx: &Variable<'ctx>, ctx: &CompCtx<'ctx>) -> Option<inkwell::values::IntValue<'ctx>> {
+    if ctx.is_const.get() {return None}
+    let i64t = ctx.context.i64_type();
+    match (&idx.data_type, idx.comp_val) {
+        (Type::IntLiteral, Some(IntValue(v))) => Some(v),
+        (Type::Int(64, _), Some(IntValue(v))) => Some(v),
+        (Type::Int(s, _), Some(IntValue(v))) if *s > 64 => Some(ctx.builder.build_int_truncate(v, i64t, "")),
+        (Type::Int(_, true), Some(IntValue(v))) => Some(ctx.builder.build_int_z_extend(v, i64t, "")),
+        (Type::Int(_, false), Some(IntValue(v))) => Some(ctx.builder.build_int_s_extend(v, i64t, "")),
+        _ => None
+    }
+}
+fn bounds_check<'ctx>(idx: inkwell::values::IntValue<'ctx>, len: inkwell::values::IntValue<'ctx>, ctx: &CompCtx<'ctx>) {
+    if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {
+        let ob = ctx.context.append_basic_block(f, "out_of_bounds");
+        let ib = ctx.context.append_basic_block(f, "in_bounds");
+        let cmp = ctx.builder.build_int_compare(ULT, idx, len, "");
+        ctx.builder.build_conditional_branch(cmp, ib, ob);
+        ctx.builder.position_at_end(ob);
+        let trap = ctx.module.get_function("llvm.trap").unwrap_or_else(|| ctx.module.add_function("llvm.trap", ctx.context.void_type().fn_type(&[], false), None));
+        ctx.builder.build_call(trap, &[], "");
+        ctx.builder.build_unreachable();
+        ctx.builder.position_at_end(ib);
+    }
+}
+pub fn subscript<'ctx>(mut val: Variable<'ctx>, mut idx: Variable<'ctx>, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    match (val.data_type, idx.data_type) {
+        (Type::Borrow(b), i) => {
+            val.data_type = *b;
+            idx.data_type = i;
+            subscript(val, idx, ctx)
+        },
+        (v, Type::Borrow(b)) => {
+            val.data_type = v;
+            idx.data_type = *b;
+            subscript(val, idx, ctx)
+        },
+        (v, Type::Reference(b, _)) => {
+            if !ctx.is_const.get() && b.register() {
+                if let Some(PointerValue(i)) = idx.comp_val {
+                    idx.comp_val = Some(ctx.builder.build_load(i, ""));
+                }
+            }
+            val.data_type = v;
+            idx.data_type = *b;
+            subscript(val, idx, ctx)
+        },
+        (Type::Reference(b, m), i @ (Type::IntLiteral | Type::Int(..))) => match *b {
+            Type::Array(b, Some(n)) => {
+                idx.data_type = i;
+                let iv = index_value(&idx, ctx);
+                if let (Some(iv), None) = (iv, &idx.inter_val) {bounds_check(iv, ctx.context.i64_type().const_int(n, false), ctx)}
+                Some(Variable {
+                    comp_val: match (val.comp_val, iv) {
+                        (Some(PointerValue(v)), Some(iv)) => Some(PointerValue(unsafe {ctx.builder.build_in_bounds_gep(v, &[ctx.context.i64_type().const_zero(), iv], "")})),
+                        _ => None
+                    },
+                    inter_val: match (val.inter_val, idx.inter_val) {
+                        (Some(InterData::Array(v)), Some(InterData::Int(i))) => usize::try_from(i).ok().and_then(|i| v.into_iter().nth(i)),
+                        _ => None
+                    },
+                    data_type: Type::Reference(b, m),
+                    good: Cell::new(true)
+                })
+            },
+            Type::Array(b, None) => {
+                idx.data_type = i;
+                let iv = index_value(&idx, ctx);
+                Some(Variable {
+                    comp_val: match (val.comp_val, iv) {
+                        (Some(PointerValue(v)), Some(iv)) => {
+                            let fp = ctx.builder.build_load(v, "").into_struct_value();
+                            let p = ctx.builder.build_extract_value(fp, 0, "").unwrap().into_pointer_value();
+                            let len = ctx.builder.build_extract_value(fp, 1, "").unwrap().into_int_value();
+                            bounds_check(iv, len, ctx);
+                            Some(PointerValue(unsafe {ctx.builder.build_in_bounds_gep(p, &[iv], "")}))
+                        },
+                        _ => None
+                    },
+                    inter_val: None,
+                    data_type: Type::Reference(b, m),
+                    good: Cell::new(true)
+                })
+            },
+            x => {
+                if !ctx.is_const.get() && x.register() {
+                    if let Some(PointerValue(v)) = val.comp_val {
+                        val.comp_val = Some(ctx.builder.build_load(v, ""));
+                    }
+                }
+                val.data_type = x;
+                idx.data_type = i;
+                subscript(val, idx, ctx)
+            }
+        },
+        (Type::Array(b, Some(n)), i @ (Type::IntLiteral | Type::Int(..))) => {
+            idx.data_type = i;
+            let iv = index_value(&idx, ctx);
+            Some(Variable {
+                comp_val: match (val.comp_val, idx.inter_val.as_ref(), iv) {
+                    (Some(ArrayValue(v)), Some(InterData::Int(i)), _) if *i >= 0 && (*i as u64) < n => ctx.builder.build_extract_value(v, *i as u32, ""),
+                    (Some(ArrayValue(v)), None, Some(iv)) => {
+                        bounds_check(iv, ctx.context.i64_type().const_int(n, false), ctx);
+                        let a = ctx.builder.build_alloca(v.get_type(), "");
+                        ctx.builder.build_store(a, v);
+                        let p = unsafe {ctx.builder.build_in_bounds_gep(a, &[ctx.context.i64_type().const_zero(), iv], "")};
+                        Some(ctx.builder.build_load(p, ""))
+                    },
+                    _ => None
+                },
+                inter_val: match (val.inter_val, idx.inter_val) {
+                    (Some(InterData::Array(v)), Some(InterData::Int(i))) => usize::try_from(i).ok().and_then(|i| v.into_iter().nth(i)),
+                    _ => None
+                },
+                data_type: *b,
+                good: Cell::new(true)
+            })
+        },
+        (Type::Array(b, None), i @ (Type::IntLiteral | Type::Int(..))) => {
+            idx.data_type = i;
+            let iv = index_value(&idx, ctx);
+            Some(Variable {
+                comp_val: match (val.comp_val, iv) {
+                    (Some(StructValue(v)), Some(iv)) => {
+                        let p = ctx.builder.build_extract_value(v, 0, "").unwrap().into_pointer_value();
+                        let len = ctx.builder.build_extract_value(v, 1, "").unwrap().into_int_value();
+                        bounds_check(iv, len, ctx);
+                        Some(PointerValue(unsafe {ctx.builder.build_in_bounds_gep(p, &[iv], "")}))
+                    },
+                    _ => None
+                },
+                inter_val: None,
+                data_type: Type::Reference(b, false),
+                good: Cell::new(true)
+            })
+        },
+        (Type::Pointer(b, m), i @ (Type::IntLiteral | Type::Int(..))) => {
+            idx.data_type = i;
+            let iv = index_value(&idx, ctx);
+            Some(Variable {
+                comp_val: match (val.comp_val, iv) {
+                    (Some(PointerValue(v)), Some(iv)) => Some(PointerValue(unsafe {ctx.builder.build_gep(v, &[iv], "")})),
+                    _ => None
+                },
+                inter_val: None,
+                data_type: Type::Reference(b, m),
+                good: Cell::new(true)
+            })
+        },
+        _ => None
+    }
+}
+pub fn field_type(t: &Type, name: &str) -> Option<Type> {
+    match t {
+        Type::Borrow(b) => field_type(b, name),
+        Type::Reference(b, m) | Type::Pointer(b, m) => match &**b {
+            Type::Struct(_, fields) => fields.iter().find(|(n, _)| n == name).map(|(_, t)| Type::Reference(Box::new(t.clone()), *m)),
+            x => field_type(x, name)
+        },
+        Type::Struct(_, fields) => fields.iter().find(|(n, _)| n == name).map(|(_, t)| t.clone()),
+        Type::Trait(_, methods) => methods.iter().find(|(n, ..)| n == name).map(|(_, t, _)| Type::Borrow(Box::new(t.clone()))),
+        Type::Coroutine(t) => match name {
+            "done" => Some(Type::Bool),
+            "value" => Some((**t).clone()),
+            _ => None
+        },
+        _ => None
+    }
+}
+pub fn variant_type(t: &Type, name: &str) -> Option<Type> {
+    if let Type::Enum(_, variants) = t {
+        match variants.iter().find(|(n, _)| n == name)? {
+            (_, None) => Some(t.clone()),
+            (_, Some(p)) => Some(Type::Function(Box::new(t.clone()), vec![(p.clone(), false)]))
+        }
+    }
+    else {None}
+}
+pub fn enum_payload<'ctx>(p: inkwell::values::PointerValue<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) -> Option<inkwell::values::PointerValue<'ctx>> {
+    let storage = ctx.builder.build_struct_gep(p, 1, "").ok()?;
+    Some(ctx.builder.build_pointer_cast(storage, t.llvm_type(ctx)?.ptr_type(inkwell::AddressSpace::from(0u16)), ""))
+}
+pub fn enum_value<'ctx>(ty: Type, tag: usize, payload: Option<Variable<'ctx>>, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let tv = ctx.context.custom_width_int_type(ty.enum_tag_bits()).const_int(tag as u64, false);
+    if ty.is_c_enum() {return Some(Variable::interpreted(IntValue(tv), InterData::Int(tag as i128), ty))}
+    let inter_val = match payload.as_ref() {
+        Some(p) => p.inter_val.clone().map(|v| InterData::Array(vec![InterData::Int(tag as i128), v])),
+        None => Some(InterData::Array(vec![InterData::Int(tag as i128)]))
+    };
+    let comp_val = if ctx.is_const.get() {None} else {
+        let a = ctx.builder.build_alloca(ty.llvm_type(ctx)?, "");
+        ctx.builder.build_store(ctx.builder.build_struct_gep(a, 0, "").ok()?, tv);
+        if let Some(p) = payload {
+            let v = p.value(ctx)?;
+            ctx.builder.build_store(enum_payload(a, &p.data_type, ctx)?, v);
+        }
+        Some(ctx.builder.build_load(a, ""))
+    };
+    Some(Variable {comp_val, inter_val, data_type: ty, good: Cell::new(true)})
+}
+pub fn coro_promise<'ctx>(hdl: inkwell::values::PointerValue<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) -> Option<inkwell::values::PointerValue<'ctx>> {
+    let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+    let f = intrinsic("llvm.coro.promise", i8p.fn_type(&[i8p.into(), ctx.context.i32_type().into(), ctx.context.bool_type().into()], false), ctx);
+    let p = ctx.builder.build_call(f, &[hdl.into(), ctx.context.i32_type().const_int(t.align().max(1), false).into(), ctx.context.bool_type().const_zero().into()], "").try_as_basic_value().left()?.into_pointer_value();
+    Some(ctx.builder.build_pointer_cast(p, t.llvm_type(ctx)?.ptr_type(inkwell::AddressSpace::from(0u16)), ""))
+}
+// associated functions of a type live in a module at the root named after the type, so they're saved with everything else
+pub fn assoc_module(t: &Type) -> DottedName {DottedName::absolute(vec![format!("<{t}>")])}
+pub fn assoc_function<'ctx>(t: &Type, name: &str, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let mut path = assoc_module(t);
+    path.ids.push(name.to_string());
+    ctx.with_vars(|v| v.lookup(&path).ok().and_then(Symbol::as_var).cloned())
+}
+// an implementation of a trait is recorded in the associated module of the implementing type, as a pointer to its vtable
+pub fn vtable_key(tr: &str) -> String {format!("[{tr}]")}
+pub fn vtable<'ctx>(t: &Type, tr: &str, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {assoc_function(t, &vtable_key(tr), ctx)}
+pub fn implements<'ctx>(t: &Type, tr: &str, ctx: &CompCtx<'ctx>) -> bool {
+    matches!(t, Type::Trait(n, _) if n == tr) || vtable(t, tr, ctx).is_some()
+}
+pub fn vtable_type<'ctx>(methods: &[(String, Type, bool)], ctx: &CompCtx<'ctx>) -> Option<inkwell::types::StructType<'ctx>> {
+    let fields = methods.iter().map(|(_, t, _)| t.llvm_type(ctx)?.into_struct_type().get_field_type_at_index(0)).collect::<Option<Vec<_>>>()?;
+    Some(ctx.context.struct_type(&fields, false))
+}
+fn trait_object<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let (name, methods) = if let Type::Trait(name, methods) = &target {(name, methods)} else {return None};
+    let (base, is_mut) = match val.data_type.clone() {
+        Type::Borrow(b) => {
+            val.data_type = *b;
+            return trait_object(val, target, ctx)
+        },
+        Type::Reference(b, m) => (*b, m),
+        // an object only points to its data, so it has to be made from something that outlives it
+        _ => return None
+    };
+    // methods that take a mutable receiver can only be called through an object made from a mutable reference
+    if methods.iter().any(|(_, _, m)| *m) && !is_mut {return None}
+    let vt = vtable(&base, name, ctx)?;
+    if ctx.is_const.get() {return Some(Variable {comp_val: None, inter_val: None, data_type: target, good: Cell::new(true)})}
+    let data = if let Some(PointerValue(p)) = val.comp_val {p} else {return None};
+    let i8p = ctx.context.i8_type().ptr_type(inkwell::AddressSpace::from(0u16));
+    let st = target.llvm_type(ctx)?.into_struct_type();
+    let v = ctx.builder.build_insert_value(st.get_undef(), ctx.builder.build_pointer_cast(data, i8p, ""), 0, "")?;
+    let v = ctx.builder.build_insert_value(v, ctx.builder.build_pointer_cast(vt.comp_val?.into_pointer_value(), i8p, ""), 1, "")?;
+    Some(Variable::compiled(StructValue(v.into_struct_value()), target))
+}
+// a destructor is an associated function named drop that takes the value by mutable reference
+pub fn destructor<'ctx>(t: &Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    assoc_function(t, "drop", ctx).filter(|f| matches!(&f.data_type, Type::Function(r, p) if **r == Type::Null && matches!(p.as_slice(), [(Type::Reference(b, true), false)] if **b == *t)))
+}
+pub fn needs_drop<'ctx>(t: &Type, ctx: &CompCtx<'ctx>) -> bool {
+    match t {
+        Type::Struct(_, fields) => destructor(t, ctx).is_some() || fields.iter().any(|(_, t)| needs_drop(t, ctx)),
+        Type::Array(b, Some(_)) | Type::Optional(b) => needs_drop(b, ctx),
+        Type::Closure(..) | Type::Coroutine(_) => true,
+        _ => false
+    }
+}
+// the destructor of the value itself runs first, then those of its fields
+pub fn drop_value<'ctx>(ptr: inkwell::values::PointerValue<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) {
+    if ctx.is_const.get() {return}
+    let f: Option<inkwell::values::CallableValue> = if let Some(Variable {comp_val: Some(PointerValue(f)), ..}) = destructor(t, ctx) {f.try_into().ok()} else {None};
+    if let Some(f) = f {ctx.builder.build_call(f, &[ptr.into()], "");}
+    match t {
+        Type::Struct(_, fields) => for (n, (_, ft)) in fields.iter().enumerate() {
+            if !needs_drop(ft, ctx) {continue}
+            if let Ok(p) = ctx.builder.build_struct_gep(ptr, n as u32, "") {drop_value(p, ft, ctx);}
+        },
+        Type::Array(b, Some(n)) if needs_drop(b, ctx) => for i in 0..*n {
+            let p = unsafe {ctx.builder.build_in_bounds_gep(ptr, &[ctx.context.i64_type().const_zero(), ctx.context.i64_type().const_int(i, false)], "")};
+            drop_value(p, b, ctx);
+        },
+        Type::Optional(b) if needs_drop(b, ctx) => if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {
+            let db = ctx.context.append_basic_block(f, "drop_some");
+            let cb = ctx.context.append_basic_block(f, "after_drop_some");
+            let (flag, val) = if let (Ok(flag), Ok(val)) = (ctx.builder.build_struct_gep(ptr, 1, ""), ctx.builder.build_struct_gep(ptr, 0, "")) {(flag, val)} else {return};
+            ctx.builder.build_conditional_branch(ctx.builder.build_load(flag, "").into_int_value(), db, cb);
+            ctx.builder.position_at_end(db);
+            drop_value(val, b, ctx);
+            ctx.builder.build_unconditional_branch(cb);
+            ctx.builder.position_at_end(cb);
+        },
+        Type::Closure(..) => if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {
+            // the first field of an environment is the function that drops and frees it
+            let env = if let Ok(env) = ctx.builder.build_struct_gep(ptr, 1, "") {ctx.builder.build_load(env, "").into_pointer_value()} else {return};
+            let db = ctx.context.append_basic_block(f, "drop_env");
+            let cb = ctx.context.append_basic_block(f, "after_drop_env");
+            ctx.builder.build_conditional_branch(ctx.builder.build_is_not_null(env, ""), db, cb);
+            ctx.builder.position_at_end(db);
+            let dt = ctx.context.void_type().fn_type(&[env.get_type().into()], false).ptr_type(inkwell::AddressSpace::from(0u16));
+            let dp = ctx.builder.build_pointer_cast(env, dt.ptr_type(inkwell::AddressSpace::from(0u16)), "");
+            let d: Option<inkwell::values::CallableValue> = ctx.builder.build_load(dp, "").into_pointer_value().try_into().ok();
+            if let Some(d) = d {ctx.builder.build_call(d, &[env.into()], "");}
+            ctx.builder.build_unconditional_branch(cb);
+            ctx.builder.position_at_end(cb);
+        },
+        Type::Coroutine(_) => {
+            // a handle owns its frame, so dropping it destroys the coroutine
+            let h = ctx.builder.build_load(ptr, "");
+            let f = intrinsic("llvm.coro.destroy", ctx.context.void_type().fn_type(&[h.get_type().into()], false), ctx);
+            ctx.builder.build_call(f, &[h.into()], "");
+        },
+        _ => {}
+    }
+}
+// drops each value whose flag is still set, in reverse order of declaration
+pub fn drop_live<'ctx>(targets: &[DropTarget<'ctx>], ctx: &CompCtx<'ctx>) {
+    if ctx.is_const.get() {return}
+    let f = if let Some(f) = ctx.builder.get_insert_block().and_then(|bb| bb.get_parent()) {f} else {return};
+    for target in targets.iter().rev() {
+        let db = ctx.context.append_basic_block(f, "drop");
+        let cb = ctx.context.append_basic_block(f, "after_drop");
+        let live = ctx.builder.build_load(target.flag, "").into_int_value();
+        ctx.builder.build_conditional_branch(live, db, cb);
+        ctx.builder.position_at_end(db);
+        drop_value(target.ptr, &target.data_type, ctx);
+        ctx.builder.build_unconditional_branch(cb);
+        ctx.builder.position_at_end(cb);
+    }
+}
+// locals that need to be dropped are registered in the innermost scope, with a flag to track whether they've been moved from
+pub fn track_drop<'ctx>(name: &DottedName, ptr: inkwell::values::PointerValue<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) {
+    if ctx.is_const.get() || !needs_drop(t, ctx) {return}
+    let mut drops = ctx.drops.borrow_mut();
+    if let Some(frame) = drops.last_mut() {
+        let flag = ctx.builder.build_alloca(ctx.context.bool_type(), "");
+        ctx.builder.build_store(flag, ctx.context.bool_type().const_int(1, false));
+        frame.push(DropTarget {name: name.clone(), ptr, flag, data_type: t.clone()});
+    }
+}
+pub fn drop_target<'ctx>(ptr: inkwell::values::PointerValue<'ctx>, ctx: &CompCtx<'ctx>) -> Option<DropTarget<'ctx>> {
+    ctx.drops.borrow().iter().flatten().rev().find(|t| t.ptr == ptr).cloned()
+}
+// moving out of a tracked local clears its flag and marks the variable as moved from; returns false if the pointer isn't tracked
+pub fn move_out<'ctx>(ptr: inkwell::values::PointerValue<'ctx>, ctx: &CompCtx<'ctx>) -> bool {
+    let target = if let Some(target) = drop_target(ptr, ctx) {target} else {return false};
+    let frame = ctx.drops.borrow().iter().rposition(|f| f.iter().any(|t| t.ptr == ptr));
+    if let (Some(frame), Some(l)) = (frame, ctx.loops.borrow_mut().last_mut()) {
+        if frame < l.depth && !l.moves.contains(&target.name) {l.moves.push(target.name.clone());}
+    }
+    ctx.builder.build_store(target.flag, ctx.context.bool_type().const_zero());
+    if let Ok(Symbol::Variable(v)) = ctx.with_vars(|v| v.lookup(&target.name)) {
+        if v.comp_val == Some(PointerValue(ptr)) {v.good.set(false);}
+    }
+    true
+}
+// assigning over a value drops whatever was there before
+fn replace_value<'ctx>(ptr: inkwell::values::PointerValue<'ctx>, t: &Type, ctx: &CompCtx<'ctx>) {
+    match drop_target(ptr, ctx) {
+        Some(target) => {
+            drop_live(std::slice::from_ref(&target), ctx);
+            ctx.builder.build_store(target.flag, ctx.context.bool_type().const_int(1, false));
+        },
+        None => drop_value(ptr, t, ctx)
+    }
+}
+// locals that live in memory are recorded in their scope, so references to them can be traced back for borrow checking
+pub fn track_local<'ctx>(name: &DottedName, ptr: inkwell::values::PointerValue<'ctx>, loc: Location, ctx: &CompCtx<'ctx>) {
+    if ctx.is_const.get() {return}
+    if let Some(frame) = ctx.borrows.borrow_mut().last_mut() {frame.locals.push((name.clone(), ptr, loc));}
+}
+// follows field accesses and casts back to the local that a pointer was derived from
+pub fn borrow_root<'ctx>(mut ptr: inkwell::values::PointerValue<'ctx>, ctx: &CompCtx<'ctx>) -> Option<(DottedName, inkwell::values::PointerValue<'ctx>, Location)> {
+    use inkwell::values::InstructionOpcode::*;
+    loop {
+        let borrows = ctx.borrows.borrow();
+        if let Some(local) = borrows.iter().flat_map(|f| f.locals.iter()).find(|(_, p, _)| *p == ptr) {return Some(local.clone())}
+        // going through a reference that's held by another local isn't a new borrow
+        if borrows.iter().flat_map(|f| f.borrows.iter()).any(|b| b.via == Some(ptr)) {return None}
+        let inst = ptr.as_instruction()?;
+        ptr = match (inst.get_opcode(), inst.get_operand(0).and_then(|x| x.left())) {
+            (GetElementPtr | BitCast, Some(PointerValue(p))) => p,
+            _ => return None
+        };
+    }
+}
+fn borrow_kind(is_mut: bool) -> &'static str {if is_mut {"mutable"} else {"immutable"}}
+// a mutable borrow has to be the only borrow of a value; returns the borrow that would be taken, if it's of a local
+pub fn check_borrow<'ctx>(val: &Variable<'ctx>, is_mut: bool, loc: Location, temps: &[BorrowTarget<'ctx>], ctx: &CompCtx<'ctx>) -> Result<Option<BorrowTarget<'ctx>>, Error> {
+    if ctx.is_const.get() || !matches!(val.data_type, Type::Reference(..)) {return Ok(None)}
+    let (name, ptr, _) = if let Some(PointerValue(p)) = val.comp_val {if let Some(root) = borrow_root(p, ctx) {root} else {return Ok(None)}} else {return Ok(None)};
+    let borrows = ctx.borrows.borrow();
+    if let Some(prev) = borrows.iter().flat_map(|f| f.borrows.iter()).chain(temps.iter()).find(|b| b.ptr == ptr && (is_mut || b.is_mut)) {
+        return Err(Error::new(loc.clone(), 370, format!("cannot borrow {name} as {} because it is already borrowed as {}", borrow_kind(is_mut), borrow_kind(prev.is_mut)))
+            .note(Note::new(loc, format!("{} borrow occurs here", borrow_kind(is_mut))))
+            .note(Note::new(prev.loc.clone(), format!("previous {} borrow occurs here", borrow_kind(prev.is_mut)))))
+    }
+    Ok(Some(BorrowTarget {name, ptr, is_mut, loc, via: None}))
+}
+// a borrow held by a local lasts until the end of the local's scope
+pub fn hold_borrow<'ctx>(borrow: BorrowTarget<'ctx>, ctx: &CompCtx<'ctx>) {
+    if let Some(frame) = ctx.borrows.borrow_mut().last_mut() {frame.borrows.push(borrow);}
+}
+// a local can't be used directly while something else holds a mutable borrow of it
+pub fn check_access<'ctx>(name: &DottedName, val: &Variable<'ctx>, loc: Location, ctx: &CompCtx<'ctx>) -> Option<Error> {
+    if ctx.is_const.get() {return None}
+    let ptr = if let Some(PointerValue(p)) = val.comp_val {p} else {return None};
+    let borrows = ctx.borrows.borrow();
+    if !borrows.iter().flat_map(|f| f.locals.iter()).any(|(n, p, _)| *p == ptr && n.ids == name.ids) {return None}
+    let prev = borrows.iter().flat_map(|f| f.borrows.iter()).find(|b| b.ptr == ptr && b.is_mut)?;
+    Some(Error::new(loc.clone(), 370, format!("cannot use {name} because it is mutably borrowed")).note(Note::new(prev.loc.clone(), "mutable borrow occurs here".to_string())).note(Note::new(loc, format!("{name} is used here"))))
+}
+// writing to a local, or taking a mutable pointer to it, isn't allowed while it's borrowed; mutable borrows are already caught by check_access
+pub fn check_write<'ctx>(val: &Variable<'ctx>, loc: Location, ctx: &CompCtx<'ctx>) -> Option<Error> {
+    if ctx.is_const.get() || !matches!(val.data_type, Type::Reference(_, true)) {return None}
+    let (name, ptr, _) = if let Some(PointerValue(p)) = val.comp_val {borrow_root(p, ctx)?} else {return None};
+    let borrows = ctx.borrows.borrow();
+    let prev = borrows.iter().flat_map(|f| f.borrows.iter()).find(|b| b.ptr == ptr && !b.is_mut)?;
+    Some(Error::new(loc.clone(), 370, format!("cannot assign to {name} because it is borrowed")).note(Note::new(prev.loc.clone(), "immutable borrow occurs here".to_string())).note(Note::new(loc, format!("{name} is assigned to here"))))
+}
+// borrows taken by arguments last for the whole call, so they can't conflict with each other
+fn call_borrows<'a, 'ctx>(args: &[(Variable<'ctx>, Location)], params: impl Iterator<Item = &'a Type>, ctx: &CompCtx<'ctx>) -> Result<(), Error> {
+    let mut temps = vec![];
+    for ((v, l), t) in args.iter().zip(params) {
+        let is_mut = match t {
+            Type::Reference(_, m) => *m,
+            Type::Borrow(_) => false,
+            _ => continue
+        };
+        if let Some(b) = check_borrow(v, is_mut, l.clone(), &temps, ctx)? {temps.push(b);}
+    }
+    Ok(())
+}
+// finds the environments a closure could have, or the data a trait object could point to, looking through the local that it's stored in
+fn held_pointers<'ctx>(val: &Variable<'ctx>) -> Vec<inkwell::values::PointerValue<'ctx>> {
+    use inkwell::values::{BasicValue, AnyValueEnum, InstructionOpcode::*};
+    fn held<'ctx>(t: &Type, v: inkwell::values::BasicValueEnum<'ctx>) -> Option<inkwell::values::PointerValue<'ctx>> {
+        use inkwell::values::{BasicValue, InstructionOpcode::*};
+        let ins = |v: inkwell::values::BasicValueEnum<'ctx>| v.as_instruction_value().filter(|i| i.get_opcode() == InsertValue);
+        // a closure's environment is the last field inserted, and a trait object's data is the first
+        let p = match t {
+            Type::Closure(..) => ins(v)?.get_operand(1)?.left()?,
+            _ => ins(ins(v)?.get_operand(0)?.left()?)?.get_operand(1)?.left()?
+        };
+        if let PointerValue(p) = p {Some(p)} else {None}
+    }
+    match (&val.data_type, val.comp_val) {
+        (t @ (Type::Closure(..) | Type::Trait(..)), Some(v)) => held(t, v).into_iter().collect(),
+        (Type::Reference(b, _), Some(PointerValue(p))) if matches!(**b, Type::Closure(..) | Type::Trait(..)) => {
+            let mut out = vec![];
+            let mut u = p.get_first_use();
+            while let Some(use_) = u {
+                if let AnyValueEnum::InstructionValue(i) = use_.get_user() {
+                    if i.get_opcode() == Store && i.get_operand(1).and_then(|x| x.left()) == Some(PointerValue(p)) {
+                        out.extend(i.get_operand(0).and_then(|x| x.left()).and_then(|v| held(b, v)));
+                    }
+                }
+                u = use_.get_next_use();
+            }
+            out
+        },
+        _ => vec![]
+    }
+}
+// the local that a trait object points into, or that one made from this reference would
+pub fn object_root<'ctx>(val: &Variable<'ctx>, ctx: &CompCtx<'ctx>) -> Option<(DottedName, inkwell::values::PointerValue<'ctx>, Location)> {
+    if ctx.is_const.get() {return None}
+    match (&val.data_type, val.comp_val) {
+        (Type::Reference(b, _), Some(PointerValue(p))) if !matches!(**b, Type::Trait(..) | Type::Reference(..)) => borrow_root(p, ctx),
+        _ => held_pointers(val).into_iter().find_map(|p| borrow_root(p, ctx))
+    }
+}
+// references to locals can't be returned, since the locals don't live past the function
+pub fn check_escape<'ctx>(val: &Variable<'ctx>, ret: &Type, loc: Location, ctx: &CompCtx<'ctx>) -> Option<Error> {
+    if !ctx.is_const.get() && matches!(ret, Type::Trait(..)) {
+        let (name, _, def) = object_root(val, ctx)?;
+        return Some(Error::new(loc.clone(), 371, format!("trait object pointing to {name} outlives it")).note(Note::new(def, format!("{name} is defined here"))).note(Note::new(loc, "trait object escapes the function here".to_string())))
+    }
+    if !ctx.is_const.get() && matches!(ret, Type::Closure(..)) {
+        let envs = held_pointers(val);
+        let borrows = ctx.borrows.borrow();
+        let (_, name, def) = borrows.iter().flat_map(|f| f.closures.iter()).find(|(e, _, _)| envs.contains(e))?;
+        return Some(Error::new(loc.clone(), 371, format!("closure capturing {name} by reference outlives it")).note(Note::new(def.clone(), format!("{name} is captured here"))).note(Note::new(loc, "closure escapes the function here".to_string())))
+    }
+    if ctx.is_const.get() || !matches!(ret, Type::Reference(..)) || !matches!(val.data_type, Type::Reference(..)) {return None}
+    let (name, _, def) = if let Some(PointerValue(p)) = val.comp_val {borrow_root(p, ctx)?} else {return None};
+    Some(Error::new(loc.clone(), 371, format!("reference to {name} outlives it")).note(Note::new(def, format!("{name} is defined here"))).note(Note::new(loc, "reference escapes the function here".to_string())))
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpKind {Binary, Prefix, Postfix}
+impl std::fmt::Display for OpKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            OpKind::Binary => "binary",
+            OpKind::Prefix => "prefix",
+            OpKind::Postfix => "postfix"
+        })
+    }
+}
+// operator overloads are kept in the associated module of the first operand's type, keyed by the operator and the type of the other operand
+pub fn op_key(op: &str, kind: OpKind, rhs: Option<&Type>) -> String {
+    match kind {
+        OpKind::Binary => format!("{op}({})", rhs.map_or(String::new(), Type::to_string)),
+        OpKind::Prefix => format!("{op}()"),
+        OpKind::Postfix => format!("(){op}")
+    }
+}
+pub fn find_op<'ctx>(op: &str, kind: OpKind, operands: &[&Type], ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    fn strip(mut t: &Type) -> &Type {
+        while let Type::Reference(b, _) | Type::Borrow(b) = t {t = &**b;}
+        t
+    }
+    let module = ctx.with_vars(|v| v.lookup(&assoc_module(strip(operands.first()?))).ok().and_then(Symbol::as_mod).cloned())?;
+    if kind != OpKind::Binary {return module.get(&op_key(op, kind, None)).and_then(Symbol::as_var).cloned()}
+    let rhs = strip(operands.get(1)?);
+    let mut cands = module.iter().filter(|(k, _)| k.starts_with(op) && k[op.len()..].starts_with('(')).filter_map(|(k, s)| Some((k, s.as_var()?))).collect::<Vec<_>>();
+    cands.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let param = |f: &Variable| if let Type::Function(_, p) = &f.data_type {p.get(1).map(|(t, _)| strip(t).clone())} else {None};
+    cands.iter().find(|(_, f)| param(f).as_ref() == Some(rhs))
+        .or_else(|| cands.iter().find(|(_, f)| *rhs == Type::IntLiteral && matches!(param(f), Some(Type::Int(..) | Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128))))
+        .map(|(_, f)| (*f).clone())
+}
+pub fn field<'ctx>(mut val: Variable<'ctx>, name: &str, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    match val.data_type {
+        Type::Borrow(b) => {
+            val.data_type = *b;
+            field(val, name, ctx)
+        },
+        Type::Reference(b, m) | Type::Pointer(b, m) => match *b {
+            Type::Struct(_, fields) => {
+                let idx = fields.iter().position(|(n, _)| n == name)?;
+                Some(Variable {
+                    comp_val: match val.comp_val {
+                        Some(PointerValue(v)) if !ctx.is_const.get() => ctx.builder.build_struct_gep(v, idx as u32, "").ok().map(PointerValue),
+                        _ => None
+                    },
+                    inter_val: if let Some(InterData::Array(v)) = val.inter_val {v.into_iter().nth(idx)} else {None},
+                    data_type: Type::Reference(Box::new(fields.into_iter().nth(idx)?.1), m),
+                    good: Cell::new(true)
+                })
+            },
+            x => {
+                if !ctx.is_const.get() && x.register() {
+                    if let Some(PointerValue(v)) = val.comp_val {
+                        val.comp_val = Some(ctx.builder.build_load(v, ""));
+                    }
+                }
+                val.data_type = x;
+                field(val, name, ctx)
+            }
+        },
+        Type::Struct(_, fields) => {
+            let idx = fields.iter().position(|(n, _)| n == name)?;
+            Some(Variable {
+                comp_val: match val.comp_val {
+                    Some(StructValue(v)) if !ctx.is_const.get() => ctx.builder.build_extract_value(v, idx as u32, ""),
+                    _ => None
+                },
+                inter_val: if let Some(InterData::Array(v)) = val.inter_val {v.into_iter().nth(idx)} else {None},
+                data_type: fields.into_iter().nth(idx)?.1,
+                good: Cell::new(true)
+            })
+        },
+        Type::Trait(_, methods) => {
+            // methods are looked up in the vtable and bound to the data pointer, which gives a closure
+            // the closure doesn't own its environment, so it's borrowed to keep it from being dropped
+            let idx = methods.iter().position(|(n, ..)| n == name)?;
+            let vt = vtable_type(&methods, ctx);
+            let ft = methods.into_iter().nth(idx)?.1;
+            Some(Variable {
+                comp_val: match (val.comp_val, vt) {
+                    (Some(StructValue(v)), Some(vt)) if !ctx.is_const.get() => (|| {
+                        let data = ctx.builder.build_extract_value(v, 0, "")?;
+                        let vp = ctx.builder.build_pointer_cast(ctx.builder.build_extract_value(v, 1, "")?.into_pointer_value(), vt.ptr_type(inkwell::AddressSpace::from(0u16)), "");
+                        let f = ctx.builder.build_load(ctx.builder.build_struct_gep(vp, idx as u32, "").ok()?, "");
+                        let st = ft.llvm_type(ctx)?.into_struct_type();
+                        let c = ctx.builder.build_insert_value(st.get_undef(), f, 0, "")?;
+                        Some(StructValue(ctx.builder.build_insert_value(c, data, 1, "")?.into_struct_value()))
+                    })(),
+                    _ => None
+                },
+                inter_val: None,
+                data_type: Type::Borrow(Box::new(ft)),
+                good: Cell::new(true)
+            })
+        },
+        Type::Coroutine(t) => match name {
+            "done" => Some(Variable {
+                comp_val: match val.comp_val {
+                    Some(PointerValue(v)) if !ctx.is_const.get() => {
+                        let f = intrinsic("llvm.coro.done", ctx.context.bool_type().fn_type(&[v.get_type().into()], false), ctx);
+                        ctx.builder.build_call(f, &[v.into()], "").try_as_basic_value().left()
+                    },
+                    _ => None
+                },
+                inter_val: None,
+                data_type: Type::Bool,
+                good: Cell::new(true)
+            }),
+            "value" => Some(Variable {
+                comp_val: match val.comp_val {
+                    Some(PointerValue(v)) if !ctx.is_const.get() => coro_promise(v, &t, ctx).map(|p| ctx.builder.build_load(p, "")),
+                    _ => None
+                },
+                inter_val: None,
+                data_type: *t,
+                good: Cell::new(true)
+            }),
+            _ => None
+        },
+        Type::TypeData => match val.inter_val {
+            Some(InterData::Type(t)) => if let Some(vt) = variant_type(&t, name) {
+                let idx = if let Type::Enum(_, variants) = &*t {variants.iter().position(|(n, _)| n == name)?} else {return None};
+                // payload-carrying variants are called like functions to construct them
+                if let Type::Function(..) = vt {Some(Variable::metaval(InterData::Int(idx as i128), vt))}
+                else {enum_value(*t, idx, None, ctx)}
+            } else {assoc_function(&t, name, ctx)},
+            _ => None
+        },
+        _ => None
+    }
+}
+pub fn to_bool<'ctx>(val: Variable<'ctx>, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    let mut t = &val.data_type;
+    while let Type::Reference(b, _) | Type::Borrow(b) = t {t = &**b;}
+    if *t == Type::Bool {return impl_convert(val, Type::Bool, ctx)}
+    bin_op(val, Variable::interpreted(IntValue(ctx.context.i64_type().const_zero()), InterData::Int(0), Type::IntLiteral), "!=", ctx)
+}
+pub fn common(lhs: &Type, rhs: &Type) -> Option<Type> {
+    if lhs == rhs {return Some(lhs.clone())}
+    match (lhs, rhs) {
+        (Type::Reference(x, _) | Type::Borrow(x), r) => common(x, r),
+        (l, Type::Reference(x, _) | Type::Borrow(x)) => common(l, x),
+        (Type::IntLiteral, x @ (Type::Int(..) | Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) | (x @ (Type::Int(..) | Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), Type::IntLiteral) => Some(x.clone()),
         _ => None
     }
 }
 pub fn impl_convert<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
     if val.data_type == target {Some(val)}
+    else if matches!(target, Type::Trait(..)) && !matches!(&val.data_type, Type::Reference(b, _) | Type::Borrow(b) if **b == target) {trait_object(val, target, ctx)}
     else {
         match val.data_type {
             Type::Borrow(b) => {
+                // a borrow doesn't own its value, so it can't become something that would be dropped
+                if needs_drop(&b, ctx) && !matches!(target, Type::Borrow(_)) {return None}
                 val.data_type = *b;
                 impl_convert(val, target, ctx)
             },
+            Type::Reference(..) if matches!(&target, Type::Optional(b) if b.null_niche()) => {
+                // the reference itself is wrapped, instead of the value behind it
+                let b = if let Type::Optional(b) = &target {(**b).clone()} else {unreachable!("the guard only matches optionals")};
+                let v = impl_convert(val, b, ctx)?;
+                Some(Variable {comp_val: v.comp_val.filter(|_| !ctx.is_const.get()), inter_val: None, data_type: target, good: Cell::new(true)})
+            },
+            Type::Reference(b, _) if matches!((&*b, &target), (Type::Array(x, Some(_)), Type::Array(y, None)) if x == y) => {
+                if let (Type::Array(_, Some(n)), Some(PointerValue(v)), false) = (*b, val.comp_val, ctx.is_const.get()) {
+                    let ft = target.llvm_type(ctx).unwrap().into_struct_type();
+                    let p = unsafe {ctx.builder.build_in_bounds_gep(v, &[ctx.context.i64_type().const_zero(), ctx.context.i64_type().const_zero()], "")};
+                    let fp = ctx.builder.build_insert_value(ft.get_undef(), p, 0, "").unwrap();
+                    let fp = ctx.builder.build_insert_value(fp, ctx.context.i64_type().const_int(n, false), 1, "").unwrap();
+                    Some(Variable::compiled(StructValue(fp.into_struct_value()), target))
+                }
+                else {Some(Variable {comp_val: None, inter_val: None, data_type: target, good: Cell::new(true)})}
+            },
+            Type::Reference(b, _) if matches!(&target, Type::Borrow(t) if **t == *b) => {
+                // borrowing reads the value without moving out of it
+                if !ctx.is_const.get() {
+                    if let Some(PointerValue(v)) = val.comp_val {
+                        val.comp_val = Some(ctx.builder.build_load(v, ""));
+                    }
+                }
+                val.data_type = target;
+                Some(val)
+            },
+            Type::Reference(b, _) if (*b == target || matches!(&target, Type::Optional(t) if *t == b)) && !ctx.is_const.get() && needs_drop(&b, ctx) => {
+                // values that need to be dropped can only be moved out of a local that owns them
+                let p = if let Some(PointerValue(p)) = val.comp_val {p} else {return None};
+                if !move_out(p, ctx) {return None}
+                impl_convert(Variable::compiled(ctx.builder.build_load(p, ""), *b), target, ctx)
+            },
             Type::Reference(b, true) => {
                 if &target == &Type::Reference(b.clone(), false) {Some(Variable {data_type: Type::Reference(b, false), ..val})}
                 else {
-                    if !ctx.is_const.get() && b.register() {
+                    if !ctx.is_const.get() && (b.register() || matches!(*b, Type::Array(_, Some(_)))) {
                         if let Some(PointerValue(v)) = val.comp_val {
                             val.comp_val = Some(ctx.builder.build_load(v, ""));
                         }
@@ -1513,7 +2521,7 @@ pub fn impl_convert<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'
                 }
             },
             Type::Reference(b, false) => {
-                if !ctx.is_const.get() && b.register() {
+                if !ctx.is_const.get() && (b.register() || matches!(*b, Type::Array(_, Some(_)))) {
                     if let Some(PointerValue(v)) = val.comp_val {
                         val.comp_val = Some(ctx.builder.build_load(v, ""));
                     }
@@ -1521,6 +2529,22 @@ pub fn impl_convert<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'
                 val.data_type = *b;
                 impl_convert(val, target, ctx)
             },
+            Type::Null if matches!(target, Type::Optional(_)) => Some(Variable::compiled(target.llvm_type(ctx)?.const_zero(), target)),
+            x if matches!(target, Type::Optional(_)) => {
+                let b = if let Type::Optional(b) = &target {(**b).clone()} else {unreachable!("the guard only matches optionals")};
+                let v = impl_convert(Variable {data_type: x, ..val}, b.clone(), ctx)?;
+                let comp_val = match (v.value(ctx), ctx.is_const.get()) {
+                    (Some(v), false) if b.null_niche() => Some(v),
+                    (Some(v), false) => {
+                        // non-register values converted from a reference are still behind a pointer
+                        let v = if let (PointerValue(p), false) = (v, b.llvm_type(ctx)?.is_pointer_type()) {ctx.builder.build_load(p, "")} else {v};
+                        let s = ctx.builder.build_insert_value(target.llvm_type(ctx)?.into_struct_type().get_undef(), v, 0, "")?;
+                        Some(StructValue(ctx.builder.build_insert_value(s, ctx.context.bool_type().const_int(1, false), 1, "")?.into_struct_value()))
+                    },
+                    _ => None
+                };
+                Some(Variable {comp_val, inter_val: None, data_type: target, good: Cell::new(true)})
+            },
             Type::IntLiteral => match target {
                 x @ Type::Int(..) => Some(Variable {
                     comp_val: if let Some(InterData::Int(v)) = val.inter_val {Some(IntValue(x.llvm_type(ctx).unwrap().into_int_type().const_int(v as u64, true)))}
@@ -1539,11 +2563,134 @@ pub fn impl_convert<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'
                 }),
                 _ => None
             },
+            x if matches!(&target, Type::Borrow(t) if **t == x) => Some(Variable {data_type: target, ..val}),
             _ => None
         }
     }
 }
-pub fn expl_convert<'ctx>(val: Variable<'ctx>, target: Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {impl_convert(val, target, ctx)}
+fn wrap_int(v: i128, bits: u64, unsigned: bool) -> i128 {
+    if bits == 0 {return 0}
+    if bits >= 128 {return v}
+    let m = v & (1i128 << bits).wrapping_sub(1);
+    if !unsigned && (m >> (bits - 1)) & 1 == 1 {m.wrapping_sub(1i128 << bits)} else {m}
+}
+pub fn expl_convert<'ctx>(mut val: Variable<'ctx>, target: Type, ctx: &CompCtx<'ctx>) -> Option<Variable<'ctx>> {
+    if val.data_type == target {return Some(val)}
+    match (val.data_type, target) {
+        (Type::Borrow(b), t) => {
+            val.data_type = *b;
+            expl_convert(val, t, ctx)
+        },
+        (l @ Type::Reference(..), t @ (Type::Reference(..) | Type::Array(_, None))) => impl_convert(Variable {data_type: l, ..val}, t, ctx),
+        (Type::Reference(b, _), t) => {
+            if !ctx.is_const.get() && (b.register() || matches!(*b, Type::Array(_, Some(_)))) {
+                if let Some(PointerValue(v)) = val.comp_val {
+                    val.comp_val = Some(ctx.builder.build_load(v, ""));
+                }
+            }
+            val.data_type = *b;
+            expl_convert(val, t, ctx)
+        },
+        (Type::Bool, x @ Type::Int(..)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(IntValue(v)), false) => Some(IntValue(ctx.builder.build_int_z_extend(v, x.llvm_type(ctx)?.into_int_type(), ""))),
+                _ => None
+            },
+            data_type: x,
+            ..val
+        }),
+        (l @ (Type::IntLiteral | Type::Int(..)), Type::Bool) => to_bool(Variable {data_type: l, ..val}, ctx),
+        (Type::IntLiteral, x @ Type::Pointer(..)) => {
+            let v = impl_convert(Variable {data_type: Type::IntLiteral, ..val}, Type::Int(64, true), ctx)?;
+            expl_convert(v, x, ctx)
+        },
+        (Type::Int(ls, lu), x @ Type::Int(rs, ru)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(IntValue(v)), false) => {
+                    let it = x.llvm_type(ctx)?.into_int_type();
+                    Some(IntValue(if rs < ls {ctx.builder.build_int_truncate(v, it, "")}
+                    else if rs == ls {v}
+                    else if lu {ctx.builder.build_int_z_extend(v, it, "")}
+                    else {ctx.builder.build_int_s_extend(v, it, "")}))
+                },
+                _ => None
+            },
+            inter_val: if let Some(InterData::Int(v)) = val.inter_val {Some(InterData::Int(wrap_int(v, rs, ru)))} else {None},
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (Type::Int(_, lu), x @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(IntValue(v)), false) => {
+                    let ft = x.llvm_type(ctx)?.into_float_type();
+                    Some(FloatValue(if lu {ctx.builder.build_unsigned_int_to_float(v, ft, "")} else {ctx.builder.build_signed_int_to_float(v, ft, "")}))
+                },
+                _ => None
+            },
+            inter_val: if let Some(InterData::Int(v)) = val.inter_val {Some(InterData::Float(v as f64))} else {None},
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128, x @ Type::Int(rs, ru)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(FloatValue(v)), false) => {
+                    let it = x.llvm_type(ctx)?.into_int_type();
+                    Some(IntValue(if ru {ctx.builder.build_float_to_unsigned_int(v, it, "")} else {ctx.builder.build_float_to_signed_int(v, it, "")}))
+                },
+                _ => None
+            },
+            inter_val: if let Some(InterData::Float(v)) = val.inter_val {Some(InterData::Int(wrap_int(v as i128, rs, ru)))} else {None},
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (l @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128), x @ (Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128)) => {
+            let inter_val = match (val.inter_val.take(), &x) {
+                (Some(InterData::Float(v)), Type::Float32 | Type::Float16) => Some(InterData::Float(v as f32 as f64)),
+                (v, _) => v
+            };
+            float_resize(Variable {data_type: l, inter_val, ..val}, x, ctx)
+        },
+        (Type::Pointer(..), x @ Type::Pointer(..)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(PointerValue(v)), false) => Some(PointerValue(ctx.builder.build_pointer_cast(v, x.llvm_type(ctx)?.into_pointer_type(), ""))),
+                _ => None
+            },
+            inter_val: None,
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (Type::Pointer(..), x @ Type::Int(64, _)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(PointerValue(v)), false) => Some(IntValue(ctx.builder.build_ptr_to_int(v, ctx.context.i64_type(), ""))),
+                _ => None
+            },
+            inter_val: None,
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (Type::Int(64, _), x @ Type::Pointer(..)) => Some(Variable {
+            comp_val: match (val.comp_val, ctx.is_const.get()) {
+                (Some(IntValue(v)), false) => Some(PointerValue(ctx.builder.build_int_to_ptr(v, x.llvm_type(ctx)?.into_pointer_type(), ""))),
+                _ => None
+            },
+            inter_val: None,
+            data_type: x,
+            good: Cell::new(true)
+        }),
+        (l, t) => impl_convert(Variable {data_type: l, ..val}, t, ctx)
+    }
+}
+pub fn expl_casts(t: &Type) -> Vec<&'static str> {
+    match t {
+        Type::Borrow(b) | Type::Reference(b, _) => expl_casts(b),
+        Type::IntLiteral | Type::Int(64, _) => vec!["integer types", "floating-point types", "pointer types", "bool"],
+        Type::Int(..) => vec!["integer types", "floating-point types", "bool"],
+        Type::Bool => vec!["integer types"],
+        Type::Float16 | Type::Float32 | Type::Float64 | Type::Float128 => vec!["integer types", "floating-point types"],
+        Type::Pointer(..) => vec!["pointer types", "u64", "i64"],
+        _ => vec![]
+    }
+}
 pub fn call<'ctx>(mut target: Variable<'ctx>, loc: Location, mut args: Vec<(Variable<'ctx>, Location)>, ctx: &CompCtx<'ctx>) -> Result<Variable<'ctx>, Error> {
     match target.data_type {
         Type::Borrow(b) => {
@@ -1559,7 +2706,46 @@ pub fn call<'ctx>(mut target: Variable<'ctx>, loc: Location, mut args: Vec<(Vari
             target.data_type = *b;
             call(target, loc, args, ctx)
         },
+        Type::Generic => {
+            let data = if let Some(InterData::Generic(data)) = target.inter_val {data} else {
+                return Err(Error::new(loc, 313, "invalid arguments to call of value of type generic".to_string()))
+            };
+            let (ast, mut errs) = if let Some(ast) = data.ast.clone() {(Some(ast), vec![])} else {
+                let (toks, mut errs) = lex(data.code.as_str(), loc.clone(), &ctx.flags);
+                let (ast, mut es) = parser::parse_generic(&toks, &ctx.flags);
+                errs.append(&mut es);
+                (ast.map(std::rc::Rc::from), errs)
+            };
+            let inst = ast.map(|ast| {
+                let old_args = ctx.generic_args.replace(Some(args.iter().map(|(v, _)| v.data_type.clone()).collect()));
+                let old_prefix = ctx.prefix.replace(data.path.clone());
+                let (inst, mut es) = ast.codegen(ctx);
+                errs.append(&mut es);
+                ctx.prefix.replace(old_prefix);
+                ctx.generic_args.replace(old_args);
+                inst
+            });
+            let mut err = Error::new(loc.clone(), 326, format!("cannot instantiate generic function with argument types ({})", args.iter().map(|(v, _)| v.data_type.to_string()).collect::<Vec<_>>().join(", ")));
+            for e in errs.into_iter().filter(|e| e.code >= 100) {err.add_note(Note::new(e.loc, e.message));}
+            match inst {
+                Some(inst) if inst.good.get() && err.notes.len() == 0 => call(inst, loc, args, ctx),
+                _ => Err(err)
+            }
+        },
+        Type::Function(ret, params) if matches!((&*ret, &target.inter_val, target.comp_val), (Type::Enum(..), Some(InterData::Int(_)), None)) => {
+            let tag = if let Some(InterData::Int(tag)) = target.inter_val {tag as usize} else {unreachable!()};
+            let (ptype, _) = params.into_iter().next().unwrap();
+            if args.len() != 1 {
+                return Err(Error::new(loc.clone(), 313, format!("invalid arguments to construction of variant of {}", *ret)).note(Note::new(loc, format!("expected 1 argument, got {}", args.len()))))
+            }
+            let (arg, l) = args.pop().unwrap();
+            let err = format!("expected value of type {ptype}, got {}", arg.data_type);
+            let arg = impl_convert(arg, ptype, ctx).ok_or_else(|| Error::new(loc.clone(), 313, format!("invalid arguments to construction of variant of {}", *ret)).note(Note::new(l, err)))?;
+            Ok(enum_value(*ret, tag, Some(arg), ctx).unwrap_or_else(Variable::error))
+        },
         Type::Function(ret, params) => {
+            call_borrows(&args, params.iter().map(|(t, _)| t), ctx)?;
+            let cfn = if let Some(InterData::Function(FnData {cfn, ..})) = &target.inter_val {cfn.clone()} else {None};
             let mut err = Error::new(loc.clone(), 313, format!("invalid arguments to call of value of type {}", Type::Function(ret.clone(), params.clone()))).note(Note::new(args.get(0).map(|(_, l)| l.clone()).unwrap_or(loc), {
                 let mut out = format!("argument types are (");
                 args.iter().for_each(|(Variable {data_type, ..}, _)| out += format!("{data_type}, ").as_str());
@@ -1601,6 +2787,7 @@ pub fn call<'ctx>(mut target: Variable<'ctx>, loc: Location, mut args: Vec<(Vari
             }).partition::<Vec<_>, _>(|(_, c)| **c);
             if !good {return Err(err)}
             if c.len() > 0 {return Err(Error::new(loc.clone(), 900, "constant function parameters aren't yet supported".to_string()))}
+            if let (Some(cfn), true) = (cfn, ctx.is_const.get()) {return const_call(&cfn, *ret, r.into_iter().map(|(v, _)| v).collect(), loc, ctx)}
             good = true;
             let val: Option<inkwell::values::CallableValue> = if let Some(PointerValue(v)) = target.comp_val {v.try_into().ok()} else {None};
             let args: Vec<inkwell::values::BasicMetadataValueEnum> = r.into_iter().filter_map(|(Variable {comp_val, ..}, _)| comp_val.map(|v| v.into()).or_else(|| {good = false; None})).collect();
@@ -1611,6 +2798,60 @@ pub fn call<'ctx>(mut target: Variable<'ctx>, loc: Location, mut args: Vec<(Vari
                 good: Cell::new(true)
             })
         },
+        Type::Closure(ret, params) => {
+            let mut err = Error::new(loc.clone(), 313, format!("invalid arguments to call of value of type {}", Type::Closure(ret.clone(), params.clone())));
+            if args.len() != params.len() {
+                return Err(err.note(Note::new(loc, format!("expected {} parameters, got {}", params.len(), args.len()))))
+            }
+            call_borrows(&args, params.iter(), ctx)?;
+            let suffixes = ["st", "nd", "rd", "th", "th", "th", "th", "th", "th", "th"];
+            let mut good = true;
+            let args = args.into_iter().zip(params.iter()).enumerate().filter_map(|(n, ((v, l), t))| {
+                let e = format!("expected value of type {t} in {}{} argument, got {}", n + 1, suffixes[n % 10], v.data_type);
+                let val = impl_convert(v, t.clone(), ctx).and_then(|v| v.value(ctx));
+                if val.is_none() {
+                    good = false;
+                    err.add_note(Note::new(l, e));
+                }
+                val
+            }).collect::<Vec<_>>();
+            if !good {return Err(err)}
+            Ok(Variable {
+                comp_val: match target.comp_val {
+                    Some(StructValue(v)) if !ctx.is_const.get() => {
+                        // the environment is passed as a hidden first argument
+                        let f: Option<inkwell::values::CallableValue> = ctx.builder.build_extract_value(v, 0, "").and_then(|f| f.into_pointer_value().try_into().ok());
+                        let env = ctx.builder.build_extract_value(v, 1, "");
+                        f.zip(env).and_then(|(f, env)| {
+                            let args = std::iter::once(env).chain(args).map(inkwell::values::BasicMetadataValueEnum::from).collect::<Vec<_>>();
+                            ctx.builder.build_call(f, &args, "").try_as_basic_value().left()
+                        })
+                    },
+                    _ => None
+                },
+                inter_val: None,
+                data_type: *ret,
+                good: Cell::new(true)
+            })
+        },
+        Type::Coroutine(t) => {
+            if let Some((_, l)) = args.first() {
+                return Err(Error::new(loc.clone(), 313, format!("invalid arguments to call of value of type {}", Type::Coroutine(t))).note(Note::new(l.clone(), "coroutines are resumed without arguments".to_string())))
+            }
+            Ok(Variable {
+                comp_val: match target.comp_val {
+                    Some(PointerValue(v)) if !ctx.is_const.get() => {
+                        let f = intrinsic("llvm.coro.resume", ctx.context.void_type().fn_type(&[v.get_type().into()], false), ctx);
+                        ctx.builder.build_call(f, &[v.into()], "");
+                        coro_promise(v, &t, ctx).map(|p| ctx.builder.build_load(p, ""))
+                    },
+                    _ => None
+                },
+                inter_val: None,
+                data_type: *t,
+                good: Cell::new(true)
+            })
+        },
         t => Err(Error::new(loc.clone(), 313, format!("invalid arguments to call of value of type {t}")).note(Note::new(args.get(0).map(|(_, l)| l.clone()).unwrap_or(loc), {
             let mut out = format!("argument types are (");
             args.iter().for_each(|(Variable {data_type, ..}, _)| out += format!("{data_type}, ").as_str());
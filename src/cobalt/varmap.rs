@@ -15,7 +15,22 @@ pub enum RedefVariable<'ctx> {
 }
 #[derive(Clone)]
 pub struct FnData {
-    pub defaults: Vec<InterData>
+    pub defaults: Vec<InterData>,
+    pub cfn: Option<ConstFnData>
+}
+#[derive(Clone)]
+pub struct ConstFnData {
+    pub path: Vec<String>,
+    pub params: Vec<String>,
+    pub body: String, // saved with the module, and parsed again when called from a library
+    pub ast: Option<std::rc::Rc<dyn AST>> // the body, if it was parsed in this compilation
+}
+#[derive(Clone)]
+pub struct GenericData {
+    pub path: Vec<String>,
+    pub params: Vec<String>,
+    pub code: String, // saved with the module, and parsed again when instantiated from a library
+    pub ast: Option<std::rc::Rc<dyn AST>> // the definition, if it was parsed in this compilation
 }
 #[derive(Clone)]
 pub enum InterData {
@@ -24,7 +39,9 @@ pub enum InterData {
     Float(f64),
     Str(String),
     Array(Vec<InterData>),
-    Function(FnData)
+    Function(FnData),
+    Type(Box<Type>),
+    Generic(GenericData)
 }
 impl InterData {
     pub fn into_compiled<'ctx>(&self, ctx: &CompCtx<'ctx>) -> Option<BasicValueEnum<'ctx>> {
@@ -55,7 +72,41 @@ impl InterData {
                 out.write_all(&[6])?;
                 out.write_all(&(v.defaults.len() as u64).to_be_bytes())?;
                 for val in v.defaults.iter() {val.save(out)?;}
-                Ok(())
+                if let Some(cfn) = v.cfn.as_ref() { // body for compile-time evaluation, saved as source code
+                    out.write_all(&[1])?;
+                    out.write_all(&(cfn.path.len() as u64).to_be_bytes())?;
+                    for id in cfn.path.iter() {
+                        out.write_all(id.as_bytes())?;
+                        out.write_all(&[0])?;
+                    }
+                    out.write_all(&(cfn.params.len() as u64).to_be_bytes())?;
+                    for id in cfn.params.iter() {
+                        out.write_all(id.as_bytes())?;
+                        out.write_all(&[0])?;
+                    }
+                    out.write_all(cfn.body.as_bytes())?;
+                    out.write_all(&[0])
+                }
+                else {out.write_all(&[0])}
+            },
+            InterData::Type(t) => {
+                out.write_all(&[7])?;
+                t.save(out)
+            },
+            InterData::Generic(v) => { // the definition is saved as source code and re-parsed on instantiation
+                out.write_all(&[8])?;
+                out.write_all(&(v.path.len() as u64).to_be_bytes())?;
+                for id in v.path.iter() {
+                    out.write_all(id.as_bytes())?;
+                    out.write_all(&[0])?;
+                }
+                out.write_all(&(v.params.len() as u64).to_be_bytes())?;
+                for id in v.params.iter() {
+                    out.write_all(id.as_bytes())?;
+                    out.write_all(&[0])?;
+                }
+                out.write_all(v.code.as_bytes())?;
+                out.write_all(&[0])
             }
         }
     }
@@ -94,12 +145,44 @@ impl InterData {
                 let len = u64::from_be_bytes(bytes);
                 let mut vec = Vec::with_capacity(len as usize);
                 for _ in 0..len {vec.push(Self::load(buf)?.expect("# of unwrapped default parameters doesn't match the prefixed count"))}
-                Some(InterData::Function(FnData{defaults: vec}))
+                buf.read_exact(std::slice::from_mut(&mut c))?;
+                let cfn = if c == 0 {None} else {
+                    let [path, params] = load_names(buf)?;
+                    let mut body = vec![];
+                    buf.read_until(0, &mut body)?;
+                    if body.last() == Some(&0) {body.pop();}
+                    Some(ConstFnData {path, params, body: String::from_utf8(body).expect("Function bodies should be valid UTF-8"), ast: None})
+                };
+                Some(InterData::Function(FnData {defaults: vec, cfn}))
             },
-            x => panic!("read interpreted data type expecting number in 1..=6, got {x}")
+            7 => Some(InterData::Type(Box::new(Type::load(buf)?))),
+            8 => {
+                let [path, params] = load_names(buf)?;
+                let mut code = vec![];
+                buf.read_until(0, &mut code)?;
+                if code.last() == Some(&0) {code.pop();}
+                Some(InterData::Generic(GenericData {path, params, code: String::from_utf8(code).expect("Generic function definitions should be valid UTF-8"), ast: None}))
+            },
+            x => panic!("read interpreted data type expecting number in 1..=8, got {x}")
         })
     }
 }
+// two counted lists of null-terminated names, used by generic and compile-time function data
+fn load_names<R: Read + BufRead>(buf: &mut R) -> io::Result<[Vec<String>; 2]> {
+    let mut lists = [vec![], vec![]];
+    for list in lists.iter_mut() {
+        let mut bytes = [0; 8];
+        buf.read_exact(&mut bytes)?;
+        let len = u64::from_be_bytes(bytes);
+        for _ in 0..len {
+            let mut id = vec![];
+            buf.read_until(0, &mut id)?;
+            if id.last() == Some(&0) {id.pop();}
+            list.push(String::from_utf8(id).expect("Cobalt symbols should be valid UTF-8"));
+        }
+    }
+    Ok(lists)
+}
 #[derive(Clone)]
 pub struct Variable<'ctx> {
     pub comp_val: Option<BasicValueEnum<'ctx>>,
@@ -114,6 +197,7 @@ impl<'ctx> Variable<'ctx> {
     pub fn metaval(inter_val: InterData, data_type: Type) -> Self {Variable {comp_val: None, inter_val: Some(inter_val), data_type, good: Cell::new(true)}}
     pub fn value(&self, ctx: &CompCtx<'ctx>) -> Option<BasicValueEnum<'ctx>> {self.comp_val.clone().or_else(|| self.inter_val.as_ref().and_then(|v| v.into_compiled(ctx)))}
 }
+#[derive(Clone)]
 pub enum Symbol<'ctx> {
     Variable(Variable<'ctx>),
     Module(HashMap<String, Symbol<'ctx>>)
@@ -181,7 +265,7 @@ impl<'ctx> Symbol<'ctx> {
                             }
                         }
                     }
-                    else if let Some(t) = var.data_type.llvm_type(ctx) {
+                    else if let Some(t) = if let Type::Reference(b, _) = &var.data_type {b.llvm_type(ctx)} else {var.data_type.llvm_type(ctx)} {
                         let gv = ctx.module.add_global(t, None, std::str::from_utf8(&name).expect("LLVM variable names should be valid UTF-8")); // maybe do something with linkage/call convention?
                         var.comp_val = Some(BasicValueEnum::PointerValue(gv.as_pointer_value()));
                     }
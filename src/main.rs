@@ -520,6 +520,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             if overall_fail {exit(101)}
             let pm = inkwell::passes::PassManager::create(());
+            opt::add_coroutine_passes(&ctx.module, &pm);
             opt::load_profile(profile, &pm);
             pm.run_on(&ctx.module);
             match output_type {
@@ -754,6 +755,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             if overall_fail {exit(101)}
             let pm = inkwell::passes::PassManager::create(());
+            opt::add_coroutine_passes(&ctx.module, &pm);
             opt::load_profile(profile, &pm);
             pm.run_on(&ctx.module);
             let jit = jit::LLJIT::new();
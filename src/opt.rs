@@ -147,6 +147,11 @@ pub fn from_file(data: &str, pm: &PassManager<Module>) {
         }
     }
 }
+pub fn add_coroutine_passes(module: &Module, pm: &PassManager<Module>) {
+    if module.get_function("llvm.coro.id").is_some() {
+        add_pass(pm, "coroutines", Null);
+    }
+}
 pub fn load_profile(name: Option<&str>, pm: &PassManager<Module>) {
     let name = name.unwrap_or("default");
     if let Ok(cobalt_dir) = var("COBALT_DIR") {
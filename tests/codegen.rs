@@ -0,0 +1,626 @@
+use cobalt::*;
+use inkwell::OptimizationLevel;
+use inkwell::context::Context;
+use inkwell::passes::PassManager;
+use inkwell::targets::{Target, InitializationConfig};
+fn codegen<'ctx>(src: &'static str, ctx: &CompCtx<'ctx>) -> Vec<u64> {
+    let flags = Flags::default();
+    let (toks, mut errs) = parser::lexer::lex(src, Location::from_name("<test>"), &flags);
+    let (ast, mut es) = parser::ast::parse(toks.as_slice(), &flags);
+    errs.append(&mut es);
+    let (_, mut es) = ast.codegen(ctx);
+    errs.append(&mut es);
+    errs.into_iter().map(|e| e.code).collect()
+}
+fn build<'ctx>(src: &'static str, ink_ctx: &'ctx Context) -> (CompCtx<'ctx>, Vec<u64>) {
+    let ctx = CompCtx::new(ink_ctx, "<test>");
+    let codes = codegen(src, &ctx);
+    (ctx, codes)
+}
+fn compile(src: &'static str) -> (Vec<u64>, bool) {
+    let ink_ctx = Context::create();
+    let (ctx, codes) = build(src, &ink_ctx);
+    (codes, ctx.module.verify().is_ok())
+}
+fn assert_clean(src: &'static str) {
+    let (codes, ok) = compile(src);
+    assert!(codes.iter().all(|&c| c < 100), "unexpected errors {codes:?} in:\n{src}");
+    assert!(ok, "module failed to verify for:\n{src}");
+}
+fn assert_error(src: &'static str, code: u64) {
+    let (codes, _) = compile(src);
+    assert!(codes.contains(&code), "expected error {code}, got {codes:?} in:\n{src}");
+}
+fn ir(src: &'static str) -> String {
+    let ink_ctx = Context::create();
+    let (ctx, codes) = build(src, &ink_ctx);
+    assert!(codes.iter().all(|&c| c < 100), "unexpected errors {codes:?} in:\n{src}");
+    ctx.module.print_to_string().to_string()
+}
+// compile src, then run the C-convention function name with arg under the JIT
+fn call(src: &'static str, name: &str, arg: i32) -> i32 {
+    let ink_ctx = Context::create();
+    let (ctx, codes) = build(src, &ink_ctx);
+    assert!(codes.iter().all(|&c| c < 100), "unexpected errors {codes:?} in:\n{src}");
+    assert!(ctx.module.verify().is_ok(), "module failed to verify for:\n{src}");
+    if ctx.module.get_function("llvm.coro.id").is_some() {
+        let pm = PassManager::create(());
+        pm.add_coroutine_early_pass();
+        pm.add_coroutine_split_pass();
+        pm.add_coroutine_elide_pass();
+        pm.add_coroutine_cleanup_pass();
+        pm.run_on(&ctx.module);
+    }
+    Target::initialize_native(&InitializationConfig::default()).unwrap();
+    let ee = ctx.module.create_jit_execution_engine(OptimizationLevel::None).unwrap();
+    unsafe {ee.get_function::<unsafe extern "C" fn(i32) -> i32>(name).unwrap().call(arg)}
+}
+
+// if/else
+#[test]
+fn if_else_value() {
+    let src = "@cconv(c) fn f(x: i32): i32 = if (x > 0) 1 else 2;";
+    assert_eq!(call(src, "f", 5), 1);
+    assert_eq!(call(src, "f", -5), 2);
+}
+#[test]
+fn nested_if_else() {
+    let src = "@cconv(c) fn f(x: i32): i32 = if (x > 10) 3 else if (x > 0) 2 else 1;";
+    assert_eq!(call(src, "f", 11), 3);
+    assert_eq!(call(src, "f", 4), 2);
+    assert_eq!(call(src, "f", 0), 1);
+}
+#[test]
+fn if_without_else_in_block() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = {mut y: i32 = 1; if (x > 0) y = x; y};", "f", 7), 7);
+}
+#[test]
+fn if_branches_with_different_types() {
+    assert_error("struct S {a: i32}; fn f(x: i32, s: S): i32 = if (x > 0) 1 else s;", 311);
+}
+
+// while
+#[test]
+fn while_loop_sums() {
+    assert_eq!(call("@cconv(c) fn f(n: i32): i32 = {mut i: i32 = 0; mut s: i32 = 0; while (i < n) {i += 1; s += i}; s};", "f", 10), 55);
+}
+#[test]
+fn while_break_and_continue() {
+    let src = "@cconv(c) fn f(n: i32): i32 = {mut i: i32 = 0; mut s: i32 = 0; while (true) {i += 1; if (i > n) break; if (i % 2 == 0) continue; s += i}; s};";
+    assert_eq!(call(src, "f", 5), 9);
+}
+#[test]
+fn labeled_break_leaves_outer_loop() {
+    let src = "@cconv(c) fn f(n: i32): i32 = {mut i: i32 = 0; mut c: i32 = 0; outer: while (i < n) {mut j: i32 = 0; while (j < n) {if (i * j == 6) break outer; c += 1; j += 1}; i += 1}; c};";
+    assert_eq!(call(src, "f", 4), 11);
+}
+#[test]
+fn break_outside_loop() {
+    assert_error("fn f(): null = break;", 330);
+}
+#[test]
+fn break_to_unknown_label() {
+    assert_error("fn f(): null = while (true) break outer;", 331);
+}
+
+// short-circuit operators
+#[test]
+fn and_skips_rhs_when_false() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut c: i32 = 0; let b = x > 0 && {c += 1; true}; c};";
+    assert_eq!(call(src, "f", -1), 0);
+    assert_eq!(call(src, "f", 1), 1);
+}
+#[test]
+fn or_skips_rhs_when_true() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut c: i32 = 0; let b = x > 0 || {c += 1; false}; c};";
+    assert_eq!(call(src, "f", 1), 0);
+    assert_eq!(call(src, "f", -1), 1);
+}
+#[test]
+fn and_or_values() {
+    let src = "@cconv(c) fn f(x: i32): i32 = if ((x > 0 && x < 10) || x == -5) 1 else 0;";
+    assert_eq!(call(src, "f", 5), 1);
+    assert_eq!(call(src, "f", 15), 0);
+    assert_eq!(call(src, "f", -5), 1);
+}
+
+// arrays
+#[test]
+fn array_indexing() {
+    let src = "@cconv(c) fn f(i: i32): i32 = {let a = [10, 20, 30]; a[i]: i32};";
+    assert_eq!(call(src, "f", 0), 10);
+    assert_eq!(call(src, "f", 2), 30);
+}
+#[test]
+fn array_element_assignment() {
+    let src = "@cconv(c) fn f(i: i32): i32 = {mut a = [1, 2]; a[i] = 5; (a[0] + a[1]): i32};";
+    assert_eq!(call(src, "f", 0), 7);
+    assert_eq!(call(src, "f", 1), 6);
+}
+#[test]
+fn constant_index_out_of_bounds() {
+    assert_error("fn f(): i64 = {let a = [1, 2, 3]; a[3]};", 314);
+}
+#[test]
+fn runtime_index_is_bounds_checked() {
+    assert!(ir("fn f(i: i32): i64 = {let a = [1, 2, 3]; a[i]};").contains("@llvm.trap"));
+}
+#[test]
+fn unsized_array_in_struct() {
+    assert_clean("struct S {a: i32[], b: i8}; fn f(s: S): i8 = s.b;");
+}
+#[test]
+fn imported_array_global() {
+    let lib_ctx = Context::create();
+    let (lib, codes) = build("let a = [1, 2, 3];", &lib_ctx);
+    assert!(codes.iter().all(|&c| c < 100), "unexpected errors {codes:?} in library");
+    let mut syms = vec![];
+    lib.with_vars(|v| v.save(&mut syms)).unwrap();
+    let ink_ctx = Context::create();
+    let ctx = CompCtx::new(&ink_ctx, "<test>");
+    ctx.with_vars(|v| v.load(&mut syms.as_slice(), &ctx)).unwrap();
+    let codes = codegen("fn f(i: i32): i64 = a[i];", &ctx);
+    assert!(codes.iter().all(|&c| c < 100), "unexpected errors {codes:?}");
+    assert!(ctx.module.verify().is_ok());
+    assert!(ctx.module.print_to_string().to_string().contains("@a = external global [3 x i64]"));
+}
+
+// modules
+#[test]
+fn module_member_by_dotted_path() {
+    let src = "module m {fn g(x: i32): i32 = x + 1;}; @cconv(c) fn f(x: i32): i32 = m.g(x);";
+    assert_eq!(call(src, "f", 1), 2);
+    assert!(ir(src).contains("@m.g("));
+}
+#[test]
+fn nested_module_names_are_mangled() {
+    assert!(ir("module a {module b {fn g(): i32 = 1;};}; fn f(): i32 = a.b.g();").contains("@a.b.g("));
+}
+#[test]
+fn module_member_redefinition() {
+    assert_error("module m {fn g(): i32 = 1; fn g(): i32 = 2;};", 321);
+}
+
+// imports
+#[test]
+fn glob_import() {
+    let src = "module m {fn g(x: i32): i32 = x * 2; fn h(x: i32): i32 = x + 1;}; import m.*; @cconv(c) fn f(x: i32): i32 = g(h(x));";
+    assert_eq!(call(src, "f", 2), 6);
+}
+#[test]
+fn group_import() {
+    let src = "module m {fn g(x: i32): i32 = x * 2; fn h(x: i32): i32 = x + 1;}; import m.{g, h}; @cconv(c) fn f(x: i32): i32 = h(g(x));";
+    assert_eq!(call(src, "f", 2), 5);
+}
+#[test]
+fn import_conflict() {
+    assert_error("module m {fn g(): i32 = 1;}; fn g(): i32 = 2; import m.*;", 321);
+}
+#[test]
+fn block_import_ends_with_block() {
+    assert_clean("module m {fn g(): i32 = 1;}; fn f(): i32 = {import m.*; g()};");
+    assert_error("module m {fn g(): i32 = 1;}; fn f(): i32 = {import m.*; g()}; fn k(): i32 = g();", 321);
+}
+
+// inline assembly
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn asm_with_operands() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut y: i32 = 0; @asm(att \"movl $1, $0\"; out \"=r\" y; in \"r\" x); y};";
+    assert_eq!(call(src, "f", 42), 42);
+}
+#[test]
+fn asm_with_clobbers() {
+    assert!(ir("fn f(): null = @asm(\"nop\"; clobber \"memory\");").contains("~{memory}"));
+}
+#[test]
+fn asm_outside_function() {
+    assert_error("let x = @asm(\"nop\");", 312);
+}
+#[test]
+fn asm_unknown_dialect() {
+    assert_error("fn f(): null = @asm(arm \"nop\");", 392);
+}
+
+// structs
+#[test]
+fn struct_literal_and_field_access() {
+    let src = "struct P {x: i32, y: i32}; @cconv(c) fn f(x: i32): i32 = {let p = P {x: x, y: 3}; p.x * p.y};";
+    assert_eq!(call(src, "f", 4), 12);
+}
+#[test]
+fn struct_field_assignment() {
+    let src = "struct P {x: i32, y: i8}; @cconv(c) fn f(x: i32): i32 = {mut p = P {x: 1, y: 2}; p.x = x; p.x + 1};";
+    assert_eq!(call(src, "f", 4), 5);
+}
+#[test]
+fn struct_passed_to_function() {
+    let src = "struct P {x: i32, y: i32}; fn sum(p: P): i32 = p.x + p.y; @cconv(c) fn f(x: i32): i32 = sum(P {y: x, x: 2});";
+    assert_eq!(call(src, "f", 4), 6);
+}
+#[test]
+fn struct_literal_missing_field() {
+    assert_error("struct P {x: i32, y: i32}; fn f(): P = P {x: 1};", 345);
+}
+#[test]
+fn struct_literal_unknown_field() {
+    assert_error("struct P {x: i32}; fn f(): P = P {x: 1, z: 2};", 343);
+}
+
+// float remainder and power
+#[test]
+fn float_remainder() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = {mut v: f64 = 10.0; if (v % 3.5 == 3.0) x else 0};", "f", 1), 1);
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = {mut v: f32 = 7.5; v %= 2.0; if (v == 1.5) x else 0};", "f", 1), 1);
+}
+#[test]
+fn float_power() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = {mut v: f64 = 2.0; if (v ^^ 10.0 == 1024.0) x else 0};", "f", 1), 1);
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = {mut v: f64 = 3.0; v ^^= 2.0; if (v == 9.0) x else 0};", "f", 1), 1);
+}
+#[test]
+fn integer_power() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = x ^^ 3;", "f", 3), 27);
+}
+#[test]
+fn constant_power_is_folded() {
+    assert!(!ir("fn f(): i64 = {const c = 2 ^^ 10; c};").contains("llvm.pow"));
+}
+
+// casts
+#[test]
+fn truncating_and_extending_casts() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = (x: i8): i32;", "f", 300), 44);
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = (x: i8): i32;", "f", -1), -1);
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = (x: u8): i32;", "f", -1), 255);
+}
+#[test]
+fn int_float_casts() {
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = ((x: f64) / 2.0): i32;", "f", 7), 3);
+    assert_eq!(call("@cconv(c) fn f(x: i32): i32 = ((x: f32): f64): i32;", "f", -9), -9);
+}
+#[test]
+fn pointer_casts() {
+    assert_clean("fn f(p: i32*): i8* = p: i8*; fn g(p: i32*): u64 = p: u64; fn h(x: u64): i32* = x: i32*;");
+}
+#[test]
+fn invalid_cast() {
+    assert_error("struct S {a: i32}; fn f(s: S): i32 = s: i32;", 311);
+}
+
+// coroutines
+#[test]
+fn coroutine_resumes_through_yields() {
+    let src = "cr fn g(x: i32): i32 = {yield x; yield x + 1; x + 2}; @cconv(c) fn f(x: i32): i32 = {let h = g(x); let a = h(); let b = h(); a * 10 + b};";
+    assert_eq!(call(src, "f", 1), 12);
+}
+#[test]
+fn coroutine_done_after_return() {
+    let src = "cr fn g(x: i32): i32 = {yield x; x + 2}; @cconv(c) fn f(x: i32): i32 = {let h = g(x); h(); let c = h(); if (h.done) c else -1};";
+    assert_eq!(call(src, "f", 1), 3);
+}
+#[test]
+fn coroutine_destroyed_twice() {
+    let (codes, _) = compile("cr fn g(): i32 = 1; fn f(): null = {let h = g(); @destroy(h); @destroy(h)};");
+    assert!(codes.contains(&369) || codes.contains(&397), "expected the second @destroy to be rejected, got {codes:?}");
+}
+#[test]
+fn destroy_non_coroutine() {
+    assert_error("fn f(): null = {let x = 1; @destroy(x)};", 397);
+}
+#[test]
+fn yield_outside_coroutine() {
+    assert_error("fn f(): i32 = {yield 1; 2};", 350);
+}
+
+// reachability
+#[test]
+fn if_returning_on_both_branches() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {if (x > 0) return 1 else return 2};";
+    assert_eq!(call(src, "f", 1), 1);
+    assert_eq!(call(src, "f", 0), 2);
+}
+#[test]
+fn code_after_return_is_unreachable() {
+    let (codes, ok) = compile("fn f(): i32 = {return 1; 2};");
+    assert!(codes.contains(&24), "expected warning 24, got {codes:?}");
+    assert!(ok);
+}
+#[test]
+fn code_after_break_is_unreachable() {
+    let (codes, ok) = compile("fn f(): null = while (true) {break; 1};");
+    assert!(codes.contains(&24), "expected warning 24, got {codes:?}");
+    assert!(ok);
+}
+#[test]
+fn missing_return() {
+    assert_error("fn f(x: i32): i32 = {if (x > 0) return 1;};", 352);
+}
+
+// for loops
+#[test]
+fn for_over_range() {
+    let src = "@cconv(c) fn f(n: i32): i32 = {mut s: i32 = 0; for i in 0..n {s += i}; s};";
+    assert_eq!(call(src, "f", 5), 10);
+    assert_eq!(call(src, "f", 0), 0);
+}
+#[test]
+fn for_over_array() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut s: i32 = x; for v in [1, 2, 3] {s += v: i32}; s};";
+    assert_eq!(call(src, "f", 4), 10);
+}
+#[test]
+fn labeled_for_with_continue() {
+    let src = "@cconv(c) fn f(n: i32): i32 = {mut s: i32 = 0; outer: for i in 0..n {for j in 0..n {if (j > i) continue outer; s += 1}}; s};";
+    assert_eq!(call(src, "f", 3), 6);
+}
+#[test]
+fn range_outside_for() {
+    let (codes, _) = compile("fn f(): null = {let r = 0..3;};");
+    assert!(codes.iter().any(|&c| c >= 100), "expected an error for a range outside of a for loop, got {codes:?}");
+}
+
+// match
+#[test]
+fn match_dispatches_on_variant() {
+    let src = "enum E {A, B(i32)}; fn pick(x: i32): E = if (x > 0) E.B(x) else E.A; @cconv(c) fn f(x: i32): i32 = match pick(x) {A => -1, B(y) => y * 2};";
+    assert_eq!(call(src, "f", 4), 8);
+    assert_eq!(call(src, "f", 0), -1);
+}
+#[test]
+fn match_arms_are_tried_in_order() {
+    let first = "enum E {A, B(i32)}; fn pick(x: i32): E = if (x > 0) E.B(x) else E.A; @cconv(c) fn f(x: i32): i32 = match pick(x) {_ => 0, A => 1, B(y) => y};";
+    assert_eq!(call(first, "f", 4), 0);
+    assert_eq!(call(first, "f", 0), 0);
+    let last = "enum E {A, B(i32)}; fn pick(x: i32): E = if (x > 0) E.B(x) else E.A; @cconv(c) fn f(x: i32): i32 = match pick(x) {A => 1, B(y) => y, _ => 0};";
+    assert_eq!(call(last, "f", 4), 4);
+    assert_eq!(call(last, "f", 0), 1);
+}
+#[test]
+fn match_duplicate_arm_uses_first() {
+    let src = "enum E {A, B(i32)}; fn pick(x: i32): E = if (x > 0) E.B(x) else E.A; @cconv(c) fn f(x: i32): i32 = match pick(x) {B(y) => y, B(z) => z + 100, _ => 0};";
+    assert_eq!(call(src, "f", 4), 4);
+    assert!(compile(src).0.contains(&25));
+}
+#[test]
+fn non_exhaustive_match() {
+    assert_error("enum E {A, B(i32)}; fn f(e: E): i32 = match e {A => 1};", 349);
+}
+
+// generics
+#[test]
+fn generic_function_call() {
+    let src = "fn twice[T](x: T): T = x + x; @cconv(c) fn f(x: i32): i32 = twice(x);";
+    assert_eq!(call(src, "f", 21), 42);
+}
+#[test]
+fn generic_instances_are_cached_per_type() {
+    let code = ir("fn id[T](x: T): T = x; fn f(x: i32, y: i32, z: i64): i64 = (id(x) + id(y)): i64 + id(z);");
+    assert_eq!(code.lines().filter(|l| l.starts_with("define") && l.contains("linkonce_odr")).count(), 2);
+}
+
+// compile-time evaluation
+#[test]
+fn constant_from_function_call() {
+    let src = "fn sq(x: i64): i64 = x * x; const n = sq(3); @cconv(c) fn f(x: i32): i32 = n: i32 + x;";
+    assert_eq!(call(src, "f", 1), 10);
+}
+#[test]
+fn recursive_constant_function() {
+    let src = "fn fact(x: i64): i64 = if (x <= 1) 1 else x * fact(x - 1); const n = fact(5); @cconv(c) fn f(x: i32): i32 = n: i32 - x;";
+    assert_eq!(call(src, "f", 20), 100);
+}
+#[test]
+fn array_size_from_function_call() {
+    assert_clean("fn three(): i64 = 1 + 2; fn f(a: i32[three()]): i32 = a[2];");
+}
+#[test]
+fn constant_recursion_limit() {
+    assert_error("fn inf(x: i64): i64 = inf(x + 1); const n = inf(0);", 357);
+}
+#[test]
+fn constant_step_limit() {
+    assert_error("fn fib(x: i64): i64 = if (x < 2) x else fib(x - 1) + fib(x - 2); const n = fib(40);", 358);
+}
+
+// bool
+#[test]
+fn bool_literals_and_not() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {let b = x > 0; if (!b) 1 else if (b == true) 2 else 3};";
+    assert_eq!(call(src, "f", 0), 1);
+    assert_eq!(call(src, "f", 5), 2);
+}
+#[test]
+fn bool_to_int_cast() {
+    let src = "@cconv(c) fn f(x: i32): i32 = (x > 0): i32;";
+    assert_eq!(call(src, "f", 5), 1);
+    assert_eq!(call(src, "f", -5), 0);
+}
+#[test]
+fn bool_lowers_to_i1() {
+    assert!(ir("fn f(b: bool): bool = !b;").contains("i1"));
+}
+#[test]
+fn bool_arithmetic() {
+    assert_error("fn f(): bool = true + false;", 310);
+}
+
+// closures
+#[test]
+fn closure_captures_by_value() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {let y = x * 2; let g = fn (z: i32): i32 = z + y; g(1)};";
+    assert_eq!(call(src, "f", 3), 7);
+}
+#[test]
+fn closure_captures_mutable_by_reference() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut c: i32 = x; let inc = fn (): null = c += 1; inc(); inc(); c};";
+    assert_eq!(call(src, "f", 3), 5);
+}
+#[test]
+fn closure_outlives_captured_reference() {
+    assert_error("fn f(): null = {let g = {mut c: i32 = 0; fn (): i32 = c}; g();};", 371);
+}
+#[test]
+fn closure_drops_its_parameters() {
+    let code = ir("struct S {a: i32}; fn S.drop(s: S mut &): null = null; fn f(): i32 = {let g = fn (s: S): i32 = s.a; g(S {a: 1})};");
+    assert!(code.lines().any(|l| l.contains("call") && l.contains("@S.drop(")));
+}
+
+// methods
+#[test]
+fn method_call_with_receiver() {
+    let src = "struct P {x: i32}; fn P.get(self: P&): i32 = self.x; fn P.add(self: P&, y: i32): i32 = self.x + y; @cconv(c) fn f(x: i32): i32 = {let p = P {x: x}; p.get() + p.add(1)};";
+    assert_eq!(call(src, "f", 2), 5);
+}
+#[test]
+fn method_with_mutable_receiver() {
+    let src = "struct P {x: i32}; fn P.set(self: P mut &, v: i32): null = self.x = v; @cconv(c) fn f(x: i32): i32 = {mut p = P {x: 0}; p.set(x); p.x};";
+    assert_eq!(call(src, "f", 9), 9);
+}
+#[test]
+fn void_method_uses_self() {
+    assert_clean("struct P {x: i32}; fn P.check(self: P&): null = {let y = self.x;}; fn f(p: P): null = p.check();");
+}
+#[test]
+fn mutable_method_on_immutable_value() {
+    let (codes, _) = compile("struct P {x: i32}; fn P.set(self: P mut &, v: i32): null = self.x = v; fn f(): null = {let p = P {x: 0}; p.set(1)};");
+    assert!(codes.iter().any(|&c| c >= 100), "expected an error for a mutable method on an immutable value, got {codes:?}");
+}
+#[test]
+fn unknown_method() {
+    assert_error("struct P {x: i32}; fn P.get(self: P&): i32 = self.x; fn f(p: P): i32 = p.gte();", 361);
+}
+
+// operator overloading
+#[test]
+fn overloaded_binary_operator() {
+    let src = "struct V {x: i32}; @op(+) fn vadd(a: V, b: V): V = V {x: a.x + b.x}; @cconv(c) fn f(x: i32): i32 = (V {x: x} + V {x: 2}).x;";
+    assert_eq!(call(src, "f", 3), 5);
+}
+#[test]
+fn overloaded_operator_with_scalar_rhs() {
+    let src = "struct V {x: i32}; @op(*) fn vmul(a: V, k: i32): V = V {x: a.x * k}; @cconv(c) fn f(x: i32): i32 = (V {x: x} * 3).x;";
+    assert_eq!(call(src, "f", 3), 9);
+}
+#[test]
+fn overloaded_prefix_operator() {
+    let src = "struct V {x: i32}; @op(-) fn vneg(a: V): V = V {x: 0 - a.x}; @cconv(c) fn f(x: i32): i32 = (-V {x: x}).x;";
+    assert_eq!(call(src, "f", 3), -3);
+}
+#[test]
+fn op_annotation_errors() {
+    assert_error("struct V {x: i32}; @op fn g(a: V, b: V): V = a;", 423);
+    assert_error("struct V {x: i32}; @op(+) @op(-) fn g(a: V, b: V): V = a;", 424);
+    assert_error("struct V {x: i32}; @op(&&) fn g(a: V, b: V): V = a;", 425);
+    assert_error("struct V {x: i32}; @op(+) fn g(a: V, b: V, c: V): V = a;", 426);
+}
+
+// traits
+#[test]
+fn dynamic_dispatch_through_trait_object() {
+    let src = "trait Show {fn show(self): i32;}; struct S {a: i32}; struct T {b: i32}; impl Show for S {fn show(self: S&): i32 = self.a * 2;}; impl Show for T {fn show(self: T&): i32 = self.b + 1;}; fn get(o: Show): i32 = o.show(); @cconv(c) fn f(x: i32): i32 = {let s = S {a: x}; let t = T {b: x}; get(s) * 100 + get(t)};";
+    assert_eq!(call(src, "f", 3), 604);
+}
+#[test]
+fn bound_method_is_not_dropped() {
+    let src = "trait Show {fn show(self): i32;}; struct S {a: i32}; impl Show for S {fn show(self: S&): i32 = self.a;}; @cconv(c) fn f(x: i32): i32 = {let s = S {a: x}; let o: Show = s; let m = o.show; m() + m()};";
+    assert_eq!(call(src, "f", 3), 6);
+}
+#[test]
+fn generic_bound_not_satisfied() {
+    assert_error("trait Show {fn show(self): i32;}; fn get[T: Show](x: T): i32 = 0; fn f(): i32 = get(1);", 363);
+}
+#[test]
+fn missing_trait_method() {
+    assert_error("trait Show {fn show(self): i32;}; struct S {a: i32}; impl Show for S {};", 363);
+}
+#[test]
+fn trait_object_outlives_data() {
+    assert_error("trait Show {fn show(self): i32;}; struct S {a: i32}; impl Show for S {fn show(self: S&): i32 = self.a;}; fn g(): Show = {let s = S {a: 1}; let o: Show = s; o};", 371);
+    assert_error("trait Show {fn show(self): i32;}; struct S {a: i32}; impl Show for S {fn show(self: S&): i32 = self.a;}; fn g(): i32 = {let o: Show = {let s = S {a: 1}; let t: Show = s; t}; o.show()};", 371);
+}
+
+// destructors
+#[test]
+fn destructor_runs_at_scope_exit() {
+    let src = "mut count: i32 = 0; struct S {a: i32}; fn S.drop(s: S mut &): null = count += s.a; @cconv(c) fn f(x: i32): i32 = {{let s = S {a: x};}; count};";
+    assert_eq!(call(src, "f", 3), 3);
+}
+#[test]
+fn destructor_runs_on_early_return() {
+    let src = "mut count: i32 = 0; struct S {a: i32}; fn S.drop(s: S mut &): null = count += 1; fn g(x: i32): i32 = {let s = S {a: x}; if (x > 0) return 1; 2}; @cconv(c) fn f(x: i32): i32 = {g(x); g(0 - x); count};";
+    assert_eq!(call(src, "f", 3), 2);
+}
+#[test]
+fn moved_value_is_dropped_once() {
+    let src = "mut count: i32 = 0; struct S {a: i32}; fn S.drop(s: S mut &): null = count += 1; @cconv(c) fn f(x: i32): i32 = {{let s = S {a: x}; let t = s;}; count};";
+    assert_eq!(call(src, "f", 3), 1);
+}
+#[test]
+fn use_after_move() {
+    assert_error("struct S {a: i32}; fn S.drop(s: S mut &): null = null; fn f(s: S): i32 = {let t = s; s.a};", 369);
+}
+#[test]
+fn move_inside_loop() {
+    assert_error("struct S {a: i32}; fn S.drop(s: S mut &): null = null; fn g(s: S): null = null; fn f(s: S): null = {mut i: i32 = 0; while (i < 2) {g(s); i += 1}};", 373);
+}
+
+// defer
+#[test]
+fn defers_run_in_reverse_order() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut r: i32 = x; {defer r = r * 10 + 1; defer r = r * 10 + 2;}; r};";
+    assert_eq!(call(src, "f", 0), 21);
+}
+#[test]
+fn defer_runs_on_return() {
+    let src = "mut g: i32 = 0; fn h(x: i32): i32 = {defer g = x; return 1}; @cconv(c) fn f(x: i32): i32 = {let r = h(x); r + g};";
+    assert_eq!(call(src, "f", 5), 6);
+}
+#[test]
+fn defer_runs_on_break() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut i: i32 = 0; mut c: i32 = 0; while (true) {defer c += 1; i += 1; if (i == x) break}; c};";
+    assert_eq!(call(src, "f", 4), 4);
+}
+#[test]
+fn defer_before_return() {
+    assert_clean("fn f(): i32 = {mut x: i32 = 1; defer x = 2; return x};");
+}
+
+// borrows
+#[test]
+fn assign_while_borrowed() {
+    assert_error("fn f(): i32 = {mut x: i32 = 1; let r: i32& = x; x = 2; r};", 370);
+}
+#[test]
+fn two_mutable_references() {
+    assert_error("fn f(): i32 = {mut x: i32 = 1; let a: i32 mut & = x; let b: i32 mut & = x; a};", 370);
+}
+#[test]
+fn borrow_ends_with_block() {
+    let src = "@cconv(c) fn f(x: i32): i32 = {mut y: i32 = 1; {let r: i32& = y;}; y = x; y};";
+    assert_eq!(call(src, "f", 4), 4);
+}
+#[test]
+fn reference_outlives_local() {
+    assert_error("fn f(): i32& = {let x: i32 = 1; let r: i32& = x; return r};", 371);
+}
+
+// optionals
+#[test]
+fn question_outside_optional_function() {
+    assert_error("fn f(x: i32?): i32 = x?;", 372);
+}
+#[test]
+fn question_propagates_and_bang_unwraps() {
+    let src = "fn get(x: i32): i32? = {if (x <= 0) return null; x}; fn add1(x: i32): i32? = get(x)? + 1; @cconv(c) fn f(x: i32): i32 = add1(x)!;";
+    assert_eq!(call(src, "f", 3), 4);
+}
+#[test]
+fn empty_optional_unwrap_traps() {
+    assert!(ir("fn f(x: i32?): i32 = x!;").contains("@llvm.trap"));
+}
+#[test]
+fn optional_pointer_uses_null_niche() {
+    assert!(ir("fn f(p: i32*): i32*? = p;").contains("i32* @f("));
+    assert!(ir("fn g(x: i32&): i32&? = x;").contains("i32* @g("));
+}